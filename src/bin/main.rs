@@ -1,35 +1,78 @@
-use std::{fs::File, io::BufWriter, path::Path, rc::Rc, time::Instant};
+use std::{fs::File, io::BufWriter, num::NonZeroUsize, path::Path, sync::Arc, time::Instant};
 
 use png::{ColorType, Encoder};
 use raytracing::{
     bvh_tree::bvh_node::BVHNode,
     camera::Camera,
     hits::{
-        constant_medium::ConstantMedium, hittable::Hittable, rotate::RotateY, translate::Translate,
+        constant_medium::ConstantMedium, csg::Csg, heterogeneous_medium::HeterogeneousMedium,
+        hittable::Hittable, rotate::RotateY, translate::Translate,
     },
+    integrators::{bdpt::Bdpt, mlt::Mlt, sppm::Sppm},
     materials::{
-        dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal,
-        Material,
+        dielectric::Dielectric, diffuse_light::DiffuseLight, dispersion::Dispersion, hair::Hair,
+        henyey_greenstein::HenyeyGreenstein, interior::Interior, lambertian::Lambertian,
+        layered::Layered, metal::Metal, mix_material::MixMaterial, Material,
     },
+    noise::{fractal::Fractal, gradient::GradientNoise},
     objects::{
         aa_rect::{XYRect, XZRect, YZRect},
         block::Block,
+        capsule::Capsule,
+        cone::Cone,
+        curve::{Curve, CurveType},
+        cylinder::Cylinder,
+        disk::Disk,
+        hyperboloid::Hyperboloid,
+        mesh::Mesh,
         moving_sphere::MovingSphere,
+        paraboloid::Paraboloid,
+        plane::Plane,
+        point_cloud::{CloudPoint, PointCloud, PointShape},
+        quad::Quad,
+        sdf_object::SdfObject,
         sphere::Sphere,
+        subdivision::{ControlMesh, SubdivisionScheme},
+        torus::Torus,
+    },
+    random_f64, random_f64_between, ray_color_spectral_with_limits, ray_color_with_limits,
+    sdf::{
+        operations::{Displacement, Repetition, SmoothUnion, Twist},
+        primitives::{BoxSdf, CapsuleSdf, SphereSdf, TorusSdf},
+        Sdf,
     },
-    random_f64, random_f64_between, ray_color,
     textures::{
-        checker_texture::CheckerTexture, image_texture::ImageTexture, noise_texture::NoiseTexture,
+        checker_texture::CheckerTexture,
+        image_texture::ImageTexture,
+        noise_texture::{NoiseTexture, Perlin},
+        procedural::{CloudTexture, GraniteTexture, MarbleTexture, WoodTexture},
+        Texture,
     },
-    vec3::{random_vector, random_vector_in_range, Color, Point3, Vec3},
-    write_color,
+    vec3::{random_unit_vector, random_vector, random_vector_in_range, Color, Point3, Vec3},
+    volumes::noise_density::NoiseDensity,
+    write_color, DepthLimits,
 };
 
+#[allow(dead_code)]
+enum Integrator {
+    PathTracing,
+    Bidirectional,
+    // Runs one iteration per sample, `initial_radius` is in scene units.
+    ProgressivePhotonMapping {
+        initial_radius: f64,
+        photons_per_iteration: usize,
+    },
+    Metropolis {
+        bootstrap_samples: usize,
+        chains: NonZeroUsize,
+    },
+}
+
 #[allow(dead_code)]
 fn random_scene(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     let mut world: Vec<Box<dyn Hittable>> = vec![];
 
-    let checker = Rc::new(CheckerTexture::new_from_color(
+    let checker = Arc::new(CheckerTexture::new_from_color(
         Color::new(0.2, 0.3, 0.1),
         Color::new(0.9, 0.9, 0.9),
     ));
@@ -38,7 +81,7 @@ fn random_scene(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     world.push(Box::new(Sphere::new(
         Point3::new(0.0, -1000.0, 0.0),
         1000.0,
-        Rc::new(ground_material),
+        Arc::new(ground_material),
     )));
 
     for a in -11..11 {
@@ -60,18 +103,26 @@ fn random_scene(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
                         (center, center2),
                         (0.0, 1.0),
                         0.2,
-                        Rc::new(sphere_material),
+                        Arc::new(sphere_material),
                     )));
                 } else if choose_material < 0.95 {
                     //metal
                     let albedo: Color = random_vector_in_range(0.5, 1.0);
                     let fuzz = random_f64_between(0.0, 0.5);
                     let sphere_material = Metal::new(albedo, fuzz);
-                    world.push(Box::new(Sphere::new(center, 0.2, Rc::new(sphere_material))));
+                    world.push(Box::new(Sphere::new(
+                        center,
+                        0.2,
+                        Arc::new(sphere_material),
+                    )));
                 } else {
                     //glass
                     let sphere_material = Dielectric::new(1.5);
-                    world.push(Box::new(Sphere::new(center, 0.2, Rc::new(sphere_material))));
+                    world.push(Box::new(Sphere::new(
+                        center,
+                        0.2,
+                        Arc::new(sphere_material),
+                    )));
                 }
             }
         }
@@ -81,21 +132,21 @@ fn random_scene(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     world.push(Box::new(Sphere::new(
         Point3::new(0.0, 1.0, 0.0),
         1.0,
-        Rc::new(material1),
+        Arc::new(material1),
     )));
 
     let material2 = Lambertian::new(Color::new(0.4, 0.2, 0.1));
     world.push(Box::new(Sphere::new(
         Point3::new(-4.0, 1.0, 0.0),
         1.0,
-        Rc::new(material2),
+        Arc::new(material2),
     )));
 
     let material3 = Metal::new(Color::new(0.7, 0.8, 0.5), 0.0);
     world.push(Box::new(Sphere::new(
         Point3::new(4.0, 1.0, 0.0),
         1.0,
-        Rc::new(material3),
+        Arc::new(material3),
     )));
 
     (
@@ -118,11 +169,11 @@ fn random_scene(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
 fn two_spheres(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     let mut world: Vec<Box<dyn Hittable>> = vec![];
 
-    let checker = Rc::new(CheckerTexture::new_from_color(
+    let checker = Arc::new(CheckerTexture::new_from_color(
         Color::new(0.2, 0.3, 0.1),
         Color::new(0.9, 0.9, 0.9),
     ));
-    let checker_material: Rc<dyn Material> = Rc::new(Lambertian::new_from_texture(checker));
+    let checker_material: Arc<dyn Material> = Arc::new(Lambertian::new_from_texture(checker));
 
     world.push(Box::new(Sphere::new(
         Point3::new(0.0, -10.0, 0.0),
@@ -155,8 +206,8 @@ fn two_spheres(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
 fn two_perlin_spheres(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     let mut world: Vec<Box<dyn Hittable>> = vec![];
 
-    let pertext = Rc::new(NoiseTexture::new(4.0));
-    let pertext_material: Rc<dyn Material> = Rc::new(Lambertian::new_from_texture(pertext));
+    let pertext = Arc::new(NoiseTexture::new(4.0));
+    let pertext_material: Arc<dyn Material> = Arc::new(Lambertian::new_from_texture(pertext));
 
     world.push(Box::new(Sphere::new(
         Point3::new(0.0, -1000.0, 0.0),
@@ -189,8 +240,9 @@ fn two_perlin_spheres(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
 fn earth(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     let mut globe: Vec<Box<dyn Hittable>> = vec![];
 
-    let earth_texture = Rc::new(ImageTexture::new("earthmap.jpg"));
-    let earth_surface: Rc<dyn Material> = Rc::new(Lambertian::new_from_texture(earth_texture));
+    let earth_texture =
+        Arc::new(ImageTexture::new("earthmap.jpg").expect("could not load earthmap.jpg"));
+    let earth_surface: Arc<dyn Material> = Arc::new(Lambertian::new_from_texture(earth_texture));
 
     globe.push(Box::new(Sphere::new(
         Point3::new(0.0, 0.0, 0.0),
@@ -218,8 +270,8 @@ fn earth(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
 fn simple_light(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     let mut objects: Vec<Box<dyn Hittable>> = vec![];
 
-    let noise_texture = Rc::new(NoiseTexture::new(4.0));
-    let metal = Rc::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.1));
+    let noise_texture = Arc::new(NoiseTexture::new(4.0));
+    let metal = Arc::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.1));
 
     objects.push(Box::new(Sphere::new(
         Point3::new(0.0, -1000.0, 0.0),
@@ -230,10 +282,10 @@ fn simple_light(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     objects.push(Box::new(Sphere::new(
         Point3::new(0.0, 2.0, 0.0),
         2.0,
-        Rc::new(Lambertian::new_from_texture(noise_texture)),
+        Arc::new(Lambertian::new_from_texture(noise_texture)),
     )));
 
-    let difflight = Rc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
+    let difflight = Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
     objects.push(Box::new(XYRect::new(
         (3.0, 5.0),
         (1.0, 3.0),
@@ -241,7 +293,7 @@ fn simple_light(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
         difflight,
     )));
 
-    let redlight = Rc::new(DiffuseLight::new(Color::new(10.0, 2.0, 2.0)));
+    let redlight = Arc::new(DiffuseLight::new(Color::new(10.0, 2.0, 2.0)));
     objects.push(Box::new(Sphere::new(
         Point3::new(0.0, 7.0, 0.0),
         2.0,
@@ -268,10 +320,10 @@ fn simple_light(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
 fn cornell_box(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     let mut objects: Vec<Box<dyn Hittable>> = vec![];
 
-    let red = Rc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
-    let white = Rc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
-    let green = Rc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
-    let light = Rc::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0)));
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0)));
 
     objects.push(Box::new(YZRect::new(
         (0.0, 555.0),
@@ -340,13 +392,13 @@ fn cornell_box(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
 }
 
 #[allow(dead_code)]
-fn cornell_smoke(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+fn cornell_caustics(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     let mut objects: Vec<Box<dyn Hittable>> = vec![];
 
-    let red = Rc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
-    let white = Rc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
-    let green = Rc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
-    let light = Rc::new(DiffuseLight::new(Color::new(7.0, 7.0, 7.0)));
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Color::new(60.0, 60.0, 60.0)));
 
     objects.push(Box::new(YZRect::new(
         (0.0, 555.0),
@@ -356,8 +408,8 @@ fn cornell_smoke(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
     )));
     objects.push(Box::new(YZRect::new((0.0, 555.0), (0.0, 555.0), 0.0, red)));
     objects.push(Box::new(XZRect::new(
-        (113.0, 443.0),
-        (127.0, 432.0),
+        (253.0, 303.0),
+        (255.0, 305.0),
         554.0,
         light,
     )));
@@ -377,33 +429,20 @@ fn cornell_smoke(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
         (0.0, 555.0),
         (0.0, 555.0),
         555.0,
-        white.clone(),
+        white,
     )));
 
-    let block1 = Block::new(
-        Point3::new(0.0, 0.0, 0.0),
-        Point3::new(165.0, 165.0, 165.0),
-        white.clone(),
-    );
-    let block1 = RotateY::new(Box::new(block1), -18.0);
-    let block1 = Translate::new(Box::new(block1), Vec3::new(130.0, 0.0, 65.0));
-    objects.push(Box::new(ConstantMedium::new_from_color(
-        Box::new(block1),
-        0.01,
-        Color::new(0.0, 0.0, 0.0),
+    // The small light focused through glass is what unidirectional path
+    // tracing struggles with.
+    objects.push(Box::new(Sphere::new(
+        Point3::new(190.0, 100.0, 190.0),
+        100.0,
+        Arc::new(Dielectric::new(1.5)),
     )));
-
-    let block2 = Block::new(
-        Point3::new(0.0, 0.0, 0.0),
-        Point3::new(165.0, 330.0, 165.0),
-        white,
-    );
-    let block2 = RotateY::new(Box::new(block2), 15.0);
-    let block2 = Translate::new(Box::new(block2), Vec3::new(265.0, 0.0, 295.0));
-    objects.push(Box::new(ConstantMedium::new_from_color(
-        Box::new(block2),
-        0.01,
-        Color::new(1.0, 1.0, 1.0),
+    objects.push(Box::new(Sphere::new(
+        Point3::new(390.0, 90.0, 370.0),
+        90.0,
+        Arc::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.0)),
     )));
 
     (
@@ -415,7 +454,7 @@ fn cornell_smoke(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
             40.0,
             aspect_ratio,
             0.0,
-            20.0,
+            10.0,
             (0.0, 1.0),
         ),
         Color::default(),
@@ -423,192 +462,1325 @@ fn cornell_smoke(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
 }
 
 #[allow(dead_code)]
-fn final_scene(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
-    // Ground
-    let mut boxes1: Vec<Box<dyn Hittable>> = vec![];
-    let ground = Rc::new(Lambertian::new(Color::new(0.48, 0.83, 0.53)));
-
-    let boxes_per_side = 20;
-    for i in 0..boxes_per_side {
-        for j in 0..boxes_per_side {
-            let w = 100.0;
-            let x0 = -1000.0 + f64::from(i) * w;
-            let z0 = -1000.0 + f64::from(j) * w;
-            let y0 = 0.0;
-
-            let x1 = x0 + w;
-            let y1 = random_f64_between(1.0, 101.0);
-            let z1 = z0 + w;
-
-            boxes1.push(Box::new(Block::new(
-                Point3::new(x0, y0, z0),
-                Point3::new(x1, y1, z1),
-                ground.clone(),
-            )));
-        }
-    }
+fn planar_shapes(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
 
-    let mut objects: Vec<Box<dyn Hittable>> = vec![Box::new(BVHNode::new(boxes1, (0.0, 1.0)))];
+    let checker = Arc::new(CheckerTexture::new_from_color(
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let light = Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
 
-    // Light
-    let light = Rc::new(DiffuseLight::new(Color::new(7.0, 7.0, 7.0)));
-    objects.push(Box::new(XZRect::new(
-        (123.0, 423.0),
-        (147.0, 412.0),
-        554.0,
-        light,
+    objects.push(Box::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new_from_texture(checker)),
     )));
 
-    // Moving Sphere
-    let center1 = Point3::new(400.0, 400.0, 200.0);
-    let center2 = center1 + Vec3::new(30.0, 0.0, 0.0);
-    let moving_sphere_material = Rc::new(Lambertian::new(Color::new(0.7, 0.3, 0.1)));
-    objects.push(Box::new(MovingSphere::new(
-        (center1, center2),
-        (0.0, 1.0),
-        50.0,
-        moving_sphere_material,
+    // Panels leaning in different directions.
+    objects.push(Box::new(Quad::new(
+        Point3::new(-3.0, 0.0, -1.0),
+        Vec3::new(0.0, 0.0, 2.0),
+        Vec3::new(-0.8, 2.0, 0.0),
+        Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05))),
     )));
-
-    // Metal and Glas Sphere
-    objects.push(Box::new(Sphere::new(
-        Point3::new(260.0, 150.0, 45.0),
-        70.0,
-        Rc::new(Dielectric::new(1.5)),
+    objects.push(Box::new(Quad::new_from_corners(
+        Point3::new(-1.0, 0.0, -2.5),
+        Point3::new(1.5, 0.0, -2.5),
+        Point3::new(-0.5, 2.5, -3.2),
+        Arc::new(Metal::new(Color::new(0.8, 0.85, 0.9), 0.05)),
+    )));
+    objects.push(Box::new(Disk::new(
+        Point3::new(2.2, 1.0, 0.0),
+        Vec3::new(-1.0, 0.3, 0.6),
+        1.0,
+        Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15))),
     )));
     objects.push(Box::new(Sphere::new(
-        Point3::new(0.0, 150.0, 145.0),
-        50.0,
-        Rc::new(Metal::new(Color::new(0.8, 0.8, 0.9), 1.0)),
+        Point3::new(0.0, 0.7, 0.0),
+        0.7,
+        Arc::new(Dielectric::new(1.5)),
     )));
 
-    // ConstantMediums
-    let boundary = Box::new(Sphere::new(
-        Point3::new(360.0, 150.0, 145.0),
-        70.0,
-        Rc::new(Dielectric::new(1.5)),
-    ));
-    objects.push(boundary);
-    let boundary = Box::new(Sphere::new(
-        Point3::new(360.0, 150.0, 145.0),
-        70.0,
-        Rc::new(Dielectric::new(1.5)),
+    // A tilted panel light and a disk light, both sampled directly.
+    objects.push(Box::new(Quad::new(
+        Point3::new(-1.0, 4.0, -1.0),
+        Vec3::new(2.0, 0.5, 0.0),
+        Vec3::new(0.0, 0.0, 2.0),
+        light.clone(),
+    )));
+    objects.push(Box::new(Disk::new(
+        Point3::new(2.5, 3.0, 2.0),
+        Vec3::new(-0.5, -1.0, -0.4),
+        0.5,
+        light,
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(0.0, 2.5, 9.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            10.0,
+            (0.0, 1.0),
+        ),
+        Color::new(0.05, 0.05, 0.08),
+    )
+}
+
+#[allow(dead_code)]
+fn analytic_shapes(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
+
+    let checker = Arc::new(CheckerTexture::new_from_color(
+        Color::new(0.2, 0.2, 0.2),
+        Color::new(0.9, 0.9, 0.9),
     ));
-    objects.push(Box::new(ConstantMedium::new_from_color(
-        boundary,
-        0.2,
-        Color::new(0.2, 0.4, 0.9),
+    let uv_checker: Arc<dyn Material> = Arc::new(Lambertian::new_from_texture(Arc::new(
+        CheckerTexture::new_from_color(Color::new(0.8, 0.3, 0.1), Color::new(0.9, 0.9, 0.8)),
     )));
-    let boundary = Box::new(Sphere::new(
+    let steel = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.85), 0.2));
+    let light = Arc::new(DiffuseLight::new(Color::new(6.0, 6.0, 6.0)));
+
+    objects.push(Box::new(Plane::new(
         Point3::new(0.0, 0.0, 0.0),
-        5000.0,
-        Rc::new(Dielectric::new(1.5)),
-    ));
-    objects.push(Box::new(ConstantMedium::new_from_color(
-        boundary,
-        0.0001,
-        Color::new(1.0, 1.0, 1.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new_from_texture(checker)),
     )));
 
-    // Earth
-    let earth_mat = Rc::new(Lambertian::new_from_texture(Rc::new(ImageTexture::new(
-        "earthmap.jpg",
-    ))));
-    objects.push(Box::new(Sphere::new(
-        Point3::new(400.0, 200.0, 400.0),
-        100.0,
-        earth_mat,
+    objects.push(Box::new(Cylinder::new(
+        Point3::new(-3.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.6, 0.0),
+        0.6,
+        uv_checker.clone(),
+    )));
+    objects.push(Box::new(Cylinder::new_uncapped(
+        Point3::new(-3.2, 0.5, 2.0),
+        Vec3::new(1.2, 0.3, -0.4),
+        0.4,
+        steel.clone(),
+    )));
+    objects.push(Box::new(Cone::new(
+        Point3::new(-1.2, 0.0, -1.0),
+        Vec3::new(0.0, 2.0, 0.0),
+        0.7,
+        Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15))),
+    )));
+    objects.push(Box::new(Torus::new(
+        Point3::new(0.6, 0.9, 0.5),
+        Vec3::new(0.3, 1.0, 0.6),
+        0.7,
+        0.25,
+        steel,
+    )));
+    objects.push(Box::new(Capsule::new(
+        Point3::new(2.0, 0.5, -1.2),
+        Vec3::new(0.8, 1.2, 0.0),
+        0.45,
+        uv_checker,
+    )));
+    objects.push(Box::new(Paraboloid::new(
+        Point3::new(3.4, 0.0, 1.0),
+        Vec3::new(0.0, 1.5, 0.0),
+        0.8,
+        Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05))),
+    )));
+    objects.push(Box::new(Hyperboloid::new(
+        Point3::new(0.5, 0.0, -3.0),
+        Vec3::new(0.0, 2.5, 0.0),
+        0.4,
+        0.9,
+        Arc::new(Lambertian::new(Color::new(0.2, 0.3, 0.7))),
     )));
 
-    // Perlin Sphere
-    let pertext = Rc::new(NoiseTexture::new(0.1));
-    objects.push(Box::new(Sphere::new(
-        Point3::new(220.0, 280.0, 300.0),
-        80.0,
-        Rc::new(Lambertian::new_from_texture(pertext)),
+    objects.push(Box::new(Quad::new(
+        Point3::new(-2.0, 6.0, -1.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 3.0),
+        light,
     )));
 
-    // Translation + Rotation
-    let mut boxes2: Vec<Box<dyn Hittable>> = vec![];
-    let white = Rc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
-    let ns = 1_000;
-    for _ in 0..ns {
-        boxes2.push(Box::new(Sphere::new(
-            random_vector_in_range(0.0, 165.0),
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(0.0, 3.5, 9.0),
+            Point3::new(0.0, 0.8, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
             10.0,
-            white.clone(),
-        )));
-    }
+            (0.0, 1.0),
+        ),
+        Color::new(0.1, 0.1, 0.12),
+    )
+}
+
+#[allow(dead_code)]
+fn csg_shapes(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
+
+    let checker = Arc::new(CheckerTexture::new_from_color(
+        Color::new(0.2, 0.2, 0.2),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let blue = Arc::new(Lambertian::new(Color::new(0.2, 0.3, 0.7)));
+    let gold = Arc::new(Metal::new(Color::new(0.9, 0.7, 0.3), 0.1));
+    let light = Arc::new(DiffuseLight::new(Color::new(6.0, 6.0, 6.0)));
+
+    objects.push(Box::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new_from_texture(checker)),
+    )));
 
+    // A cube with its corners rounded off by a sphere, and a spherical
+    // bite taken out of the front.
+    let rounded = Csg::new_intersection(
+        Box::new(Block::new(
+            Point3::new(-1.0, 0.0, -1.0),
+            Point3::new(1.0, 2.0, 1.0),
+            red.clone(),
+        )),
+        Box::new(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.35, red)),
+    );
     objects.push(Box::new(Translate::new(
         Box::new(RotateY::new(
-            Box::new(BVHNode::new(boxes2, (0.0, 1.0))),
-            15.0,
+            Box::new(Csg::new_difference(
+                Box::new(rounded),
+                Box::new(Sphere::new(Point3::new(0.0, 1.4, 1.0), 0.6, blue.clone())),
+            )),
+            30.0,
         )),
-        Vec3::new(-100.0, 270.0, 395.0),
+        Vec3::new(-2.2, 0.0, 0.0),
+    )));
+
+    // A glass lens where two spheres overlap.
+    objects.push(Box::new(Csg::new_intersection(
+        Box::new(Sphere::new(
+            Point3::new(0.0, 1.2, -1.2),
+            1.5,
+            Arc::new(Dielectric::new(1.5)),
+        )),
+        Box::new(Sphere::new(
+            Point3::new(0.0, 1.2, 1.2),
+            1.5,
+            Arc::new(Dielectric::new(1.5)),
+        )),
+    )));
+
+    // A sphere and a cylinder merged, with a hole drilled through both.
+    let merged = Csg::new_union(
+        Box::new(Sphere::new(Point3::new(2.4, 1.0, 0.0), 0.9, gold.clone())),
+        Box::new(Cylinder::new(
+            Point3::new(2.4, 0.0, 0.0),
+            Vec3::new(0.0, 2.4, 0.0),
+            0.45,
+            gold,
+        )),
+    );
+    objects.push(Box::new(Csg::new_difference(
+        Box::new(merged),
+        Box::new(Cylinder::new(
+            Point3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.8, 0.0, 0.0),
+            0.3,
+            blue,
+        )),
+    )));
+
+    objects.push(Box::new(Quad::new(
+        Point3::new(-2.0, 6.0, -1.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 3.0),
+        light,
     )));
 
     (
         BVHNode::new(objects, (0.0, 1.0)),
         Camera::new(
-            Vec3::new(478.0, 278.0, -600.0),
-            Vec3::new(278.0, 278.0, 0.0),
-            Vec3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 3.0, 9.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
             40.0,
             aspect_ratio,
             0.0,
-            20.0,
+            10.0,
             (0.0, 1.0),
         ),
-        Color::default(),
+        Color::new(0.1, 0.1, 0.12),
     )
 }
 
-fn main() {
-    // Image
-    let aspect_ratio = 1.0;
-    let image_width: u32 = 400;
-    let image_height: u32 = (f64::from(image_width) / aspect_ratio) as u32;
-    let samples_per_pixel: u32 = 100;
-    let max_depth = 50;
+#[allow(dead_code)]
+fn sdf_shapes(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
 
-    // World + Camera
-    let (world, camera, background) = cornell_box(aspect_ratio);
+    let checker = Arc::new(CheckerTexture::new_from_color(
+        Color::new(0.2, 0.2, 0.2),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let light = Arc::new(DiffuseLight::new(Color::new(6.0, 6.0, 6.0)));
 
-    // PNG File
-    let mut data: Vec<u8> = Vec::with_capacity((3 * image_width * image_height) as usize);
+    objects.push(Box::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new_from_texture(checker)),
+    )));
 
-    let path = Path::new(r"images\test.png");
-    let file = File::create(path).unwrap();
-    let w = &mut BufWriter::new(file);
+    // A sphere melting into a ring.
+    let blob: Arc<dyn Sdf> = Arc::new(SmoothUnion::new(
+        Arc::new(SphereSdf::new(Point3::new(-2.4, 1.3, 0.0), 0.6)),
+        Arc::new(TorusSdf::new(Point3::new(-2.4, 0.7, 0.0), 0.8, 0.2)),
+        0.5,
+    ));
+    objects.push(Box::new(SdfObject::new(
+        blob,
+        Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05))),
+    )));
 
-    let mut encoder = Encoder::new(w, image_width, image_height);
-    encoder.set_color(ColorType::Rgb);
-    let mut writer = encoder.write_header().unwrap();
+    // A twisted column, moved into place after twisting around the y axis.
+    let column: Arc<dyn Sdf> = Arc::new(Twist::new(
+        Arc::new(BoxSdf::new(
+            Point3::new(0.0, 1.2, 0.0),
+            Vec3::new(0.45, 1.2, 0.45),
+            0.05,
+        )),
+        1.2,
+    ));
+    objects.push(Box::new(Translate::new(
+        Box::new(SdfObject::new(
+            column,
+            Arc::new(Metal::new(Color::new(0.9, 0.7, 0.3), 0.1)),
+        )),
+        Vec3::new(0.0, 0.0, -0.5),
+    )));
 
-    // Render
-    let start = Instant::now();
+    // A row of pills.
+    let pills: Arc<dyn Sdf> = Arc::new(Repetition::new(
+        Arc::new(CapsuleSdf::new(
+            Point3::new(0.0, 0.15, -0.2),
+            Point3::new(0.0, 0.15, 0.2),
+            0.15,
+        )),
+        Vec3::new(0.5, 0.0, 0.0),
+        (3, 0, 0),
+    ));
+    objects.push(Box::new(Translate::new(
+        Box::new(SdfObject::new(
+            pills,
+            Arc::new(Lambertian::new(Color::new(0.2, 0.3, 0.7))),
+        )),
+        Vec3::new(0.0, 0.0, 1.8),
+    )));
 
-    for j in (0..image_height).rev() {
-        eprint!(
-            "\r{} / {} lines rendered...",
-            image_height - j,
-            image_height
-        );
-        //eprint!("\rProgress: {}%", ((image_height - j) * 100) / image_height);
-        for i in 0..image_width {
-            let mut pixel_color = Color::default();
-            for _ in 0..samples_per_pixel {
-                let u = (f64::from(i) + random_f64()) / f64::from(image_width - 1);
-                let v = (f64::from(j) + random_f64()) / f64::from(image_height - 1);
-                let r = camera.get_ray(u, v);
-
-                pixel_color += ray_color(r, &background, &world, max_depth);
-                if j == 0 && i == 0 {
-                    pixel_color = Color::new(1.0, 0.0, 0.0);
-                }
-            }
-            write_color(&mut data, pixel_color, samples_per_pixel);
+    // A lumpy rock.
+    let rock: Arc<dyn Sdf> = Arc::new(Displacement::new(
+        Arc::new(SphereSdf::new(Point3::new(2.4, 0.8, 0.0), 0.7)),
+        Arc::new(Perlin::new()),
+        3.0,
+        0.15,
+    ));
+    objects.push(Box::new(SdfObject::new(
+        rock,
+        Arc::new(Lambertian::new(Color::new(0.5, 0.45, 0.4))),
+    )));
+
+    objects.push(Box::new(Quad::new(
+        Point3::new(-2.0, 6.0, -1.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 3.0),
+        light,
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(0.0, 3.0, 9.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            10.0,
+            (0.0, 1.0),
+        ),
+        Color::new(0.1, 0.1, 0.12),
+    )
+}
+
+// A cube cage from `min` to `max` with outward facing quads.
+fn cube_cage(min: Point3, max: Point3) -> ControlMesh {
+    let positions = (0..8)
+        .map(|i| {
+            Point3::new(
+                if i & 1 == 0 { min.x() } else { max.x() },
+                if i & 2 == 0 { min.y() } else { max.y() },
+                if i & 4 == 0 { min.z() } else { max.z() },
+            )
+        })
+        .collect();
+    let faces = vec![
+        vec![0, 2, 3, 1],
+        vec![4, 5, 7, 6],
+        vec![0, 1, 5, 4],
+        vec![2, 6, 7, 3],
+        vec![0, 4, 6, 2],
+        vec![1, 3, 7, 5],
+    ];
+    ControlMesh::new(positions, faces).unwrap()
+}
+
+#[allow(dead_code)]
+fn subdivision_surfaces(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
+
+    let checker = Arc::new(CheckerTexture::new_from_color(
+        Color::new(0.2, 0.2, 0.2),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let light = Arc::new(DiffuseLight::new(Color::new(6.0, 6.0, 6.0)));
+
+    objects.push(Box::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new_from_texture(checker)),
+    )));
+
+    // A cube smoothed into a blob.
+    objects.push(Box::new(Mesh::new_subdivided(
+        &cube_cage(Point3::new(-3.2, 0.0, -0.8), Point3::new(-1.6, 1.6, 0.8)),
+        SubdivisionScheme::CatmullClark,
+        4,
+        Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05))),
+    )));
+
+    // The same cube with its top rim sharp and the vertical edges creased
+    // for two levels.
+    let mut creased = cube_cage(Point3::new(-0.8, 0.0, -0.8), Point3::new(0.8, 1.6, 0.8));
+    for (a, b) in [(2, 3), (3, 7), (7, 6), (6, 2)] {
+        creased.set_crease(a, b, f64::INFINITY);
+    }
+    for (a, b) in [(0, 2), (1, 3), (5, 7), (4, 6)] {
+        creased.set_crease(a, b, 2.0);
+    }
+    objects.push(Box::new(Mesh::new_subdivided(
+        &creased,
+        SubdivisionScheme::CatmullClark,
+        4,
+        Arc::new(Metal::new(Color::new(0.9, 0.7, 0.3), 0.1)),
+    )));
+
+    // An octahedron refined with Loop subdivision.
+    let octahedron = ControlMesh::new(
+        vec![
+            Point3::new(2.4, 0.1, 0.0),
+            Point3::new(3.4, 1.1, 0.0),
+            Point3::new(2.4, 1.1, 1.0),
+            Point3::new(1.4, 1.1, 0.0),
+            Point3::new(2.4, 1.1, -1.0),
+            Point3::new(2.4, 2.1, 0.0),
+        ],
+        vec![
+            vec![0, 1, 2],
+            vec![0, 2, 3],
+            vec![0, 3, 4],
+            vec![0, 4, 1],
+            vec![5, 2, 1],
+            vec![5, 3, 2],
+            vec![5, 4, 3],
+            vec![5, 1, 4],
+        ],
+    )
+    .unwrap();
+    objects.push(Box::new(Mesh::new_subdivided(
+        &octahedron,
+        SubdivisionScheme::Loop,
+        4,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+
+    objects.push(Box::new(Quad::new(
+        Point3::new(-2.0, 6.0, -1.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 3.0),
+        light,
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(0.0, 3.0, 9.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            10.0,
+            (0.0, 1.0),
+        ),
+        Color::new(0.1, 0.1, 0.12),
+    )
+}
+
+#[allow(dead_code)]
+fn curves_and_hair(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
+
+    objects.push(Box::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new(Color::new(0.35, 0.3, 0.25))),
+    )));
+
+    // A furry ball: strands leaving a sphere along its normal and drooping
+    // under their own weight.
+    let center = Point3::new(-1.3, 0.9, 0.0);
+    let fur: Arc<dyn Material> = Arc::new(Hair::new_from_melanin(0.4, 0.6, 0.25, 0.3));
+    objects.push(Box::new(Sphere::new(
+        center,
+        0.6,
+        Arc::new(Lambertian::new(Color::new(0.1, 0.05, 0.02))),
+    )));
+    for _ in 0..3000 {
+        let normal = random_unit_vector();
+        let root = center + 0.6 * normal;
+        let length = random_f64_between(0.25, 0.35);
+        let droop = Vec3::new(0.0, -0.5 * length, 0.0);
+        let control = [
+            root,
+            root + length / 3.0 * normal,
+            root + 2.0 * length / 3.0 * normal + 0.4 * droop,
+            root + length * normal + droop,
+        ];
+        for segment in
+            Curve::new(control, (0.012, 0.002), CurveType::Cylinder, fur.clone()).split(2)
+        {
+            objects.push(Box::new(segment));
+        }
+    }
+
+    // A tuft of grass blades, twisting ribbons that bend to one side.
+    let grass: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.2, 0.5, 0.1)));
+    for _ in 0..60 {
+        let root = Point3::new(
+            random_f64_between(0.3, 1.1),
+            0.0,
+            random_f64_between(-0.4, 0.4),
+        );
+        let height = random_f64_between(0.8, 1.4);
+        let bend = Vec3::new(
+            random_f64_between(0.1, 0.5),
+            0.0,
+            random_f64_between(-0.2, 0.2),
+        );
+        let control = [
+            root,
+            root + Vec3::new(0.0, height / 3.0, 0.0),
+            root + Vec3::new(0.0, 2.0 * height / 3.0, 0.0) + 0.3 * bend,
+            root + Vec3::new(0.0, height, 0.0) + bend,
+        ];
+        let facing = random_f64_between(0.0, 2.0 * std::f64::consts::PI);
+        let normals = CurveType::Ribbon(
+            Vec3::new(facing.cos(), 0.0, facing.sin()),
+            Vec3::new((facing + 1.2).cos(), 0.0, (facing + 1.2).sin()),
+        );
+        for segment in Curve::new(control, (0.08, 0.0), normals, grass.clone()).split(4) {
+            objects.push(Box::new(segment));
+        }
+    }
+
+    // A cable arching over the ground.
+    let cable = Curve::new(
+        [
+            Point3::new(1.6, 0.0, -0.6),
+            Point3::new(1.6, 1.6, -0.4),
+            Point3::new(2.6, 1.6, 0.4),
+            Point3::new(2.6, 0.0, 0.6),
+        ],
+        (0.12, 0.12),
+        CurveType::Cylinder,
+        Arc::new(Metal::new(Color::new(0.8, 0.3, 0.2), 0.2)),
+    );
+    for segment in cable.split(16) {
+        objects.push(Box::new(segment));
+    }
+
+    objects.push(Box::new(Quad::new(
+        Point3::new(-2.0, 6.0, -1.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 3.0),
+        Arc::new(DiffuseLight::new(Color::new(6.0, 6.0, 6.0))),
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(0.0, 2.0, 7.0),
+            Point3::new(0.3, 0.7, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            10.0,
+            (0.0, 1.0),
+        ),
+        Color::new(0.1, 0.1, 0.12),
+    )
+}
+
+#[allow(dead_code)]
+fn particle_cloud(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
+
+    objects.push(Box::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+
+    // A spiral galaxy of small spheres, blue at the rim and yellow in the
+    // middle.
+    let mut stars = vec![];
+    for _ in 0..500_000 {
+        let arm = f64::from(random_f64() < 0.5) * std::f64::consts::PI;
+        let distance = random_f64().powi(2) * 2.5;
+        let angle = arm + 2.5 * distance + 0.4 * random_f64_between(-1.0, 1.0);
+        let height = 0.15 * random_f64_between(-1.0, 1.0) * (-distance).exp();
+        let fraction = distance / 2.5;
+        stars.push(CloudPoint::new(
+            Point3::new(distance * angle.cos(), 1.4 + height, distance * angle.sin()),
+            random_f64_between(0.004, 0.012),
+            Color::new(
+                1.0 - 0.7 * fraction,
+                0.8 - 0.3 * fraction,
+                0.3 + 0.7 * fraction,
+            ),
+        ));
+    }
+    objects.push(Box::new(PointCloud::new(
+        stars,
+        PointShape::Sphere,
+        |color| Arc::new(Lambertian::new(color)),
+    )));
+
+    // Splats scattered over the ground.
+    let splats = (0..2000)
+        .map(|_| {
+            CloudPoint::new(
+                Point3::new(
+                    random_f64_between(-3.0, 3.0),
+                    0.05,
+                    random_f64_between(-3.0, 2.0),
+                ),
+                0.04,
+                Color::new(0.8, 0.1, 0.1),
+            )
+        })
+        .collect();
+    objects.push(Box::new(PointCloud::new(
+        splats,
+        PointShape::Disc,
+        |color| Arc::new(Lambertian::new(color)),
+    )));
+
+    objects.push(Box::new(Quad::new(
+        Point3::new(-2.0, 6.0, -1.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 3.0),
+        Arc::new(DiffuseLight::new(Color::new(6.0, 6.0, 6.0))),
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(0.0, 4.0, 6.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            10.0,
+            (0.0, 1.0),
+        ),
+        Color::new(0.05, 0.05, 0.08),
+    )
+}
+
+#[allow(dead_code)]
+fn cornell_smoke(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
+
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Color::new(7.0, 7.0, 7.0)));
+
+    objects.push(Box::new(YZRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        555.0,
+        green,
+    )));
+    objects.push(Box::new(YZRect::new((0.0, 555.0), (0.0, 555.0), 0.0, red)));
+    objects.push(Box::new(XZRect::new(
+        (113.0, 443.0),
+        (127.0, 432.0),
+        554.0,
+        light,
+    )));
+    objects.push(Box::new(XZRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        0.0,
+        white.clone(),
+    )));
+    objects.push(Box::new(XZRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        555.0,
+        white.clone(),
+    )));
+    objects.push(Box::new(XYRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        555.0,
+        white.clone(),
+    )));
+
+    let block1 = Block::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 165.0, 165.0),
+        white.clone(),
+    );
+    let block1 = RotateY::new(Box::new(block1), -18.0);
+    let block1 = Translate::new(Box::new(block1), Vec3::new(130.0, 0.0, 65.0));
+    objects.push(Box::new(ConstantMedium::new_from_color(
+        Box::new(block1),
+        0.01,
+        Color::new(0.0, 0.0, 0.0),
+    )));
+
+    let block2 = Block::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 330.0, 165.0),
+        white,
+    );
+    let block2 = RotateY::new(Box::new(block2), 15.0);
+    let block2 = Translate::new(Box::new(block2), Vec3::new(265.0, 0.0, 295.0));
+    objects.push(Box::new(ConstantMedium::new(
+        Box::new(block2),
+        0.01,
+        Arc::new(HenyeyGreenstein::new_from_color(
+            Color::new(1.0, 1.0, 1.0),
+            0.6,
+        )),
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(278.0, 278.0, -800.0),
+            Point3::new(278.0, 278.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            20.0,
+            (0.0, 1.0),
+        ),
+        Color::default(),
+    )
+}
+
+#[allow(dead_code)]
+fn cornell_clouds(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
+
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Color::new(7.0, 7.0, 7.0)));
+
+    objects.push(Box::new(YZRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        555.0,
+        green,
+    )));
+    objects.push(Box::new(YZRect::new((0.0, 555.0), (0.0, 555.0), 0.0, red)));
+    objects.push(Box::new(XZRect::new(
+        (113.0, 443.0),
+        (127.0, 432.0),
+        554.0,
+        light,
+    )));
+    objects.push(Box::new(XZRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        0.0,
+        white.clone(),
+    )));
+    objects.push(Box::new(XZRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        555.0,
+        white.clone(),
+    )));
+    objects.push(Box::new(XYRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        555.0,
+        white.clone(),
+    )));
+
+    let noise = Arc::new(Fractal::new_fbm(Arc::new(GradientNoise::new(7)), 5));
+    let density = Arc::new(NoiseDensity::new(noise, 0.015, 0.5, 0.02));
+    let boundary = Sphere::new(Point3::new(278.0, 278.0, 278.0), 200.0, white);
+    objects.push(Box::new(HeterogeneousMedium::new(
+        Box::new(boundary),
+        density,
+        Color::new(0.9, 0.85, 0.8),
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(278.0, 278.0, -800.0),
+            Point3::new(278.0, 278.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            20.0,
+            (0.0, 1.0),
+        ),
+        Color::default(),
+    )
+}
+
+#[allow(dead_code)]
+fn nested_dielectrics(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
+
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0)));
+
+    objects.push(Box::new(YZRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        555.0,
+        green,
+    )));
+    objects.push(Box::new(YZRect::new((0.0, 555.0), (0.0, 555.0), 0.0, red)));
+    objects.push(Box::new(XZRect::new(
+        (213.0, 343.0),
+        (227.0, 332.0),
+        554.0,
+        light,
+    )));
+    objects.push(Box::new(XZRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        0.0,
+        white.clone(),
+    )));
+    objects.push(Box::new(XZRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        555.0,
+        white.clone(),
+    )));
+    objects.push(Box::new(XYRect::new(
+        (0.0, 555.0),
+        (0.0, 555.0),
+        555.0,
+        white,
+    )));
+
+    // Ice floating in water: the ice has the higher priority, so the part of
+    // the water surface inside it is ignored.
+    let water = Arc::new(Interior::new_with_absorption(
+        1.33,
+        1,
+        Color::new(0.004, 0.0015, 0.001),
+    ));
+    objects.push(Box::new(Block::new(
+        Point3::new(60.0, 0.0, 200.0),
+        Point3::new(280.0, 200.0, 420.0),
+        Arc::new(Dielectric::new_with_interior(water)),
+    )));
+    let ice = Arc::new(Interior::new(1.31, 2));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(170.0, 190.0, 310.0),
+        60.0,
+        Arc::new(Dielectric::new_with_interior(ice)),
+    )));
+
+    // Beer-Lambert colored glass.
+    let green_glass = Arc::new(Interior::new_with_absorption(
+        1.5,
+        1,
+        Color::new(0.012, 0.001, 0.008),
+    ));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(400.0, 90.0, 160.0),
+        90.0,
+        Arc::new(Dielectric::new_with_interior(green_glass)),
+    )));
+
+    // A scattering interior behind a smooth surface gives a milky,
+    // subsurface look.
+    let milk = Arc::new(Interior::new_with_scattering(
+        1.35,
+        1,
+        Color::new(0.0005, 0.001, 0.002),
+        0.08,
+        Arc::new(HenyeyGreenstein::new_from_color(
+            Color::new(1.0, 1.0, 1.0),
+            0.3,
+        )),
+    ));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(230.0, 60.0, 90.0),
+        60.0,
+        Arc::new(Dielectric::new_with_interior(milk)),
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(278.0, 278.0, -800.0),
+            Point3::new(278.0, 278.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            10.0,
+            (0.0, 1.0),
+        ),
+        Color::default(),
+    )
+}
+
+#[allow(dead_code)]
+fn dispersive_glass(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
+
+    let checker = Arc::new(CheckerTexture::new_from_color(
+        Color::new(0.05, 0.05, 0.05),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new_from_texture(checker)),
+    )));
+
+    // Render with `spectral` enabled to see the colored fringes.
+    objects.push(Box::new(Sphere::new(
+        Point3::new(-1.1, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new_dispersive(Dispersion::BK7)),
+    )));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(1.1, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new_dispersive(Dispersion::SF11)),
+    )));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(0.0, 0.5, 2.0),
+        0.5,
+        Arc::new(Dielectric::new_dispersive(Dispersion::Cauchy {
+            a: 1.45,
+            b: 0.02,
+        })),
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(0.0, 2.0, 8.0),
+            Point3::new(0.0, 0.8, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            30.0,
+            aspect_ratio,
+            0.0,
+            10.0,
+            (0.0, 1.0),
+        ),
+        Color::new(0.7, 0.8, 1.0),
+    )
+}
+
+#[allow(dead_code)]
+fn final_scene(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    // Ground
+    let mut boxes1: Vec<Box<dyn Hittable>> = vec![];
+    let ground = Arc::new(Lambertian::new(Color::new(0.48, 0.83, 0.53)));
+
+    let boxes_per_side = 20;
+    for i in 0..boxes_per_side {
+        for j in 0..boxes_per_side {
+            let w = 100.0;
+            let x0 = -1000.0 + f64::from(i) * w;
+            let z0 = -1000.0 + f64::from(j) * w;
+            let y0 = 0.0;
+
+            let x1 = x0 + w;
+            let y1 = random_f64_between(1.0, 101.0);
+            let z1 = z0 + w;
+
+            boxes1.push(Box::new(Block::new(
+                Point3::new(x0, y0, z0),
+                Point3::new(x1, y1, z1),
+                ground.clone(),
+            )));
+        }
+    }
+
+    let mut objects: Vec<Box<dyn Hittable>> = vec![Box::new(BVHNode::new(boxes1, (0.0, 1.0)))];
+
+    // Light
+    let light = Arc::new(DiffuseLight::new(Color::new(7.0, 7.0, 7.0)));
+    objects.push(Box::new(XZRect::new(
+        (123.0, 423.0),
+        (147.0, 412.0),
+        554.0,
+        light,
+    )));
+
+    // Moving Sphere
+    let center1 = Point3::new(400.0, 400.0, 200.0);
+    let center2 = center1 + Vec3::new(30.0, 0.0, 0.0);
+    let moving_sphere_material = Arc::new(Lambertian::new(Color::new(0.7, 0.3, 0.1)));
+    objects.push(Box::new(MovingSphere::new(
+        (center1, center2),
+        (0.0, 1.0),
+        50.0,
+        moving_sphere_material,
+    )));
+
+    // Metal and Glas Sphere
+    objects.push(Box::new(Sphere::new(
+        Point3::new(260.0, 150.0, 45.0),
+        70.0,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(0.0, 150.0, 145.0),
+        50.0,
+        Arc::new(Metal::new(Color::new(0.8, 0.8, 0.9), 1.0)),
+    )));
+
+    // ConstantMediums
+    let boundary = Box::new(Sphere::new(
+        Point3::new(360.0, 150.0, 145.0),
+        70.0,
+        Arc::new(Dielectric::new(1.5)),
+    ));
+    objects.push(boundary);
+    let boundary = Box::new(Sphere::new(
+        Point3::new(360.0, 150.0, 145.0),
+        70.0,
+        Arc::new(Dielectric::new(1.5)),
+    ));
+    objects.push(Box::new(ConstantMedium::new_from_color(
+        boundary,
+        0.2,
+        Color::new(0.2, 0.4, 0.9),
+    )));
+    let boundary = Box::new(Sphere::new(
+        Point3::new(0.0, 0.0, 0.0),
+        5000.0,
+        Arc::new(Dielectric::new(1.5)),
+    ));
+    objects.push(Box::new(ConstantMedium::new(
+        boundary,
+        0.0001,
+        Arc::new(HenyeyGreenstein::new_from_color(
+            Color::new(1.0, 1.0, 1.0),
+            0.7,
+        )),
+    )));
+
+    // Earth
+    let earth_mat = Arc::new(Lambertian::new_from_texture(Arc::new(
+        ImageTexture::new("earthmap.jpg").expect("could not load earthmap.jpg"),
+    )));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(400.0, 200.0, 400.0),
+        100.0,
+        earth_mat,
+    )));
+
+    // Perlin Sphere
+    let pertext = Arc::new(NoiseTexture::new(0.1));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(220.0, 280.0, 300.0),
+        80.0,
+        Arc::new(Lambertian::new_from_texture(pertext)),
+    )));
+
+    // Translation + Rotation
+    let mut boxes2: Vec<Box<dyn Hittable>> = vec![];
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let ns = 1_000;
+    for _ in 0..ns {
+        boxes2.push(Box::new(Sphere::new(
+            random_vector_in_range(0.0, 165.0),
+            10.0,
+            white.clone(),
+        )));
+    }
+
+    objects.push(Box::new(Translate::new(
+        Box::new(RotateY::new(
+            Box::new(BVHNode::new(boxes2, (0.0, 1.0))),
+            15.0,
+        )),
+        Vec3::new(-100.0, 270.0, 395.0),
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Vec3::new(478.0, 278.0, -600.0),
+            Vec3::new(278.0, 278.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            20.0,
+            (0.0, 1.0),
+        ),
+        Color::default(),
+    )
+}
+
+#[allow(dead_code)]
+fn coated_materials(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut objects: Vec<Box<dyn Hittable>> = vec![];
+
+    let checker = Arc::new(CheckerTexture::new_from_color(
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new_from_texture(checker)),
+    )));
+
+    // Varnished wood
+    let wood = Arc::new(Lambertian::new(Color::new(0.45, 0.25, 0.1)));
+    let varnish = Layered::new_with_absorption(wood, 1.5, Color::new(0.1, 0.3, 0.8), 0.5);
+    objects.push(Box::new(Sphere::new(
+        Point3::new(-2.2, 1.0, 0.0),
+        1.0,
+        Arc::new(varnish),
+    )));
+
+    // Clear coat over a diffuse base
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Layered::new(red, 1.5)),
+    )));
+
+    // Dusty metal
+    let metal = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.9), 0.05));
+    let dust = Arc::new(Lambertian::new(Color::new(0.6, 0.55, 0.5)));
+    let mask = Arc::new(NoiseTexture::new(4.0));
+    objects.push(Box::new(Sphere::new(
+        Point3::new(2.2, 1.0, 0.0),
+        1.0,
+        Arc::new(MixMaterial::new_from_texture(metal, dust, mask)),
+    )));
+
+    (
+        BVHNode::new(objects, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(0.0, 2.0, 9.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            10.0,
+            (0.0, 1.0),
+        ),
+        Color::new(0.7, 0.8, 1.0),
+    )
+}
+
+#[allow(dead_code)]
+fn procedural_spheres(aspect_ratio: f64) -> (BVHNode, Camera, Color) {
+    let mut world: Vec<Box<dyn Hittable>> = vec![];
+
+    let clouds = Arc::new(CloudTexture::new(
+        Color::new(0.3, 0.45, 0.8),
+        Color::new(0.95, 0.95, 0.95),
+        0.05,
+        0.5,
+        1,
+    ));
+    world.push(Box::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new_from_texture(clouds)),
+    )));
+
+    let textures: Vec<Arc<dyn Texture>> = vec![
+        Arc::new(MarbleTexture::new(
+            Color::new(0.9, 0.9, 0.85),
+            Color::new(0.2, 0.2, 0.25),
+            3.0,
+            4.0,
+            2,
+        )),
+        Arc::new(WoodTexture::new(
+            Color::new(0.75, 0.5, 0.25),
+            Color::new(0.4, 0.2, 0.08),
+            8.0,
+            0.3,
+            3,
+        )),
+        Arc::new(GraniteTexture::new(
+            Color::new(0.55, 0.5, 0.5),
+            Color::new(0.8, 0.75, 0.7),
+            Color::new(0.05, 0.05, 0.05),
+            6.0,
+            4,
+        )),
+    ];
+
+    for (i, texture) in textures.into_iter().enumerate() {
+        world.push(Box::new(Sphere::new(
+            Point3::new(-2.2 + 2.2 * i as f64, 1.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new_from_texture(texture)),
+        )));
+    }
+
+    (
+        BVHNode::new(world, (0.0, 1.0)),
+        Camera::new(
+            Point3::new(0.0, 2.0, 9.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            0.0,
+            10.0,
+            (0.0, 1.0),
+        ),
+        Color::new(0.7, 0.8, 1.0),
+    )
+}
+
+fn main() {
+    // Image
+    let aspect_ratio = 1.0;
+    let image_width: u32 = 400;
+    let image_height: u32 = (f64::from(image_width) / aspect_ratio) as u32;
+    let samples_per_pixel: u32 = 100;
+    let max_depth = 50;
+    let depth_limits = DepthLimits::new(max_depth);
+    let spectral = false;
+    let integrator = Integrator::PathTracing;
+
+    // World + Camera
+    let (world, camera, background) = cornell_box(aspect_ratio);
+
+    // PNG File
+    let mut data: Vec<u8> = Vec::with_capacity((3 * image_width * image_height) as usize);
+
+    let path = Path::new(r"images\test.png");
+    let file = File::create(path).unwrap();
+    let w = &mut BufWriter::new(file);
+
+    let mut encoder = Encoder::new(w, image_width, image_height);
+    encoder.set_color(ColorType::Rgb);
+    let mut writer = encoder.write_header().unwrap();
+
+    // Render
+    let start = Instant::now();
+
+    match integrator {
+        Integrator::PathTracing => {
+            for j in (0..image_height).rev() {
+                eprint!(
+                    "\r{} / {} lines rendered...",
+                    image_height - j,
+                    image_height
+                );
+                //eprint!("\rProgress: {}%", ((image_height - j) * 100) / image_height);
+                for i in 0..image_width {
+                    let mut pixel_color = Color::default();
+                    for _ in 0..samples_per_pixel {
+                        let u = (f64::from(i) + random_f64()) / f64::from(image_width - 1);
+                        let v = (f64::from(j) + random_f64()) / f64::from(image_height - 1);
+                        let r = camera.get_ray(u, v);
+
+                        pixel_color += if spectral {
+                            ray_color_spectral_with_limits(r, &background, &world, &depth_limits)
+                        } else {
+                            ray_color_with_limits(r, &background, &world, &depth_limits)
+                        };
+                        if j == 0 && i == 0 {
+                            pixel_color = Color::new(1.0, 0.0, 0.0);
+                        }
+                    }
+                    write_color(&mut data, pixel_color, samples_per_pixel);
+                }
+            }
+        }
+        Integrator::Bidirectional => {
+            let film = Bdpt::new(&camera, &world, background, max_depth).render(
+                image_width,
+                image_height,
+                samples_per_pixel,
+            );
+            film.write(&mut data, samples_per_pixel);
+        }
+        Integrator::ProgressivePhotonMapping {
+            initial_radius,
+            photons_per_iteration,
+        } => {
+            let film = Sppm::new(
+                &camera,
+                &world,
+                background,
+                max_depth,
+                initial_radius,
+                photons_per_iteration,
+            )
+            .render(image_width, image_height, samples_per_pixel);
+            film.write(&mut data, 1);
+        }
+        Integrator::Metropolis {
+            bootstrap_samples,
+            chains,
+        } => {
+            let film = Mlt::new(
+                &camera,
+                &world,
+                background,
+                max_depth,
+                bootstrap_samples,
+                chains,
+            )
+            .render(image_width, image_height, samples_per_pixel);
+            film.write(&mut data, samples_per_pixel);
         }
     }
     writer.write_image_data(&data).unwrap();
@@ -13,19 +13,27 @@ pub struct BVHNode {
     left: Option<Box<dyn Hittable>>,
     right: Option<Box<dyn Hittable>>,
     hitbox: AABB,
+    // Objects without bounds, like infinite planes, are kept out of the tree
+    // and tested against every ray.
+    unbounded: Vec<Box<dyn Hittable>>,
 }
 
 impl BVHNode {
     pub fn new(src_list: Vec<Box<dyn Hittable>>, time_frame: (f64, f64)) -> Self {
+        let (mut list, unbounded): (Vec<_>, Vec<_>) = src_list
+            .into_iter()
+            .partition(|object| object.bounding_box(time_frame).is_some());
+
         let mut new = BVHNode {
             left: None,
             right: None,
             hitbox: AABB::default(),
+            unbounded,
         };
 
-        let mut list = src_list;
-
-        if list.len() == 1 {
+        if list.is_empty() {
+            return new;
+        } else if list.len() == 1 {
             new.left = list.pop();
             new.hitbox = new.left.as_ref().unwrap().bounding_box(time_frame).unwrap();
         } else if list.len() == 2 {
@@ -91,23 +99,27 @@ impl BVHNode {
 
 impl Hittable for BVHNode {
     fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
-        if !self.hitbox.hit(r, interval) {
-            return None;
+        let mut hitrecord = None;
+        let mut t_max = interval.1;
+
+        for object in &self.unbounded {
+            if let Some(hit) = object.hit(r, (interval.0, t_max)) {
+                t_max = hit.t;
+                hitrecord = Some(hit);
+            }
         }
 
-        let mut hitrecord = None;
+        if self.left.is_none() || !self.hitbox.hit(r, (interval.0, t_max)) {
+            return hitrecord;
+        }
 
         if let Some(box_left) = &self.left {
-            if let Some(hit_left) = box_left.hit(r, interval) {
+            if let Some(hit_left) = box_left.hit(r, (interval.0, t_max)) {
+                t_max = hit_left.t;
                 hitrecord = Some(hit_left);
             }
         }
 
-        let t_max = match &hitrecord {
-            Some(record) => record.t,
-            None => interval.1,
-        };
-
         if let Some(box_right) = &self.right {
             if let Some(hit_right) = box_right.hit(r, (interval.0, t_max)) {
                 hitrecord = Some(hit_right);
@@ -117,8 +129,91 @@ impl Hittable for BVHNode {
         hitrecord
     }
 
+    fn transmittance(&self, r: &Ray, interval: (f64, f64)) -> f64 {
+        let mut transmittance = 1.0;
+        for object in &self.unbounded {
+            transmittance *= object.transmittance(r, interval);
+        }
+        if transmittance == 0.0 || self.left.is_none() || !self.hitbox.hit(r, interval) {
+            return transmittance;
+        }
+
+        for child in [&self.left, &self.right].into_iter().flatten() {
+            transmittance *= child.transmittance(r, interval);
+            if transmittance == 0.0 {
+                break;
+            }
+        }
+        transmittance
+    }
+
     #[allow(unused_variables)]
     fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
         Some(self.hitbox)
     }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        for object in &self.unbounded {
+            object.lights(lights);
+        }
+        if let Some(left) = &self.left {
+            left.lights(lights);
+        }
+        if let Some(right) = &self.right {
+            right.lights(lights);
+        }
+    }
+
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let left_area = self.left.as_ref().map_or(0.0, |left| left.area());
+        let right_area = self.right.as_ref().map_or(0.0, |right| right.area());
+        let total = left_area + right_area;
+        // Nothing to sample, e.g. an emitter of degenerate triangles only.
+        if total <= 0.0 || total.is_nan() {
+            return None;
+        }
+        let split = left_area / total;
+        if u.0 < split {
+            self.left.as_ref()?.sample_surface((u.0 / split, u.1), time)
+        } else {
+            self.right
+                .as_ref()?
+                .sample_surface(((u.0 - split) / (1.0 - split), u.1), time)
+        }
+    }
+
+    fn area(&self) -> f64 {
+        self.left.as_ref().map_or(0.0, |left| left.area())
+            + self.right.as_ref().map_or(0.0, |right| right.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hits::hittable::Hittable,
+        materials::diffuse_light::DiffuseLight,
+        objects::sphere::Sphere,
+        vec3::{Color, Point3},
+    };
+
+    use super::BVHNode;
+
+    #[test]
+    fn sampling_without_area_gives_nothing() {
+        let light = Arc::new(DiffuseLight::new(Color::new(1.0, 1.0, 1.0)));
+        let points: Vec<Box<dyn Hittable>> = vec![
+            Box::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 0.0, light.clone())),
+            Box::new(Sphere::new(Point3::new(1.0, 0.0, 0.0), 0.0, light)),
+        ];
+        let bvh = BVHNode::new(points, (0.0, 1.0));
+
+        assert_eq!(bvh.area(), 0.0);
+        assert!(bvh.sample_surface((0.5, 0.5), 0.0).is_none());
+    }
 }
@@ -1,7 +1,8 @@
 use crate::{
     degrees_to_radians, random_f64_between,
     ray::Ray,
-    vec3::{cross, random_in_unit_disk, unit_vector, Point3, Vec3},
+    vec3::{cross, dot, sample_uniform_disk, unit_vector, Point3, Vec3},
+    IndependentSampler, Sampler,
 };
 
 #[derive(Default)]
@@ -13,6 +14,7 @@ pub struct Camera {
     vertical: Vec3,
     uvw: (Vec3, Vec3, Vec3),
     lens_radius: f64,
+    focus_dist: f64,
     time_frame: (f64, f64),
 }
 
@@ -51,20 +53,106 @@ impl Camera {
             vertical,
             uvw: (u, v, w),
             lens_radius,
+            focus_dist,
             time_frame,
         }
     }
 
     pub fn get_ray(&self, s: f64, t: f64) -> Ray {
+        self.get_ray_with_sampler(s, t, &mut IndependentSampler)
+    }
+
+    // Takes the lens position and the time from `sampler`.
+    pub fn get_ray_with_sampler(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Ray {
         let (u, v, _) = self.uvw;
         let (time0, time1) = self.time_frame;
-        let rd = self.lens_radius * random_in_unit_disk();
+        let rd = self.lens_radius * sample_uniform_disk((sampler.next_f64(), sampler.next_f64()));
         let offset = u * rd.x() + v * rd.y();
 
         Ray::new(
             self.origin + offset,
             self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
+            time0 + sampler.next_f64() * (time1 - time0),
+        )
+    }
+
+    // A time within the shutter interval, for rays that do not start at the
+    // camera.
+    pub fn sample_time(&self) -> f64 {
+        let (time0, time1) = self.time_frame;
+        random_f64_between(time0, time1)
+    }
+
+    pub fn origin(&self) -> Point3 {
+        self.origin
+    }
+
+    // A ray through the film from the center of the lens, for integrators
+    // that connect paths to the camera and so treat it as a pinhole.
+    pub fn get_pinhole_ray(&self, s: f64, t: f64) -> Ray {
+        let (time0, time1) = self.time_frame;
+        Ray::new(
+            self.origin,
+            self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin,
             random_f64_between(time0, time1),
         )
     }
+
+    // The film coordinates `p` is seen at through the pinhole, if it is in
+    // view.
+    pub fn project(&self, p: &Point3) -> Option<(f64, f64)> {
+        let (u, v, w) = self.uvw;
+        let direction = *p - self.origin;
+        let depth = -dot(&direction, &w);
+        if depth <= 0.0 {
+            return None;
+        }
+
+        let on_film = self.origin + (self.focus_dist / depth) * direction - self.lower_left_corner;
+        let s = dot(&on_film, &u) / self.horizontal.len();
+        let t = dot(&on_film, &v) / self.vertical.len();
+        if !(0.0..1.0).contains(&s) || !(0.0..1.0).contains(&t) {
+            return None;
+        }
+        Some((s, t))
+    }
+
+    // Importance emitted along `direction`, normalized so that it integrates
+    // to one over the film at unit distance.
+    pub fn importance(&self, direction: &Vec3) -> f64 {
+        let cos_theta = self.cos_theta(direction);
+        if cos_theta <= 0.0 || self.project(&(self.origin + *direction)).is_none() {
+            return 0.0;
+        }
+        1.0 / (self.film_area() * cos_theta.powi(4))
+    }
+
+    // Solid angle density of `get_pinhole_ray` for uniform film coordinates.
+    pub fn pdf_direction(&self, direction: &Vec3) -> f64 {
+        let cos_theta = self.cos_theta(direction);
+        if cos_theta <= 0.0 || self.project(&(self.origin + *direction)).is_none() {
+            return 0.0;
+        }
+        1.0 / (self.film_area() * cos_theta.powi(3))
+    }
+
+    // Film position of `p` and the importance the pinhole sends to it,
+    // divided by the density of sampling the pinhole as seen from `p`.
+    pub fn sample_importance(&self, p: &Point3) -> Option<((f64, f64), f64)> {
+        let position = self.project(p)?;
+        let direction = *p - self.origin;
+        let distance_squared = direction.len_squared();
+        let importance =
+            self.importance(&direction) * self.cos_theta(&direction) / distance_squared;
+        Some((position, importance))
+    }
+
+    fn cos_theta(&self, direction: &Vec3) -> f64 {
+        -dot(&unit_vector(*direction), &self.uvw.2)
+    }
+
+    // Area of the film moved to unit distance from the pinhole.
+    fn film_area(&self) -> f64 {
+        self.horizontal.len() * self.vertical.len() / (self.focus_dist * self.focus_dist)
+    }
 }
@@ -0,0 +1,75 @@
+use crate::{vec3::Color, write_color};
+
+// Accumulates the samples of each pixel together with light contributions
+// splatted onto arbitrary film positions, as light tracing produces them.
+pub struct Film {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+    splats: Vec<Color>,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            width,
+            height,
+            pixels: vec![Color::default(); size],
+            splats: vec![Color::default(); size],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Pixel (i, j) counts columns from the left and rows from the bottom.
+    pub fn add_sample(&mut self, pixel: (u32, u32), color: Color) {
+        let index = self.index(pixel);
+        self.pixels[index] += color;
+    }
+
+    // `film` are the coordinates in [0, 1)² the camera projects a point to.
+    pub fn add_splat(&mut self, film: (f64, f64), color: Color) {
+        let (s, t) = film;
+        let i = ((s * f64::from(self.width)) as u32).min(self.width - 1);
+        let j = ((t * f64::from(self.height)) as u32).min(self.height - 1);
+        let index = self.index((i, j));
+        self.splats[index] += color;
+    }
+
+    // Adds everything recorded on `other`, a film of the same size.
+    pub fn merge(&mut self, other: &Film) {
+        for (pixel, sample) in self.pixels.iter_mut().zip(&other.pixels) {
+            *pixel += *sample;
+        }
+        for (splat, sample) in self.splats.iter_mut().zip(&other.splats) {
+            *splat += *sample;
+        }
+    }
+
+    // Appends the image top row first. Splats are scaled like the samples,
+    // since every sample traced one light path.
+    pub fn write(&self, data: &mut Vec<u8>, samples_per_pixel: u32) {
+        for j in (0..self.height).rev() {
+            for i in 0..self.width {
+                let index = self.index((i, j));
+                write_color(
+                    data,
+                    self.pixels[index] + self.splats[index],
+                    samples_per_pixel,
+                );
+            }
+        }
+    }
+
+    fn index(&self, pixel: (u32, u32)) -> usize {
+        let (i, j) = pixel;
+        (j * self.width + i) as usize
+    }
+}
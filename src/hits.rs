@@ -1,5 +1,7 @@
 pub mod aabb;
 pub mod constant_medium;
+pub mod csg;
+pub mod heterogeneous_medium;
 pub mod hittable;
 pub mod hittalbe_list;
 pub mod rotate;
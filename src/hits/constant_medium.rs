@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::{
     materials::{isotropic::Isotropic, Material},
@@ -14,12 +14,12 @@ use super::{
 
 pub struct ConstantMedium {
     boundary: Box<dyn Hittable>,
-    phase_function: Rc<dyn Material>,
+    phase_function: Arc<dyn Material>,
     neg_inv_density: f64,
 }
 
 impl ConstantMedium {
-    pub fn new(b: Box<dyn Hittable>, d: f64, a: Rc<dyn Material>) -> Self {
+    pub fn new(b: Box<dyn Hittable>, d: f64, a: Arc<dyn Material>) -> Self {
         Self {
             boundary: b,
             neg_inv_density: (-1.0) / d,
@@ -30,7 +30,7 @@ impl ConstantMedium {
     pub fn new_from_color(b: Box<dyn Hittable>, d: f64, c: Color) -> Self {
         Self {
             boundary: b,
-            phase_function: Rc::new(Isotropic::new_from_color(c)),
+            phase_function: Arc::new(Isotropic::new_from_color(c)),
             neg_inv_density: -1.0 / d,
         }
     }
@@ -41,6 +41,24 @@ impl Hittable for ConstantMedium {
         self.boundary.bounding_box(time)
     }
 
+    // Beer-Lambert over the part of `r` inside the convex boundary.
+    fn transmittance(&self, r: &Ray, interval: (f64, f64)) -> f64 {
+        let enter = match self.boundary.hit(r, (-f64::INFINITY, f64::INFINITY)) {
+            Some(enter) => enter.t,
+            None => return 1.0,
+        };
+        let exit = match self.boundary.hit(r, (enter + 0.0001, f64::INFINITY)) {
+            Some(exit) => exit.t,
+            None => return 1.0,
+        };
+        let start = enter.max(interval.0).max(0.0);
+        let end = exit.min(interval.1);
+        if start >= end {
+            return 1.0;
+        }
+        ((end - start) * r.direction().len() / self.neg_inv_density).exp()
+    }
+
     fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
         let enable_debug = false;
         let debugging = enable_debug && random_f64() < 0.00001;
@@ -96,7 +114,12 @@ impl Hittable for ConstantMedium {
                         t: rec_t,
                         p: rec_p,
                         normal: rec_normal,
+                        shading_normal: rec_normal,
+                        dpdu: Vec3::new(0.0, 1.0, 0.0),
+                        dpdv: Vec3::new(0.0, 0.0, 1.0),
                         front_face: rec_front_face,
+                        samplable: false,
+                        exterior_ior: 1.0,
                         material: rec_mat_ptr,
                         surface_coordinates: (0.0, 0.0),
                     })
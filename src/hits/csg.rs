@@ -0,0 +1,283 @@
+use crate::{
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    aabb::{surrounding_box, AABB},
+    hittable::{HitRecord, Hittable},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    fn inside(&self, left: bool, right: bool) -> bool {
+        match self {
+            CsgOperation::Union => left || right,
+            CsgOperation::Intersection => left && right,
+            CsgOperation::Difference => left && !right,
+        }
+    }
+}
+
+// Combines two closed objects into the region inside either, both, or the
+// first but not the second. The surface of the result is found by walking
+// every crossing of both along the ray and keeping those where being inside
+// the result changes.
+pub struct Csg {
+    operation: CsgOperation,
+    left: Box<dyn Hittable>,
+    right: Box<dyn Hittable>,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        Self {
+            operation,
+            left,
+            right,
+        }
+    }
+
+    pub fn new_union(left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        Self::new(CsgOperation::Union, left, right)
+    }
+
+    pub fn new_intersection(left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        Self::new(CsgOperation::Intersection, left, right)
+    }
+
+    pub fn new_difference(left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        Self::new(CsgOperation::Difference, left, right)
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        self.hit_all(r, interval).into_iter().next()
+    }
+
+    fn hit_all(&self, r: &Ray, interval: (f64, f64)) -> Vec<HitRecord> {
+        // Whether the ray starts inside an operand is only known from its
+        // crossings before the interval ends, so all of them are collected.
+        let left = self.left.hit_all(r, (interval.0, f64::INFINITY));
+        let right = self.right.hit_all(r, (interval.0, f64::INFINITY));
+
+        let mut inside_left = left.first().is_some_and(|hit| !hit.front_face);
+        let mut inside_right = right.first().is_some_and(|hit| !hit.front_face);
+        let mut inside = self.operation.inside(inside_left, inside_right);
+
+        let mut events: Vec<(HitRecord, bool)> = left
+            .into_iter()
+            .map(|hit| (hit, true))
+            .chain(right.into_iter().map(|hit| (hit, false)))
+            .collect();
+        events.sort_by(|a, b| a.0.t.total_cmp(&b.0.t));
+
+        let mut hits = vec![];
+        for (mut hitrecord, from_left) in events {
+            if hitrecord.t > interval.1 {
+                break;
+            }
+            if from_left {
+                inside_left = hitrecord.front_face;
+            } else {
+                inside_right = hitrecord.front_face;
+            }
+
+            let now_inside = self.operation.inside(inside_left, inside_right);
+            if now_inside == inside {
+                continue;
+            }
+            inside = now_inside;
+
+            // The second object's surface bounds a difference from the
+            // outside, so its normal turns around.
+            let mut outward = if hitrecord.front_face {
+                hitrecord.normal
+            } else {
+                -hitrecord.normal
+            };
+            if !from_left && self.operation == CsgOperation::Difference {
+                outward = -outward;
+            }
+            hitrecord.set_face_normal(r, outward);
+            hits.push(hitrecord);
+        }
+
+        hits
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        // Only part of each operand's surface is kept, so the whole object
+        // stands in for the lights inside it.
+        let mut inner = vec![];
+        self.left.lights(&mut inner);
+        self.right.lights(&mut inner);
+        if !inner.is_empty() {
+            lights.push(self);
+        }
+    }
+
+    // Samples both operands by area and keeps only points on the surface of
+    // the result, so that the density over it is one over `area` and the
+    // rest of the samples are lost.
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let left_area = self.left.area();
+        let x = u.0 * self.area();
+        let (from_left, mut hitrecord) = if x < left_area {
+            (true, self.left.sample_surface((x / left_area, u.1), time)?)
+        } else {
+            let v = ((x - left_area) / self.right.area()).min(1.0);
+            (false, self.right.sample_surface((v, u.1), time)?)
+        };
+
+        let other = if from_left { &self.right } else { &self.left };
+        let in_other = contains(other.as_ref(), &hitrecord.p, time);
+        let inside = |in_operand: bool| {
+            if from_left {
+                self.operation.inside(in_operand, in_other)
+            } else {
+                self.operation.inside(in_other, in_operand)
+            }
+        };
+        if inside(true) == inside(false) {
+            return None;
+        }
+        // Where the result is inside on the outside of the operand, as for
+        // the second object of a difference, the normal turns around.
+        if !inside(true) {
+            hitrecord.normal = -hitrecord.normal;
+            hitrecord.shading_normal = -hitrecord.shading_normal;
+        }
+        Some(hitrecord)
+    }
+
+    fn area(&self) -> f64 {
+        self.left.area() + self.right.area()
+    }
+
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        let left = self.left.bounding_box(time);
+        let right = self.right.bounding_box(time);
+        match self.operation {
+            CsgOperation::Union => Some(surrounding_box(left?, right?)),
+            CsgOperation::Intersection => match (left, right) {
+                (Some(left), Some(right)) => Some(overlap(&left, &right)),
+                (left, right) => left.or(right),
+            },
+            CsgOperation::Difference => left,
+        }
+    }
+}
+
+// Whether `p` is inside the closed `object`, from the side of the first
+// surface a ray leaving `p` crosses.
+fn contains(object: &dyn Hittable, p: &Point3, time: f64) -> bool {
+    let r = Ray::new(*p, Vec3::new(0.5773, 0.5774, 0.5775), time);
+    object
+        .hit(&r, (0.0001, f64::INFINITY))
+        .is_some_and(|hitrecord| !hitrecord.front_face)
+}
+
+fn overlap(a: &AABB, b: &AABB) -> AABB {
+    let mut min = a.min();
+    let mut max = a.max();
+    for axis in 0..3 {
+        min[axis] = min[axis].max(b.min()[axis]);
+        max[axis] = max[axis].min(b.max()[axis]).max(min[axis]);
+    }
+    AABB::new(min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use std::f64::consts::PI;
+
+    use crate::{
+        materials::{diffuse_light::DiffuseLight, lambertian::Lambertian},
+        objects::{block::Block, sphere::Sphere},
+        ray::Ray,
+        vec3::{dot, Color, Point3, Vec3},
+    };
+
+    use super::{Csg, Hittable};
+
+    #[test]
+    fn sphere_carved_out_of_block() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let csg = Csg::new_difference(
+            Box::new(Block::new(
+                Point3::new(-1.0, -1.0, -1.0),
+                Point3::new(1.0, 1.0, 1.0),
+                material.clone(),
+            )),
+            Box::new(Sphere::new(Point3::new(0.0, 0.0, 1.0), 0.5, material)),
+        );
+
+        // Through the dent: in at the bottom of the hole, out at the back.
+        let r = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hits = csg.hit_all(&r, (0.001, f64::INFINITY));
+        assert_eq!(hits.len(), 2);
+        assert!((hits[0].t - 4.5).abs() < 1e-6);
+        assert!(hits[0].front_face);
+        assert!(hits[0].normal.z() > 0.99);
+        assert!((hits[1].t - 6.0).abs() < 1e-6);
+        assert!(!hits[1].front_face);
+
+        // Beside the dent the block's own face is hit.
+        let r = Ray::new(Point3::new(0.8, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = csg.hit(&r, (0.001, f64::INFINITY)).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-6);
+    }
+
+    // The samples kept lie on the carved surface and cover its share of
+    // both operands' area.
+    #[test]
+    fn samples_stay_on_the_carved_surface() {
+        let light = Arc::new(DiffuseLight::new(Color::new(1.0, 1.0, 1.0)));
+        let csg = Csg::new_difference(
+            Box::new(Block::new(
+                Point3::new(-1.0, -1.0, -1.0),
+                Point3::new(1.0, 1.0, 1.0),
+                light.clone(),
+            )),
+            Box::new(Sphere::new(Point3::new(0.0, 0.0, 1.0), 0.5, light)),
+        );
+        let mut lights = vec![];
+        csg.lights(&mut lights);
+        assert_eq!(lights.len(), 1);
+
+        let n = 200;
+        let mut kept = 0;
+        for i in 0..n {
+            for j in 0..n {
+                let u = (
+                    (f64::from(i) + 0.5) / f64::from(n),
+                    (f64::from(j) + 0.5) / f64::from(n),
+                );
+                let sample = match csg.sample_surface(u, 0.0) {
+                    Some(sample) => sample,
+                    None => continue,
+                };
+                kept += 1;
+                let r = Ray::new(sample.p + 0.01 * sample.normal, -sample.normal, 0.0);
+                let hit = csg.hit(&r, (0.0, f64::INFINITY)).unwrap();
+                assert!((hit.t - 0.01).abs() < 1e-6);
+                assert!(hit.front_face && dot(&hit.normal, &sample.normal) > 0.999);
+            }
+        }
+
+        // The block loses a disk of its face and gains a hemisphere.
+        let carved = 24.0 + PI * 0.25;
+        let fraction = f64::from(kept) / f64::from(n * n);
+        assert!((fraction - carved / csg.area()).abs() < 0.005, "{fraction}");
+    }
+}
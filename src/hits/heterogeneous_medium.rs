@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use crate::{
+    materials::{bsdf::BsdfSample, isotropic::Isotropic, Material},
+    random_f64,
+    ray::Ray,
+    textures::{solid_color::SolidColor, Texture},
+    vec3::{Color, Point3, Vec3},
+    volumes::DensityField,
+};
+
+use super::{
+    aabb::AABB,
+    hittable::{HitRecord, Hittable},
+};
+
+const BOUNDARY_EPSILON: f64 = 0.0001;
+
+// A medium with spatially varying density inside a closed, not necessarily
+// convex, boundary. Collisions are found by delta tracking against the
+// density field's piecewise majorants.
+pub struct HeterogeneousMedium {
+    boundary: Box<dyn Hittable>,
+    density: Arc<dyn DensityField>,
+    phase_function: Arc<dyn Material>,
+}
+
+impl HeterogeneousMedium {
+    pub fn new(boundary: Box<dyn Hittable>, density: Arc<dyn DensityField>, albedo: Color) -> Self {
+        Self {
+            boundary,
+            density,
+            phase_function: Arc::new(Isotropic::new_from_color(albedo)),
+        }
+    }
+
+    // `emission` is the radiance added at every collision, on top of what
+    // `phase_function` scatters.
+    pub fn new_with_emission(
+        boundary: Box<dyn Hittable>,
+        density: Arc<dyn DensityField>,
+        phase_function: Arc<dyn Material>,
+        emission: Arc<dyn Texture>,
+    ) -> Self {
+        Self {
+            boundary,
+            density,
+            phase_function: Arc::new(EmissivePhase {
+                phase_function,
+                emission,
+            }),
+        }
+    }
+
+    pub fn new_from_phase_function(
+        boundary: Box<dyn Hittable>,
+        density: Arc<dyn DensityField>,
+        phase_function: Arc<dyn Material>,
+    ) -> Self {
+        Self::new_with_emission(
+            boundary,
+            density,
+            phase_function,
+            Arc::new(SolidColor::default()),
+        )
+    }
+
+    // Calls `visit` with every parametric interval of `r` inside the boundary
+    // and within `interval`, in order, until it returns a value.
+    fn for_each_segment<T>(
+        &self,
+        r: &Ray,
+        interval: (f64, f64),
+        mut visit: impl FnMut(f64, f64) -> Option<T>,
+    ) -> Option<T> {
+        let (t_min, t_max) = interval;
+        let mut t = t_min;
+
+        while t < t_max {
+            let first = self.boundary.hit(r, (t, f64::INFINITY))?;
+
+            let (enter, exit) = if first.front_face {
+                let second = self
+                    .boundary
+                    .hit(r, (first.t + BOUNDARY_EPSILON, f64::INFINITY))?;
+                (first.t, second.t)
+            } else {
+                (t, first.t)
+            };
+
+            if enter >= t_max {
+                return None;
+            }
+
+            if let Some(result) = visit(enter, exit.min(t_max)) {
+                return Some(result);
+            }
+            t = exit + BOUNDARY_EPSILON;
+        }
+
+        None
+    }
+
+    // Samples tentative collisions inside the boundary with each piece's
+    // majorant, calling `collide` with their distance and majorant until it
+    // returns a value.
+    fn track<T>(
+        &self,
+        r: &Ray,
+        interval: (f64, f64),
+        mut collide: impl FnMut(f64, f64) -> Option<T>,
+    ) -> Option<T> {
+        let ray_length = r.direction().len();
+
+        self.for_each_segment(r, interval, |enter, exit| {
+            for (start, end, majorant) in self.density.majorants(r, (enter, exit)) {
+                if majorant <= 0.0 {
+                    continue;
+                }
+                let mut t = start;
+                loop {
+                    t -= (1.0 - random_f64()).ln() / (majorant * ray_length);
+                    if t >= end {
+                        break;
+                    }
+                    if let Some(result) = collide(t, majorant) {
+                        return Some(result);
+                    }
+                }
+            }
+            None
+        })
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        self.boundary.bounding_box(time)
+    }
+
+    // Estimates the transmittance along `r` with ratio tracking.
+    fn transmittance(&self, r: &Ray, interval: (f64, f64)) -> f64 {
+        if self.density.max_density() <= 0.0 {
+            return 1.0;
+        }
+
+        let mut transmittance = 1.0;
+        self.track(r, interval, |t, majorant| {
+            transmittance *= 1.0 - self.density.density(&r.at(t)) / majorant;
+            (transmittance <= 0.0).then_some(())
+        });
+
+        transmittance.max(0.0)
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        if self.density.max_density() <= 0.0 {
+            return None;
+        }
+
+        let t = self.track(r, interval, |t, majorant| {
+            (random_f64() * majorant < self.density.density(&r.at(t))).then_some(t)
+        })?;
+
+        Some(HitRecord {
+            t,
+            p: r.at(t),
+            normal: Vec3::new(1.0, 0.0, 0.0),
+            shading_normal: Vec3::new(1.0, 0.0, 0.0),
+            dpdu: Vec3::new(0.0, 1.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, 1.0),
+            front_face: true,
+            samplable: false,
+            exterior_ior: 1.0,
+            material: self.phase_function.clone(),
+            surface_coordinates: (0.0, 0.0),
+        })
+    }
+}
+
+struct EmissivePhase {
+    phase_function: Arc<dyn Material>,
+    emission: Arc<dyn Texture>,
+}
+
+impl Material for EmissivePhase {
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        self.phase_function.eval(hitrecord, wo, wi)
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        self.phase_function.sample(hitrecord, wo, u)
+    }
+
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        self.phase_function.pdf(hitrecord, wo, wi)
+    }
+
+    fn emitted(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.emission.value(uv, p)
+    }
+
+    fn is_phase_function(&self) -> bool {
+        self.phase_function.is_phase_function()
+    }
+}
@@ -1,25 +1,82 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::{
     materials::Material,
+    onb::Onb,
     ray::Ray,
     vec3::{dot, Point3, Vec3},
 };
 
 use super::aabb::AABB;
 
-pub trait Hittable {
+// Distance along a ray skipped past a crossing to find the next one.
+const CROSSING_EPSILON: f64 = 0.0001;
+
+pub trait Hittable: Send + Sync {
     fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord>;
     fn bounding_box(&self, time: (f64, f64)) -> Option<AABB>;
+
+    // Every crossing of the surface along `r` within `interval`, in order.
+    // Along a closed surface they alternate between entering and leaving.
+    fn hit_all(&self, r: &Ray, interval: (f64, f64)) -> Vec<HitRecord> {
+        let mut hits = vec![];
+        let mut t = interval.0;
+        while let Some(hitrecord) = self.hit(r, (t, interval.1)) {
+            t = hitrecord.t + CROSSING_EPSILON;
+            hits.push(hitrecord);
+        }
+        hits
+    }
+
+    // The fraction of light that gets through along `r` within `interval`,
+    // for shadow and connection rays. Surfaces block all of it, media
+    // override this with an estimate of what they let through.
+    fn transmittance(&self, r: &Ray, interval: (f64, f64)) -> f64 {
+        if self.hit(r, interval).is_some() {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    // Collects the emitting objects for light sampling. Containers forward
+    // the call, transforms add themselves so that samples are transformed.
+    #[allow(unused_variables)]
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {}
+
+    // A point distributed uniformly by area over the surface at `time`, with
+    // the outward normal and `front_face` set.
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        None
+    }
+
+    fn area(&self) -> f64 {
+        0.0
+    }
 }
 
 pub struct HitRecord {
     pub p: Point3,
+    // The geometric normal, on the side the ray came from.
     pub normal: Vec3,
+    // The normal the material shades with, on the same side as `normal`.
+    // Primitives set it to `normal`, meshes interpolate their vertex normals.
+    pub shading_normal: Vec3,
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
     pub t: f64,
     pub surface_coordinates: (f64, f64),
     pub front_face: bool,
-    pub material: Rc<dyn Material>,
+    // Whether light sampling can pick this point too. Emission on surfaces
+    // that cannot be sampled by area, like signed distance fields, curves,
+    // point clouds and planes, is only found by hitting them.
+    pub samplable: bool,
+    pub material: Arc<dyn Material>,
+    // Refraction index of the medium around the surface. Objects report 1,
+    // the integrator replaces it with the enclosing medium's for nested
+    // dielectrics.
+    pub exterior_ior: f64,
 }
 
 impl HitRecord {
@@ -30,6 +87,22 @@ impl HitRecord {
         } else {
             -outward_normal
         };
+        if dot(&self.shading_normal, &self.normal) < 0.0 {
+            self.shading_normal = -self.shading_normal;
+        }
+    }
+
+    pub fn shading_frame(&self, shading_normal: &Vec3) -> Onb {
+        Onb::build_from_w_and_tangent(shading_normal, &self.dpdu)
+    }
+
+    pub fn offset_origin(&self, w: &Vec3) -> Point3 {
+        let offset = 0.0001 * self.normal;
+        if dot(w, &self.normal) > 0.0 {
+            self.p + offset
+        } else {
+            self.p - offset
+        }
     }
 }
 
@@ -38,10 +111,15 @@ impl Clone for HitRecord {
         Self {
             p: self.p,
             normal: self.normal,
+            shading_normal: self.shading_normal,
+            dpdu: self.dpdu,
+            dpdv: self.dpdv,
             t: self.t,
             front_face: self.front_face,
-            material: Rc::clone(&self.material),
+            samplable: self.samplable,
+            material: Arc::clone(&self.material),
             surface_coordinates: self.surface_coordinates,
+            exterior_ior: self.exterior_ior,
         }
     }
 }
@@ -38,6 +38,17 @@ impl Hittable for HittableList {
         hit_anything
     }
 
+    fn transmittance(&self, r: &Ray, interval: (f64, f64)) -> f64 {
+        let mut transmittance = 1.0;
+        for object in &self.list {
+            transmittance *= object.transmittance(r, interval);
+            if transmittance == 0.0 {
+                break;
+            }
+        }
+        transmittance
+    }
+
     fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
         if self.list.is_empty() {
             return None;
@@ -62,4 +73,28 @@ impl Hittable for HittableList {
 
         Some(return_option)
     }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        for object in &self.list {
+            object.lights(lights);
+        }
+    }
+
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        // Picks an object in proportion to its area and reuses the rest of
+        // the first dimension for the point on it.
+        let mut remaining = u.0 * self.area();
+        for object in &self.list {
+            let area = object.area();
+            if area > 0.0 && remaining < area {
+                return object.sample_surface((remaining / area, u.1), time);
+            }
+            remaining -= area;
+        }
+        None
+    }
+
+    fn area(&self) -> f64 {
+        self.list.iter().map(|object| object.area()).sum()
+    }
 }
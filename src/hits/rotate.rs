@@ -27,13 +27,18 @@ impl RotateY {
             b_box: bounding_box,
         };
 
+        // Unbounded objects stay unbounded.
+        let aabb = match new.b_box {
+            Some(aabb) => aabb,
+            None => return new,
+        };
+
         let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
         let mut max = Point3::new(-f64::INFINITY, -f64::INFINITY, -f64::INFINITY);
 
         for i in 0..2 {
             for j in 0..2 {
                 for k in 0..2 {
-                    let aabb = new.b_box.unwrap();
                     let x = f64::from(i) * aabb.max().x() + f64::from(1 - i) * aabb.min().x();
                     let y = f64::from(j) * aabb.max().y() + f64::from(1 - j) * aabb.min().y();
                     let z = f64::from(k) * aabb.max().z() + f64::from(1 - k) * aabb.min().z();
@@ -53,15 +58,9 @@ impl RotateY {
         new.b_box = Some(AABB::new(min, max));
         new
     }
-}
-
-impl Hittable for RotateY {
-    #[allow(unused_variables)]
-    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
-        self.b_box
-    }
 
-    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+    // `r` in object space.
+    fn to_object(&self, r: &Ray) -> Ray {
         let mut origin = r.origin();
         let mut direction = r.direction();
 
@@ -71,8 +70,27 @@ impl Hittable for RotateY {
         direction[0] = self.cos_theta * r.direction()[0] - self.sin_theta * r.direction()[2];
         direction[2] = self.sin_theta * r.direction()[0] + self.cos_theta * r.direction()[2];
 
-        let rotated_r = Ray::new(origin, direction, r.time());
+        Ray::new(origin, direction, r.time())
+    }
+
+    // Object to world space.
+    fn rotate(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(
+            self.cos_theta * v.x() + self.sin_theta * v.z(),
+            v.y(),
+            -self.sin_theta * v.x() + self.cos_theta * v.z(),
+        )
+    }
+}
 
+impl Hittable for RotateY {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        self.b_box
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let rotated_r = self.to_object(r);
         match self.object.hit(&rotated_r, interval) {
             Some(mut hitrecord) => {
                 let mut p = hitrecord.p;
@@ -86,12 +104,55 @@ impl Hittable for RotateY {
                 normal[2] =
                     -self.sin_theta * hitrecord.normal[0] + self.cos_theta * hitrecord.normal[2];
 
+                let mut dpdu = hitrecord.dpdu;
+                let mut dpdv = hitrecord.dpdv;
+
+                dpdu[0] = self.cos_theta * hitrecord.dpdu[0] + self.sin_theta * hitrecord.dpdu[2];
+                dpdu[2] = -self.sin_theta * hitrecord.dpdu[0] + self.cos_theta * hitrecord.dpdu[2];
+
+                dpdv[0] = self.cos_theta * hitrecord.dpdv[0] + self.sin_theta * hitrecord.dpdv[2];
+                dpdv[2] = -self.sin_theta * hitrecord.dpdv[0] + self.cos_theta * hitrecord.dpdv[2];
+
                 hitrecord.p = p;
-                hitrecord.set_face_normal(&rotated_r, normal);
+                hitrecord.shading_normal = self.rotate(&hitrecord.shading_normal);
+                hitrecord.dpdu = dpdu;
+                hitrecord.dpdv = dpdv;
+                let outward = if hitrecord.front_face {
+                    normal
+                } else {
+                    -normal
+                };
+                hitrecord.set_face_normal(r, outward);
 
                 Some(hitrecord)
             }
             None => None,
         }
     }
+
+    fn transmittance(&self, r: &Ray, interval: (f64, f64)) -> f64 {
+        self.object.transmittance(&self.to_object(r), interval)
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        let mut inner = vec![];
+        self.object.lights(&mut inner);
+        if !inner.is_empty() {
+            lights.push(self);
+        }
+    }
+
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let mut hitrecord = self.object.sample_surface(u, time)?;
+        hitrecord.p = self.rotate(&hitrecord.p);
+        hitrecord.normal = self.rotate(&hitrecord.normal);
+        hitrecord.shading_normal = self.rotate(&hitrecord.shading_normal);
+        hitrecord.dpdu = self.rotate(&hitrecord.dpdu);
+        hitrecord.dpdv = self.rotate(&hitrecord.dpdv);
+        Some(hitrecord)
+    }
+
+    fn area(&self) -> f64 {
+        self.object.area()
+    }
 }
@@ -21,9 +21,11 @@ impl Translate {
 
 impl Hittable for Translate {
     fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
-        let min = self.object.bounding_box(time).unwrap().min() + self.offset;
-        let max = self.object.bounding_box(time).unwrap().max() + self.offset;
-        Some(AABB::new(min, max))
+        let bounding_box = self.object.bounding_box(time)?;
+        Some(AABB::new(
+            bounding_box.min() + self.offset,
+            bounding_box.max() + self.offset,
+        ))
     }
 
     fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
@@ -31,10 +33,40 @@ impl Hittable for Translate {
         match self.object.hit(&moved_r, interval) {
             Some(mut hitrecord) => {
                 hitrecord.p += self.offset;
-                hitrecord.set_face_normal(&moved_r, hitrecord.normal);
+                let outward = if hitrecord.front_face {
+                    hitrecord.normal
+                } else {
+                    -hitrecord.normal
+                };
+                hitrecord.set_face_normal(&moved_r, outward);
                 Some(hitrecord)
             }
             None => None,
         }
     }
+
+    fn transmittance(&self, r: &Ray, interval: (f64, f64)) -> f64 {
+        let moved_r = Ray::new(r.origin() - self.offset, r.direction(), r.time());
+        self.object.transmittance(&moved_r, interval)
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        // Samples have to be moved, so the whole object stands in for the
+        // lights inside it.
+        let mut inner = vec![];
+        self.object.lights(&mut inner);
+        if !inner.is_empty() {
+            lights.push(self);
+        }
+    }
+
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let mut hitrecord = self.object.sample_surface(u, time)?;
+        hitrecord.p += self.offset;
+        Some(hitrecord)
+    }
+
+    fn area(&self) -> f64 {
+        self.object.area()
+    }
 }
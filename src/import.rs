@@ -0,0 +1,29 @@
+pub mod gltf;
+pub mod ply;
+pub mod stl;
+
+use std::{error::Error, fmt, io};
+
+// What goes wrong reading the simpler mesh and point formats.
+#[derive(Debug)]
+pub enum ImportError {
+    Io(io::Error),
+    Format(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(error) => write!(f, "could not read file: {error}"),
+            ImportError::Format(message) => write!(f, "invalid file: {message}"),
+        }
+    }
+}
+
+impl Error for ImportError {}
+
+impl From<io::Error> for ImportError {
+    fn from(error: io::Error) -> Self {
+        ImportError::Io(error)
+    }
+}
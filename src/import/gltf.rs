@@ -0,0 +1,721 @@
+use std::{collections::HashMap, error::Error, f64::consts::PI, fmt, sync::Arc};
+
+use ::gltf::{
+    camera::Projection, image::Format, khr_lights_punctual::Kind, mesh::Mode,
+    texture::WrappingMode, Node,
+};
+
+use crate::{
+    bvh_tree::bvh_node::BVHNode,
+    camera::Camera,
+    hits::{aabb::surrounding_box, hittable::Hittable},
+    materials::{
+        diffuse_light::DiffuseLight, normal_map::NormalMap, principled::Principled, Material,
+    },
+    objects::{
+        disk::Disk,
+        mesh::{Mesh, TriangleMesh},
+        sphere::Sphere,
+    },
+    textures::{
+        image_texture::{srgb_to_linear, Filter, ImageTexture, ImageTextureError, WrapMode},
+        nodes::MultiplyTexture,
+        scalar::{Channel, ConstantScalar},
+        solid_color::SolidColor,
+        ScalarTexture, Texture,
+    },
+    vec3::{cross, dot, unit_vector, Color, Point3, Vec3},
+};
+
+// glTF gives light in photometric units, the renderer works in radiometric
+// ones. Dividing by the efficacy of 555 nm light keeps typical exports in a
+// sensible range.
+const LUMENS_PER_WATT: f64 = 683.0;
+
+// Size of the spheres standing in for point and spot lights, relative to
+// the extent of the scene.
+const LIGHT_RADIUS: f64 = 0.005;
+
+// Directional lights become discs this far away, relative to the extent of
+// the scene, seen under this angular radius in degrees.
+const SUN_DISTANCE: f64 = 10.0;
+const SUN_ANGULAR_RADIUS: f64 = 0.5;
+
+#[derive(Debug)]
+pub enum GltfError {
+    Import(::gltf::Error),
+    Image(ImageTextureError),
+    Format(String),
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GltfError::Import(error) => write!(f, "could not import glTF: {error}"),
+            GltfError::Image(error) => write!(f, "could not load glTF image: {error}"),
+            GltfError::Format(message) => write!(f, "invalid glTF: {message}"),
+        }
+    }
+}
+
+impl Error for GltfError {}
+
+impl From<::gltf::Error> for GltfError {
+    fn from(error: ::gltf::Error) -> Self {
+        GltfError::Import(error)
+    }
+}
+
+impl From<ImageTextureError> for GltfError {
+    fn from(error: ImageTextureError) -> Self {
+        GltfError::Image(error)
+    }
+}
+
+// A world built from the default scene of a glTF or GLB file, with its
+// first perspective camera or one framing everything when there is none.
+//
+// Node transforms are baked into the meshes, materials map onto
+// `Principled` and KHR punctual lights become small emitters: spheres for
+// point and spot lights and a distant disc for directional ones. What the
+// renderer cannot reproduce, like spot cones or orthographic cameras, is
+// listed in `warnings`.
+pub struct GltfScene {
+    pub world: BVHNode,
+    pub camera: Camera,
+    pub warnings: Vec<String>,
+}
+
+impl GltfScene {
+    pub fn new(path: &str, aspect_ratio: f64) -> Result<Self, GltfError> {
+        let (document, buffers, images) = ::gltf::import(path)?;
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or_else(|| GltfError::Format("no scene".to_string()))?;
+
+        let mut loader = Loader {
+            buffers,
+            images,
+            textures: HashMap::new(),
+            materials: HashMap::new(),
+            objects: vec![],
+            camera: None,
+            lights: vec![],
+            aspect_ratio,
+            warnings: vec![],
+        };
+        for node in scene.nodes() {
+            loader.visit(&node, &IDENTITY)?;
+        }
+        loader.finish()
+    }
+}
+
+// A column major affine transform.
+type Matrix = [[f64; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut result = [[0.0; 4]; 4];
+    for (column, b_column) in result.iter_mut().zip(b) {
+        for (row, value) in column.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][row] * b_column[k]).sum();
+        }
+    }
+    result
+}
+
+fn transform_point(m: &Matrix, p: &Point3) -> Point3 {
+    transform_vector(m, p) + Point3::new(m[3][0], m[3][1], m[3][2])
+}
+
+fn transform_vector(m: &Matrix, v: &Vec3) -> Vec3 {
+    v.x() * Vec3::new(m[0][0], m[0][1], m[0][2])
+        + v.y() * Vec3::new(m[1][0], m[1][1], m[1][2])
+        + v.z() * Vec3::new(m[2][0], m[2][1], m[2][2])
+}
+
+// Normals go through the inverse transpose of the linear part, which is the
+// cofactor matrix up to a scale that normalizing removes. Also returns the
+// determinant, whose sign tells whether the winding flips.
+fn normal_transform(m: &Matrix) -> ([Vec3; 3], f64) {
+    let x = Vec3::new(m[0][0], m[0][1], m[0][2]);
+    let y = Vec3::new(m[1][0], m[1][1], m[1][2]);
+    let z = Vec3::new(m[2][0], m[2][1], m[2][2]);
+    (
+        [cross(&y, &z), cross(&z, &x), cross(&x, &y)],
+        dot(&x, &cross(&y, &z)),
+    )
+}
+
+// Light read from the scene, placed once the extent of the geometry is known.
+struct PendingLight {
+    kind: Kind,
+    color: Color,
+    intensity: f64,
+    transform: Matrix,
+}
+
+struct Loader {
+    buffers: Vec<::gltf::buffer::Data>,
+    images: Vec<::gltf::image::Data>,
+    // By texture index and whether the texels are sRGB encoded.
+    textures: HashMap<(usize, bool), Arc<ImageTexture>>,
+    // With the texture coordinate set the material's textures use.
+    materials: HashMap<Option<usize>, (Arc<dyn Material>, u32)>,
+    objects: Vec<Box<dyn Hittable>>,
+    camera: Option<Camera>,
+    lights: Vec<PendingLight>,
+    aspect_ratio: f64,
+    warnings: Vec<String>,
+}
+
+impl Loader {
+    fn visit(&mut self, node: &Node, parent: &Matrix) -> Result<(), GltfError> {
+        let local = node
+            .transform()
+            .matrix()
+            .map(|column| column.map(f64::from));
+        let transform = multiply(parent, &local);
+
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                // Points and lines have no surface to render.
+                if primitive.mode() != Mode::Triangles {
+                    continue;
+                }
+                let (material, uv_set) = self.material(&primitive.material())?;
+                if let Some(mesh) = self.triangles(&primitive, &transform, uv_set) {
+                    self.objects.push(Box::new(Mesh::new(mesh, material)));
+                }
+            }
+        }
+
+        if let Some(camera) = node.camera() {
+            if let Projection::Orthographic(_) = camera.projection() {
+                self.warnings.push(format!(
+                    "orthographic camera {} is not supported and was skipped",
+                    camera.index()
+                ));
+            }
+            if let (None, Projection::Perspective(perspective)) =
+                (&self.camera, camera.projection())
+            {
+                let origin = transform_point(&transform, &Point3::default());
+                let forward = transform_vector(&transform, &Vec3::new(0.0, 0.0, -1.0));
+                let up = transform_vector(&transform, &Vec3::new(0.0, 1.0, 0.0));
+                self.camera = Some(Camera::new(
+                    origin,
+                    origin + unit_vector(forward),
+                    up,
+                    f64::from(perspective.yfov()).to_degrees(),
+                    self.aspect_ratio,
+                    0.0,
+                    1.0,
+                    (0.0, 1.0),
+                ));
+            }
+        }
+
+        if let Some(light) = node.light() {
+            if let Kind::Spot { .. } = light.kind() {
+                self.warnings.push(format!(
+                    "spot light {} shines in all directions, its cone is not modelled",
+                    light.index()
+                ));
+            }
+            let [r, g, b] = light.color();
+            self.lights.push(PendingLight {
+                kind: light.kind(),
+                color: Color::new(f64::from(r), f64::from(g), f64::from(b)),
+                intensity: f64::from(light.intensity()) / LUMENS_PER_WATT,
+                transform,
+            });
+        }
+
+        for child in node.children() {
+            self.visit(&child, &transform)?;
+        }
+        Ok(())
+    }
+
+    // The primitive's triangles in world space, with texture coordinates from
+    // `uv_set`.
+    fn triangles(
+        &self,
+        primitive: &::gltf::Primitive,
+        transform: &Matrix,
+        uv_set: u32,
+    ) -> Option<TriangleMesh> {
+        let reader = primitive.reader(|buffer| Some(&self.buffers[buffer.index()]));
+        let positions: Vec<Point3> = reader
+            .read_positions()?
+            .map(|[x, y, z]| {
+                transform_point(
+                    transform,
+                    &Point3::new(f64::from(x), f64::from(y), f64::from(z)),
+                )
+            })
+            .collect();
+
+        let (normal_matrix, determinant) = normal_transform(transform);
+        let mut triangles: Vec<[usize; 3]> = match reader.read_indices() {
+            Some(indices) => {
+                let indices: Vec<usize> = indices.into_u32().map(|i| i as usize).collect();
+                indices
+                    .chunks_exact(3)
+                    .map(|t| [t[0], t[1], t[2]])
+                    .collect()
+            }
+            None => (0..positions.len() / 3)
+                .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+                .collect(),
+        };
+        triangles.retain(|t| t.iter().all(|&i| i < positions.len()));
+        if triangles.is_empty() {
+            return None;
+        }
+        if determinant < 0.0 {
+            for triangle in &mut triangles {
+                triangle.swap(1, 2);
+            }
+        }
+
+        let mut mesh = TriangleMesh::new(positions, triangles);
+        if let Some(normals) = reader.read_normals() {
+            mesh.normals = normals
+                .map(|[x, y, z]| {
+                    let n = f64::from(x) * normal_matrix[0]
+                        + f64::from(y) * normal_matrix[1]
+                        + f64::from(z) * normal_matrix[2];
+                    if n.near_zero() {
+                        n
+                    } else {
+                        unit_vector(n)
+                    }
+                })
+                .collect();
+        }
+        if let Some(uvs) = reader.read_tex_coords(uv_set) {
+            // glTF puts v = 0 at the top of images.
+            mesh.uvs = uvs
+                .into_f32()
+                .map(|[u, v]| (f64::from(u), 1.0 - f64::from(v)))
+                .collect();
+        }
+        if mesh.normals.len() != mesh.positions.len() {
+            mesh.compute_normals();
+        }
+        if mesh.uvs.len() != mesh.positions.len() {
+            mesh.uvs.clear();
+        }
+        Some(mesh)
+    }
+
+    fn material(
+        &mut self,
+        material: &::gltf::Material,
+    ) -> Result<(Arc<dyn Material>, u32), GltfError> {
+        if let Some((cached, uv_set)) = self.materials.get(&material.index()) {
+            return Ok((Arc::clone(cached), *uv_set));
+        }
+
+        let pbr = material.pbr_metallic_roughness();
+        // Meshes carry a single set of texture coordinates, the one the first
+        // texture asks for.
+        let uv_sets: Vec<u32> = [
+            pbr.base_color_texture().map(|info| info.tex_coord()),
+            pbr.metallic_roughness_texture()
+                .map(|info| info.tex_coord()),
+            material.emissive_texture().map(|info| info.tex_coord()),
+            material.normal_texture().map(|normal| normal.tex_coord()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let uv_set = uv_sets.first().copied().unwrap_or(0);
+        if uv_sets.iter().any(|&set| set != uv_set) {
+            self.warnings.push(format!(
+                "material {} uses several texture coordinate sets, all its textures use set {}",
+                material
+                    .index()
+                    .map_or("default".to_string(), |i| i.to_string()),
+                uv_set
+            ));
+        }
+
+        let [r, g, b, _] = pbr.base_color_factor();
+        let mut base_color: Arc<dyn Texture> = Arc::new(SolidColor::new_from_color(Color::new(
+            f64::from(r),
+            f64::from(g),
+            f64::from(b),
+        )));
+        if let Some(info) = pbr.base_color_texture() {
+            let texture = self.texture(&info.texture(), true)?;
+            base_color = Arc::new(MultiplyTexture::new(
+                base_color,
+                texture as Arc<dyn Texture>,
+            ));
+        }
+
+        let mut metallic: Arc<dyn ScalarTexture> =
+            Arc::new(ConstantScalar::new(f64::from(pbr.metallic_factor())));
+        let mut roughness: Arc<dyn ScalarTexture> =
+            Arc::new(ConstantScalar::new(f64::from(pbr.roughness_factor())));
+        if let Some(info) = pbr.metallic_roughness_texture() {
+            // Roughness is in the green channel and metalness in the blue one.
+            let texture: Arc<dyn Texture> = self.texture(&info.texture(), false)?;
+            metallic = Arc::new(MultiplyTexture::new(
+                metallic,
+                Arc::new(Channel::new(Arc::clone(&texture), 2)) as Arc<dyn ScalarTexture>,
+            ));
+            roughness = Arc::new(MultiplyTexture::new(
+                roughness,
+                Arc::new(Channel::new(texture, 1)) as Arc<dyn ScalarTexture>,
+            ));
+        }
+
+        let [r, g, b] = material.emissive_factor();
+        let strength = f64::from(material.emissive_strength().unwrap_or(1.0));
+        let emissive = strength * Color::new(f64::from(r), f64::from(g), f64::from(b));
+        let mut result: Arc<dyn Material> = if emissive.near_zero() {
+            Arc::new(Principled::new_from_texture(
+                base_color, metallic, roughness,
+            ))
+        } else {
+            let mut emission: Arc<dyn Texture> = Arc::new(SolidColor::new_from_color(emissive));
+            if let Some(info) = material.emissive_texture() {
+                let texture = self.texture(&info.texture(), true)?;
+                emission = Arc::new(MultiplyTexture::new(emission, texture as Arc<dyn Texture>));
+            }
+            Arc::new(Principled::new_with_emission(
+                base_color, metallic, roughness, emission,
+            ))
+        };
+
+        if let Some(normal) = material.normal_texture() {
+            let texture = self.texture(&normal.texture(), false)?;
+            result = Arc::new(NormalMap::new_with_scale(
+                result,
+                texture,
+                f64::from(normal.scale()),
+            ));
+        }
+
+        self.materials
+            .insert(material.index(), (Arc::clone(&result), uv_set));
+        Ok((result, uv_set))
+    }
+
+    fn texture(
+        &mut self,
+        texture: &::gltf::Texture,
+        srgb: bool,
+    ) -> Result<Arc<ImageTexture>, GltfError> {
+        let key = (texture.index(), srgb);
+        if let Some(cached) = self.textures.get(&key) {
+            return Ok(Arc::clone(cached));
+        }
+
+        let image = &self.images[texture.source().index()];
+        let data = texels(image, srgb)?;
+        let wrap_mode = |mode| match mode {
+            WrappingMode::ClampToEdge => WrapMode::Clamp,
+            WrappingMode::MirroredRepeat => WrapMode::Mirror,
+            WrappingMode::Repeat => WrapMode::Repeat,
+        };
+        let sampler = texture.sampler();
+        let result = Arc::new(ImageTexture::new_from_data_with_wrap_modes(
+            data,
+            image.width as usize,
+            image.height as usize,
+            (wrap_mode(sampler.wrap_s()), wrap_mode(sampler.wrap_t())),
+            Filter::Bilinear,
+        )?);
+        self.textures.insert(key, Arc::clone(&result));
+        Ok(result)
+    }
+
+    fn finish(mut self) -> Result<GltfScene, GltfError> {
+        let bounds = self
+            .objects
+            .iter()
+            .filter_map(|object| object.bounding_box((0.0, 1.0)))
+            .reduce(surrounding_box);
+        let (center, extent) = match bounds {
+            Some(bounds) => (
+                0.5 * (bounds.min() + bounds.max()),
+                (bounds.max() - bounds.min()).len().max(1e-3),
+            ),
+            None => (Point3::default(), 1.0),
+        };
+
+        for light in std::mem::take(&mut self.lights) {
+            let position = transform_point(&light.transform, &Point3::default());
+            let direction = unit_vector(transform_vector(
+                &light.transform,
+                &Vec3::new(0.0, 0.0, -1.0),
+            ));
+            match light.kind {
+                Kind::Point | Kind::Spot { .. } => {
+                    // A sphere of radius r and radiance L has intensity
+                    // L π r² in every direction.
+                    let radius = LIGHT_RADIUS * extent;
+                    let radiance = light.intensity / (PI * radius * radius) * light.color;
+                    self.objects.push(Box::new(Sphere::new(
+                        position,
+                        radius,
+                        Arc::new(DiffuseLight::new(radiance)),
+                    )));
+                }
+                Kind::Directional => {
+                    // A disc subtending the solid angle Ω gives irradiance LΩ.
+                    let distance = SUN_DISTANCE * extent;
+                    let tangent = SUN_ANGULAR_RADIUS.to_radians().tan();
+                    let solid_angle = PI * tangent * tangent;
+                    let radiance = light.intensity / solid_angle * light.color;
+                    self.objects.push(Box::new(Disk::new(
+                        center - distance * direction,
+                        direction,
+                        distance * tangent,
+                        Arc::new(DiffuseLight::new(radiance)),
+                    )));
+                }
+            }
+        }
+
+        if self.objects.is_empty() {
+            return Err(GltfError::Format("nothing to render".to_string()));
+        }
+
+        let camera = self.camera.unwrap_or_else(|| {
+            Camera::new(
+                center + Vec3::new(0.0, 0.3 * extent, 1.2 * extent),
+                center,
+                Vec3::new(0.0, 1.0, 0.0),
+                40.0,
+                self.aspect_ratio,
+                0.0,
+                1.0,
+                (0.0, 1.0),
+            )
+        });
+
+        Ok(GltfScene {
+            world: BVHNode::new(self.objects, (0.0, 1.0)),
+            camera,
+            warnings: self.warnings,
+        })
+    }
+}
+
+// The image as linear RGBA texels.
+fn texels(image: &::gltf::image::Data, srgb: bool) -> Result<Vec<[f32; 4]>, GltfError> {
+    let (channels, bytes) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+    let value = |bytes: &[u8]| match bytes.len() {
+        1 => f32::from(bytes[0]) / 255.0,
+        2 => f32::from(u16::from_ne_bytes([bytes[0], bytes[1]])) / 65535.0,
+        _ => f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    };
+
+    let texel_size = channels * bytes;
+    if image.pixels.len() != texel_size * image.width as usize * image.height as usize {
+        return Err(GltfError::Format(
+            "image size does not match its data".to_string(),
+        ));
+    }
+    // Floating point images are linear already.
+    let srgb = srgb && bytes < 4;
+    Ok(image
+        .pixels
+        .chunks_exact(texel_size)
+        .map(|texel| {
+            let channel = |c: usize| value(&texel[c * bytes..(c + 1) * bytes]);
+            let decode = |v: f32| if srgb { srgb_to_linear(v) } else { v };
+            match channels {
+                1 => {
+                    let v = decode(channel(0));
+                    [v, v, v, 1.0]
+                }
+                2 => {
+                    let v = decode(channel(0));
+                    [v, v, v, channel(1)]
+                }
+                3 => [
+                    decode(channel(0)),
+                    decode(channel(1)),
+                    decode(channel(2)),
+                    1.0,
+                ],
+                _ => [
+                    decode(channel(0)),
+                    decode(channel(1)),
+                    decode(channel(2)),
+                    channel(3),
+                ],
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::Write};
+
+    use crate::{
+        hits::hittable::Hittable,
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
+
+    use super::GltfScene;
+
+    // A unit quad in the xy plane, its indices and a 2x2 red PNG.
+    fn buffer() -> (Vec<u8>, usize, usize) {
+        let mut bytes = vec![];
+        for [x, y] in [[-1.0f32, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]] {
+            for value in [x, y, 0.0] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        for index in [0u16, 1, 2, 0, 2, 3] {
+            bytes.extend_from_slice(&index.to_le_bytes());
+        }
+        let geometry = bytes.len();
+
+        let mut png = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut png, 2, 2);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[255, 0, 0].repeat(4)).unwrap();
+        }
+        bytes.extend_from_slice(&png);
+        while !bytes.len().is_multiple_of(4) {
+            bytes.push(0);
+        }
+        (bytes, geometry, png.len())
+    }
+
+    // A quad scaled by two under a parent node, a camera at z = 5 and a
+    // point light above.
+    fn json(buffer: &str, length: usize, geometry: usize, png: usize) -> String {
+        format!(
+            r#"{{
+  "asset": {{"version": "2.0"}},
+  "extensionsUsed": ["KHR_lights_punctual"],
+  "extensions": {{"KHR_lights_punctual": {{"lights": [{{"type": "point", "intensity": 6830}}]}}}},
+  "scene": 0,
+  "scenes": [{{"nodes": [0, 2, 3]}}],
+  "nodes": [
+    {{"children": [1], "translation": [0, 0, -1]}},
+    {{"mesh": 0, "scale": [2, 2, 2], "translation": [0, 0, 1]}},
+    {{"camera": 0, "translation": [0, 0, 5]}},
+    {{"translation": [0, 3, 0], "extensions": {{"KHR_lights_punctual": {{"light": 0}}}}}}
+  ],
+  "cameras": [{{"type": "perspective", "perspective": {{"yfov": 0.7, "znear": 0.1}}}}],
+  "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}, "indices": 1, "material": 0}}]}}],
+  "materials": [{{"pbrMetallicRoughness": {{"baseColorTexture": {{"index": 0}}, "metallicFactor": 0}}}}],
+  "textures": [{{"source": 0}}],
+  "images": [{{"bufferView": 2, "mimeType": "image/png"}}],
+  "accessors": [
+    {{"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [-1, -1, 0], "max": [1, 1, 0]}},
+    {{"bufferView": 1, "componentType": 5123, "count": 6, "type": "SCALAR"}}
+  ],
+  "bufferViews": [
+    {{"buffer": 0, "byteOffset": 0, "byteLength": 48}},
+    {{"buffer": 0, "byteOffset": 48, "byteLength": 12}},
+    {{"buffer": 0, "byteOffset": {geometry}, "byteLength": {png}}}
+  ],
+  "buffers": [{{"byteLength": {length}{buffer}}}]
+}}"#
+        )
+    }
+
+    fn check(scene: &GltfScene) {
+        assert!(scene.warnings.is_empty(), "{:?}", scene.warnings);
+        let r = scene.camera.get_pinhole_ray(0.5, 0.5);
+        assert!((r.origin() - Point3::new(0.0, 0.0, 5.0)).len() < 1e-9);
+
+        let hit = |x: f64| {
+            scene.world.hit(
+                &Ray::new(Point3::new(x, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0),
+                (0.001, f64::INFINITY),
+            )
+        };
+        let inside = hit(1.5).unwrap();
+        assert!((inside.t - 5.0).abs() < 1e-6);
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        let f = inside.material.eval(&inside, &normal, &normal);
+        assert!(f.x() > 2.0 * f.y(), "the base color texture is red");
+        assert!(hit(2.5).is_none());
+
+        let light = scene
+            .world
+            .hit(
+                &Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.0),
+                (0.001, f64::INFINITY),
+            )
+            .unwrap();
+        assert!(light.material.is_emissive());
+    }
+
+    #[test]
+    fn binary_and_external_buffers() {
+        let (bytes, geometry, png) = buffer();
+        let directory = env::temp_dir().join(format!("gltf-test-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        // GLB: a JSON chunk then a binary chunk holding the buffer.
+        let mut chunk = json("", bytes.len(), geometry, png).into_bytes();
+        while !chunk.len().is_multiple_of(4) {
+            chunk.push(b' ');
+        }
+        let length = 12 + 8 + chunk.len() + 8 + bytes.len();
+        let glb = directory.join("quad.glb");
+        let mut file = fs::File::create(&glb).unwrap();
+        file.write_all(b"glTF").unwrap();
+        file.write_all(&2u32.to_le_bytes()).unwrap();
+        file.write_all(&(length as u32).to_le_bytes()).unwrap();
+        file.write_all(&(chunk.len() as u32).to_le_bytes()).unwrap();
+        file.write_all(b"JSON").unwrap();
+        file.write_all(&chunk).unwrap();
+        file.write_all(&(bytes.len() as u32).to_le_bytes()).unwrap();
+        file.write_all(b"BIN\0").unwrap();
+        file.write_all(&bytes).unwrap();
+        drop(file);
+        check(&GltfScene::new(glb.to_str().unwrap(), 1.0).unwrap());
+
+        // JSON next to a separate buffer file.
+        fs::write(directory.join("quad.bin"), &bytes).unwrap();
+        let gltf = directory.join("quad.gltf");
+        fs::write(
+            &gltf,
+            json(r#", "uri": "quad.bin""#, bytes.len(), geometry, png),
+        )
+        .unwrap();
+        check(&GltfScene::new(gltf.to_str().unwrap(), 1.0).unwrap());
+
+        assert!(GltfScene::new(directory.join("missing.gltf").to_str().unwrap(), 1.0).is_err());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
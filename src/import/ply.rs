@@ -0,0 +1,561 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+};
+
+use crate::{
+    objects::{
+        mesh::TriangleMesh,
+        point_cloud::CloudPoint,
+        subdivision::{has_repeated_vertex, ControlMesh},
+    },
+    textures::image_texture::srgb_to_linear,
+    vec3::{cross, Color, Point3, Vec3},
+};
+
+use super::ImportError;
+
+// Reads the vertices and faces of a PLY file, ASCII or binary, into a
+// triangle mesh. Normals, surface coordinates and colors are kept when the
+// vertices have them, polygons are split into fans and degenerate triangles
+// are dropped.
+pub fn load(path: &str) -> Result<TriangleMesh, ImportError> {
+    read(&mut BufReader::new(File::open(path)?))
+}
+
+pub fn read(reader: &mut impl BufRead) -> Result<TriangleMesh, ImportError> {
+    let elements = read_elements(reader)?;
+    let vertices = elements
+        .get("vertex")
+        .ok_or_else(|| format_error("no vertex element"))?;
+    let mut mesh = TriangleMesh::new(positions(vertices)?, vec![]);
+
+    if let Some([x, y, z]) = vertices.columns(["nx", "ny", "nz"]) {
+        mesh.normals = (0..vertices.count)
+            .map(|i| Vec3::new(x[i], y[i], z[i]))
+            .collect();
+    }
+    let uvs = [
+        ["u", "v"],
+        ["s", "t"],
+        ["texture_u", "texture_v"],
+        ["texture_s", "texture_t"],
+    ];
+    if let Some([u, v]) = uvs.into_iter().find_map(|names| vertices.columns(names)) {
+        mesh.uvs = (0..vertices.count).map(|i| (u[i], v[i])).collect();
+    }
+    if let Some(colors) = colors(vertices) {
+        mesh.colors = colors;
+    }
+
+    for face in faces(&elements, mesh.positions.len())? {
+        for i in 1..face.len().saturating_sub(1) {
+            let triangle = [face[0], face[i], face[i + 1]];
+            if !is_degenerate(&mesh.positions, triangle) {
+                mesh.triangles.push(triangle);
+            }
+        }
+    }
+    Ok(mesh)
+}
+
+// Reads the vertices and faces of a PLY file as a subdivision cage, keeping
+// the polygons as they are. Faces that use a vertex twice are dropped.
+pub fn load_control_mesh(path: &str) -> Result<ControlMesh, ImportError> {
+    read_control_mesh(&mut BufReader::new(File::open(path)?))
+}
+
+pub fn read_control_mesh(reader: &mut impl BufRead) -> Result<ControlMesh, ImportError> {
+    let elements = read_elements(reader)?;
+    let vertices = elements
+        .get("vertex")
+        .ok_or_else(|| format_error("no vertex element"))?;
+    let positions = positions(vertices)?;
+    let mut faces = faces(&elements, positions.len())?;
+    faces.retain(|face| !has_repeated_vertex(face));
+    ControlMesh::new(positions, faces).map_err(|error| format_error(&error.to_string()))
+}
+
+// Reads the vertices of a PLY file as points, with a `radius` property or
+// else the given radius, and white unless they have colors.
+pub fn load_points(path: &str, radius: f64) -> Result<Vec<CloudPoint>, ImportError> {
+    read_points(&mut BufReader::new(File::open(path)?), radius)
+}
+
+pub fn read_points(reader: &mut impl BufRead, radius: f64) -> Result<Vec<CloudPoint>, ImportError> {
+    let elements = read_elements(reader)?;
+    let vertices = elements
+        .get("vertex")
+        .ok_or_else(|| format_error("no vertex element"))?;
+    let positions = positions(vertices)?;
+    let radii = vertices.scalars.get("radius");
+    let colors = colors(vertices);
+    Ok((0..vertices.count)
+        .map(|i| {
+            CloudPoint::new(
+                positions[i],
+                radii.map_or(radius, |radii| radii[i]),
+                colors
+                    .as_ref()
+                    .map_or(Color::new(1.0, 1.0, 1.0), |colors| colors[i]),
+            )
+        })
+        .collect())
+}
+
+fn format_error(message: &str) -> ImportError {
+    ImportError::Format(message.to_string())
+}
+
+// The vertex indices of every face, checked against the number of vertices.
+fn faces(
+    elements: &HashMap<String, Element>,
+    vertex_count: usize,
+) -> Result<Vec<Vec<usize>>, ImportError> {
+    let faces = elements
+        .get("face")
+        .and_then(|faces| {
+            faces
+                .lists
+                .get("vertex_indices")
+                .or_else(|| faces.lists.get("vertex_index"))
+        })
+        .ok_or_else(|| format_error("no face element with vertex indices"))?;
+    faces
+        .iter()
+        .map(|face| {
+            face.iter()
+                .map(|&index| {
+                    if index >= 0.0 && (index as usize) < vertex_count {
+                        Ok(index as usize)
+                    } else {
+                        Err(format_error(&format!("vertex index {index} out of range")))
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn positions(vertices: &Element) -> Result<Vec<Point3>, ImportError> {
+    let [x, y, z] = vertices
+        .columns(["x", "y", "z"])
+        .ok_or_else(|| format_error("vertices without positions"))?;
+    let positions: Vec<Point3> = (0..vertices.count)
+        .map(|i| Point3::new(x[i], y[i], z[i]))
+        .collect();
+    if positions
+        .iter()
+        .any(|p| !(p.x().is_finite() && p.y().is_finite() && p.z().is_finite()))
+    {
+        return Err(format_error("vertex positions that are not finite"));
+    }
+    Ok(positions)
+}
+
+// Vertex colors are taken as sRGB, stored either as integers over their
+// full range or as numbers from zero to one.
+fn colors(vertices: &Element) -> Option<Vec<Color>> {
+    let names = [
+        ["red", "green", "blue"],
+        ["r", "g", "b"],
+        ["diffuse_red", "diffuse_green", "diffuse_blue"],
+    ];
+    let (names, [r, g, b]) = names
+        .into_iter()
+        .find_map(|names| Some((names, vertices.columns(names)?)))?;
+    let scale = match vertices.types[names[0]] {
+        Type::UInt8 | Type::Int8 => 1.0 / 255.0,
+        Type::UInt16 | Type::Int16 => 1.0 / 65535.0,
+        _ => 1.0,
+    };
+    let channel = |value: f64| f64::from(srgb_to_linear((scale * value) as f32));
+    Some(
+        (0..vertices.count)
+            .map(|i| Color::new(channel(r[i]), channel(g[i]), channel(b[i])))
+            .collect(),
+    )
+}
+
+fn is_degenerate(positions: &[Point3], [a, b, c]: [usize; 3]) -> bool {
+    a == b
+        || b == c
+        || a == c
+        || cross(
+            &(positions[b] - positions[a]),
+            &(positions[c] - positions[a]),
+        )
+        .len_squared()
+            == 0.0
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Type {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl Type {
+    fn parse(name: &str) -> Result<Self, ImportError> {
+        Ok(match name {
+            "char" | "int8" => Type::Int8,
+            "uchar" | "uint8" => Type::UInt8,
+            "short" | "int16" => Type::Int16,
+            "ushort" | "uint16" => Type::UInt16,
+            "int" | "int32" => Type::Int32,
+            "uint" | "uint32" => Type::UInt32,
+            "float" | "float32" => Type::Float32,
+            "double" | "float64" => Type::Float64,
+            _ => return Err(format_error(&format!("unknown property type {name}"))),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Type::Int8 | Type::UInt8 => 1,
+            Type::Int16 | Type::UInt16 => 2,
+            Type::Int32 | Type::UInt32 | Type::Float32 => 4,
+            Type::Float64 => 8,
+        }
+    }
+}
+
+enum Property {
+    Scalar(String, Type),
+    // The types of the count and of the items.
+    List(String, Type, Type),
+}
+
+// An element's properties, stored by column.
+#[derive(Default)]
+struct Element {
+    count: usize,
+    types: HashMap<String, Type>,
+    scalars: HashMap<String, Vec<f64>>,
+    lists: HashMap<String, Vec<Vec<f64>>>,
+}
+
+impl Element {
+    fn columns<const N: usize>(&self, names: [&str; N]) -> Option<[&Vec<f64>; N]> {
+        let columns = names.map(|name| self.scalars.get(name));
+        columns
+            .iter()
+            .all(Option::is_some)
+            .then(|| columns.map(Option::unwrap))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+// Reads the header and then every element of the body.
+fn read_elements(reader: &mut impl BufRead) -> Result<HashMap<String, Element>, ImportError> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if line.trim() != "ply" {
+        return Err(format_error("missing PLY header"));
+    }
+
+    let mut encoding = None;
+    let mut layout: Vec<(String, usize, Vec<Property>)> = vec![];
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(format_error("unterminated header"));
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["end_header"] => break,
+            ["format", format, _] => {
+                encoding = Some(match *format {
+                    "ascii" => Encoding::Ascii,
+                    "binary_little_endian" => Encoding::LittleEndian,
+                    "binary_big_endian" => Encoding::BigEndian,
+                    _ => return Err(format_error(&format!("unknown format {format}"))),
+                })
+            }
+            ["element", name, count] => {
+                let count = count
+                    .parse()
+                    .map_err(|_| format_error(&format!("bad element count {count}")))?;
+                layout.push((name.to_string(), count, vec![]));
+            }
+            ["property", "list", count, item, name] => layout
+                .last_mut()
+                .ok_or_else(|| format_error("property before any element"))?
+                .2
+                .push(Property::List(
+                    name.to_string(),
+                    Type::parse(count)?,
+                    Type::parse(item)?,
+                )),
+            ["property", kind, name] => layout
+                .last_mut()
+                .ok_or_else(|| format_error("property before any element"))?
+                .2
+                .push(Property::Scalar(name.to_string(), Type::parse(kind)?)),
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => {
+                return Err(format_error(&format!(
+                    "unexpected header line {}",
+                    line.trim()
+                )))
+            }
+        }
+    }
+    let encoding = encoding.ok_or_else(|| format_error("missing format"))?;
+
+    // The columns grow as values are read rather than from the header
+    // counts, so a corrupt count runs into the end of the body instead of
+    // asking for more memory than there is.
+    let mut body = Body::new(reader, encoding)?;
+    let mut elements = HashMap::new();
+    for (name, count, properties) in layout {
+        let mut element = Element {
+            count,
+            ..Default::default()
+        };
+        for property in &properties {
+            match property {
+                Property::Scalar(name, kind) => {
+                    element.types.insert(name.clone(), *kind);
+                    element.scalars.insert(name.clone(), vec![]);
+                }
+                Property::List(name, _, kind) => {
+                    element.types.insert(name.clone(), *kind);
+                    element.lists.insert(name.clone(), vec![]);
+                }
+            }
+        }
+
+        for _ in 0..count {
+            for property in &properties {
+                match property {
+                    Property::Scalar(name, kind) => {
+                        let value = body.value(*kind)?;
+                        element.scalars.get_mut(name).unwrap().push(value);
+                    }
+                    Property::List(name, count_kind, kind) => {
+                        let length = body.value(*count_kind)?;
+                        if !(0.0..=f64::from(u16::MAX)).contains(&length) {
+                            return Err(format_error(&format!("bad list length {length}")));
+                        }
+                        let items = (0..length as usize)
+                            .map(|_| body.value(*kind))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        element.lists.get_mut(name).unwrap().push(items);
+                    }
+                }
+            }
+        }
+        elements.insert(name, element);
+    }
+    Ok(elements)
+}
+
+// The data after the header, as whitespace separated text or packed values.
+enum Body<'a, R: Read> {
+    // The text and how far into it has been read.
+    Ascii(String, usize),
+    Binary(&'a mut R, bool),
+}
+
+impl<'a, R: Read> Body<'a, R> {
+    fn new(reader: &'a mut R, encoding: Encoding) -> Result<Self, ImportError> {
+        Ok(match encoding {
+            Encoding::Ascii => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                Body::Ascii(text, 0)
+            }
+            Encoding::LittleEndian => Body::Binary(reader, false),
+            Encoding::BigEndian => Body::Binary(reader, true),
+        })
+    }
+
+    fn value(&mut self, kind: Type) -> Result<f64, ImportError> {
+        match self {
+            Body::Ascii(text, position) => {
+                let rest = &text[*position..];
+                let start = rest
+                    .find(|c: char| !c.is_ascii_whitespace())
+                    .ok_or_else(|| format_error("body ends early"))?;
+                let length = rest[start..]
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .unwrap_or(rest.len() - start);
+                let word = &rest[start..start + length];
+                *position += start + length;
+                word.parse()
+                    .map_err(|_| format_error(&format!("bad number {word}")))
+            }
+            Body::Binary(reader, big_endian) => {
+                let mut bytes = [0; 8];
+                let bytes = &mut bytes[..kind.size()];
+                reader
+                    .read_exact(bytes)
+                    .map_err(|error| match error.kind() {
+                        std::io::ErrorKind::UnexpectedEof => format_error("body ends early"),
+                        _ => ImportError::Io(error),
+                    })?;
+                if *big_endian {
+                    bytes.reverse();
+                }
+                let array = |bytes: &[u8]| {
+                    let mut array = [0; 8];
+                    array[..bytes.len()].copy_from_slice(bytes);
+                    array
+                };
+                let a = array(bytes);
+                Ok(match kind {
+                    Type::Int8 => f64::from(a[0] as i8),
+                    Type::UInt8 => f64::from(a[0]),
+                    Type::Int16 => f64::from(i16::from_le_bytes([a[0], a[1]])),
+                    Type::UInt16 => f64::from(u16::from_le_bytes([a[0], a[1]])),
+                    Type::Int32 => f64::from(i32::from_le_bytes([a[0], a[1], a[2], a[3]])),
+                    Type::UInt32 => f64::from(u32::from_le_bytes([a[0], a[1], a[2], a[3]])),
+                    Type::Float32 => f64::from(f32::from_le_bytes([a[0], a[1], a[2], a[3]])),
+                    Type::Float64 => f64::from_le_bytes(a),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hits::hittable::Hittable,
+        materials::lambertian::Lambertian,
+        objects::mesh::Mesh,
+        ray::Ray,
+        textures::Texture,
+        vec3::{Point3, Vec3},
+    };
+
+    use super::{read, read_control_mesh, read_points};
+
+    // A square with red, green, blue and white corners as one quad, plus a
+    // face repeating a vertex.
+    const HEADER: &str = "ply\nformat {format} 1.0\ncomment test\nelement vertex 4\n\
+        property float x\nproperty float y\nproperty float z\n\
+        property uchar red\nproperty uchar green\nproperty uchar blue\n\
+        element face 2\nproperty list uchar int vertex_indices\nend_header\n";
+    const VERTICES: [([f32; 3], [u8; 3]); 4] = [
+        ([0.0, 0.0, 0.0], [255, 0, 0]),
+        ([1.0, 0.0, 0.0], [0, 255, 0]),
+        ([1.0, 1.0, 0.0], [0, 0, 255]),
+        ([0.0, 1.0, 0.0], [255, 255, 255]),
+    ];
+    const FACES: [&[i32]; 2] = [&[0, 1, 2, 3], &[0, 1, 1]];
+
+    fn file(format: &str) -> Vec<u8> {
+        let mut bytes = HEADER.replace("{format}", format).into_bytes();
+        let big_endian = format == "binary_big_endian";
+        let mut push = |value: &[u8]| {
+            if big_endian {
+                bytes.extend(value.iter().rev());
+            } else {
+                bytes.extend_from_slice(value);
+            }
+        };
+        if format == "ascii" {
+            let mut text = String::new();
+            for (p, c) in VERTICES {
+                text += &format!("{} {} {} {} {} {}\n", p[0], p[1], p[2], c[0], c[1], c[2]);
+            }
+            for face in FACES {
+                text += &format!("{}", face.len());
+                for index in face {
+                    text += &format!(" {index}");
+                }
+                text += "\n";
+            }
+            push(text.as_bytes());
+        } else {
+            for (p, c) in VERTICES {
+                for value in p {
+                    push(&value.to_le_bytes());
+                }
+                for value in c {
+                    push(&[value]);
+                }
+            }
+            for face in FACES {
+                push(&[face.len() as u8]);
+                for index in face {
+                    push(&index.to_le_bytes());
+                }
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn encodings_agree_and_colors_bake() {
+        for format in ["ascii", "binary_little_endian", "binary_big_endian"] {
+            let mut mesh = read(&mut file(format).as_slice()).unwrap();
+            assert_eq!(mesh.positions.len(), 4);
+            assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 2, 3]]);
+            assert!((mesh.colors[1] - Vec3::new(0.0, 1.0, 0.0)).len() < 1e-9);
+
+            let texture = Arc::new(mesh.bake_colors().unwrap());
+            let mesh = Mesh::new(
+                mesh,
+                Arc::new(Lambertian::new_from_texture(texture.clone())),
+            );
+            // Halfway along the edge from the red to the green corner.
+            let hit = mesh
+                .hit(
+                    &Ray::new(
+                        Point3::new(0.5, 0.0001, 1.0),
+                        Vec3::new(0.0, 0.0, -1.0),
+                        0.0,
+                    ),
+                    (0.001, f64::INFINITY),
+                )
+                .unwrap();
+            let color = texture.value(hit.surface_coordinates, &hit.p);
+            assert!((color - Vec3::new(0.5, 0.5, 0.0)).len() < 1e-3, "{color:?}");
+
+            let cage = read_control_mesh(&mut file(format).as_slice()).unwrap();
+            assert_eq!(cage.faces(), [vec![0, 1, 2, 3]]);
+
+            let points = read_points(&mut file(format).as_slice(), 0.1).unwrap();
+            assert_eq!(points.len(), 4);
+            assert_eq!(points[2].color, [0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn errors_are_reported() {
+        let truncated = file("binary_little_endian");
+        assert!(read(&mut &truncated[..truncated.len() - 3]).is_err());
+        assert!(read(&mut "plx\n".as_bytes()).is_err());
+        let out_of_range = String::from_utf8(file("ascii"))
+            .unwrap()
+            .replace("4 0 1 2 3", "4 0 1 2 9");
+        assert!(read(&mut out_of_range.as_bytes()).is_err());
+        for format in ["ascii", "binary_little_endian"] {
+            let bytes = file(format);
+            let body = HEADER.len() + format.len() - "{format}".len();
+            let header = String::from_utf8(bytes[..body].to_vec())
+                .unwrap()
+                .replace("element vertex 4", "element vertex 100000000000000");
+            let huge = [header.as_bytes(), &bytes[body..]].concat();
+            assert!(read(&mut huge.as_slice()).is_err());
+        }
+    }
+}
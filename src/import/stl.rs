@@ -0,0 +1,161 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+};
+
+use crate::{
+    objects::mesh::TriangleMesh,
+    vec3::{cross, Point3},
+};
+
+use super::ImportError;
+
+// Size of the header of binary STL files and of each of their triangles.
+const BINARY_HEADER: usize = 84;
+const BINARY_TRIANGLE: usize = 50;
+
+// Reads an STL file, ASCII or binary, into a triangle mesh. STL stores
+// every triangle on its own and CAD models are faceted, so vertices are not
+// shared and the mesh is shaded flat. The stored facet normals are ignored
+// in favour of the winding, and degenerate triangles are dropped.
+pub fn load(path: &str) -> Result<TriangleMesh, ImportError> {
+    read(&mut BufReader::new(File::open(path)?))
+}
+
+pub fn read(reader: &mut impl Read) -> Result<TriangleMesh, ImportError> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+
+    // Binary files may start with "solid" too, but their size gives them
+    // away.
+    let binary_count = (bytes.len() >= BINARY_HEADER)
+        .then(|| u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize);
+    let corners = match binary_count {
+        Some(count) if bytes.len() == BINARY_HEADER + count * BINARY_TRIANGLE => {
+            read_binary(&bytes[BINARY_HEADER..], count)
+        }
+        _ if bytes.starts_with(b"solid") => read_ascii(&bytes)?,
+        _ => {
+            return Err(ImportError::Format(
+                "neither ASCII nor binary STL".to_string(),
+            ))
+        }
+    };
+
+    let mut mesh = TriangleMesh::default();
+    for triangle in corners.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+        let finite = triangle
+            .iter()
+            .all(|p| p.x().is_finite() && p.y().is_finite() && p.z().is_finite());
+        if !finite || cross(&(b - a), &(c - a)).len_squared() == 0.0 {
+            continue;
+        }
+        let start = mesh.positions.len();
+        mesh.positions.extend_from_slice(triangle);
+        mesh.triangles.push([start, start + 1, start + 2]);
+    }
+    Ok(mesh)
+}
+
+fn read_binary(bytes: &[u8], count: usize) -> Vec<Point3> {
+    let value =
+        |bytes: &[u8]| f64::from(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+    bytes
+        .chunks_exact(BINARY_TRIANGLE)
+        .take(count)
+        .flat_map(|triangle| {
+            // Skips the normal and ends before the attribute byte count.
+            (1..4).map(move |corner| {
+                let p = &triangle[12 * corner..12 * corner + 12];
+                Point3::new(value(&p[0..4]), value(&p[4..8]), value(&p[8..12]))
+            })
+        })
+        .collect()
+}
+
+fn read_ascii(bytes: &[u8]) -> Result<Vec<Point3>, ImportError> {
+    let format_error = |message: String| ImportError::Format(message);
+    let text = std::str::from_utf8(bytes)
+        .map_err(|_| format_error("ASCII STL that is not text".to_string()))?;
+
+    let mut corners = vec![];
+    let mut in_loop = 0;
+    let mut words = text.split_ascii_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "outer" => in_loop = 0,
+            "vertex" => {
+                let mut coordinate = || {
+                    let word = words
+                        .next()
+                        .ok_or_else(|| format_error("vertex without coordinates".to_string()))?;
+                    word.parse::<f64>()
+                        .map_err(|_| format_error(format!("bad coordinate {word}")))
+                };
+                corners.push(Point3::new(coordinate()?, coordinate()?, coordinate()?));
+                in_loop += 1;
+            }
+            "endloop" if in_loop != 3 => {
+                return Err(format_error(format!(
+                    "facet with {in_loop} vertices instead of 3"
+                )))
+            }
+            _ => {}
+        }
+    }
+    if corners.len() % 3 != 0 {
+        return Err(format_error("unterminated facet".to_string()));
+    }
+    Ok(corners)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read;
+
+    // Two triangles of a square and a degenerate one, in both encodings.
+    const TRIANGLES: [[[f32; 3]; 3]; 3] = [
+        [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]],
+        [[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]],
+        [[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [2.0, 2.0, 0.0]],
+    ];
+
+    #[test]
+    fn ascii_and_binary_agree() {
+        let mut ascii = "solid square\n".to_string();
+        for triangle in TRIANGLES {
+            ascii += "facet normal 0 0 1\nouter loop\n";
+            for [x, y, z] in triangle {
+                ascii += &format!("vertex {x} {y} {z}\n");
+            }
+            ascii += "endloop\nendfacet\n";
+        }
+        ascii += "endsolid square\n";
+
+        // The header starts with "solid" to look like text.
+        let mut binary = b"solid".to_vec();
+        binary.resize(80, b' ');
+        binary.extend_from_slice(&(TRIANGLES.len() as u32).to_le_bytes());
+        for triangle in TRIANGLES {
+            binary.extend_from_slice(&[0; 12]);
+            for value in triangle.iter().flatten() {
+                binary.extend_from_slice(&value.to_le_bytes());
+            }
+            binary.extend_from_slice(&[0; 2]);
+        }
+
+        let ascii = read(&mut ascii.as_bytes()).unwrap();
+        let binary = read(&mut binary.as_slice()).unwrap();
+        assert_eq!(ascii.triangles.len(), 2);
+        assert!(ascii
+            .positions
+            .iter()
+            .zip(&binary.positions)
+            .all(|(a, b)| (*a - *b).len() == 0.0));
+        assert_eq!(ascii.triangles, binary.triangles);
+
+        assert!(read(&mut "solid\nfacet\nouter loop\nvertex 0 0 0\nendloop\n".as_bytes()).is_err());
+        assert!(read(&mut [0u8; 10].as_slice()).is_err());
+    }
+}
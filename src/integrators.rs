@@ -0,0 +1,4 @@
+pub mod bdpt;
+pub mod light_list;
+pub mod mlt;
+pub mod sppm;
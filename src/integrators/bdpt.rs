@@ -0,0 +1,619 @@
+use std::f64::consts::PI;
+
+use crate::{
+    camera::Camera,
+    film::Film,
+    hits::hittable::{HitRecord, Hittable},
+    onb::Onb,
+    random_f64,
+    ray::Ray,
+    vec3::{dot, sample_cosine_hemisphere, unit_vector, Color, Point3, Vec3},
+};
+
+use super::light_list::LightList;
+
+// Fraction of a connection that is left out at both ends of visibility rays.
+const SHADOW_EPSILON: f64 = 0.0001;
+
+#[derive(Clone, Copy, PartialEq)]
+enum TransportMode {
+    Radiance,
+    Importance,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum VertexKind {
+    Camera,
+    Light,
+    Surface,
+}
+
+#[derive(Clone)]
+struct Vertex {
+    kind: VertexKind,
+    mode: TransportMode,
+    p: Point3,
+    // Geometric and shading normals, zero at the camera and inside media.
+    normal: Vec3,
+    shading_normal: Vec3,
+    hitrecord: Option<HitRecord>,
+    frame: Onb,
+    // Unit direction towards the previous vertex of the subpath.
+    wo: Vec3,
+    beta: Color,
+    delta: bool,
+    // Area densities of sampling this vertex from the previous one and, in
+    // the other direction, from the next one.
+    pdf_fwd: f64,
+    pdf_rev: f64,
+}
+
+impl Vertex {
+    fn camera(origin: Point3) -> Self {
+        Self {
+            kind: VertexKind::Camera,
+            mode: TransportMode::Radiance,
+            p: origin,
+            normal: Vec3::default(),
+            shading_normal: Vec3::default(),
+            hitrecord: None,
+            frame: Onb::default(),
+            wo: Vec3::default(),
+            beta: Color::new(1.0, 1.0, 1.0),
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn light(hitrecord: HitRecord, beta: Color, pdf_fwd: f64) -> Self {
+        Self {
+            kind: VertexKind::Light,
+            mode: TransportMode::Importance,
+            p: hitrecord.p,
+            normal: hitrecord.normal,
+            shading_normal: hitrecord.normal,
+            frame: Onb::build_from_w(&hitrecord.normal),
+            hitrecord: Some(hitrecord),
+            wo: Vec3::default(),
+            beta,
+            delta: false,
+            pdf_fwd,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn surface(hitrecord: HitRecord, wo: Vec3, beta: Color, mode: TransportMode) -> Self {
+        let shading_normal = hitrecord.material.shading_normal(&hitrecord);
+        let frame = hitrecord.shading_frame(&shading_normal);
+        let (normal, shading_normal) = if hitrecord.material.is_phase_function() {
+            (Vec3::default(), Vec3::default())
+        } else {
+            (hitrecord.normal, shading_normal)
+        };
+        Self {
+            kind: VertexKind::Surface,
+            mode,
+            p: hitrecord.p,
+            normal,
+            shading_normal,
+            hitrecord: Some(hitrecord),
+            frame,
+            wo,
+            beta,
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn is_on_surface(&self) -> bool {
+        !self.normal.near_zero()
+    }
+
+    // On an emitter that light sampling can also pick.
+    fn is_light(&self) -> bool {
+        self.hitrecord
+            .as_ref()
+            .is_some_and(|hitrecord| hitrecord.material.is_emissive() && hitrecord.samplable)
+    }
+
+    // Radiance leaving the vertex, lights are two-sided.
+    fn emitted(&self) -> Color {
+        match &self.hitrecord {
+            Some(hitrecord) => hitrecord
+                .material
+                .emitted(hitrecord.surface_coordinates, &hitrecord.p),
+            None => Color::default(),
+        }
+    }
+
+    // The scattering function for light arriving from `next` and leaving
+    // towards the previous vertex, or the other way round on light paths.
+    fn f(&self, next: &Vertex) -> Color {
+        let hitrecord = match (&self.hitrecord, self.kind) {
+            (Some(hitrecord), VertexKind::Surface) => hitrecord,
+            _ => return Color::default(),
+        };
+        let wi = unit_vector(next.p - self.p);
+        let f = hitrecord.material.eval(
+            hitrecord,
+            &self.frame.to_local(&self.wo),
+            &self.frame.to_local(&wi),
+        );
+        f * self.shading_correction(&wi)
+    }
+
+    // Light paths scatter with the shading normal but arrive through the
+    // geometric one, which makes the adjoint differ from the plain BSDF.
+    fn shading_correction(&self, wi: &Vec3) -> f64 {
+        if self.mode == TransportMode::Radiance || !self.is_on_surface() {
+            return 1.0;
+        }
+        let denominator = dot(&self.wo, &self.normal).abs() * dot(wi, &self.shading_normal).abs();
+        if denominator == 0.0 {
+            return 0.0;
+        }
+        dot(&self.wo, &self.shading_normal).abs() * dot(wi, &self.normal).abs() / denominator
+    }
+
+    fn convert_density(&self, pdf: f64, next: &Vertex) -> f64 {
+        let w = next.p - self.p;
+        let distance_squared = w.len_squared();
+        if distance_squared == 0.0 {
+            return 0.0;
+        }
+        let mut pdf = pdf / distance_squared;
+        if next.is_on_surface() {
+            pdf *= dot(&next.normal, &w).abs() / distance_squared.sqrt();
+        }
+        pdf
+    }
+
+    // Area density at `next` of continuing a subpath that came from `prev`.
+    fn pdf(&self, camera: &Camera, prev: Option<&Vertex>, next: &Vertex) -> f64 {
+        match self.kind {
+            VertexKind::Light => self.pdf_light(next),
+            VertexKind::Camera => {
+                self.convert_density(camera.pdf_direction(&(next.p - self.p)), next)
+            }
+            VertexKind::Surface => {
+                let (hitrecord, prev) = match (&self.hitrecord, prev) {
+                    (Some(hitrecord), Some(prev)) => (hitrecord, prev),
+                    _ => return 0.0,
+                };
+                let wo = self.frame.to_local(&unit_vector(prev.p - self.p));
+                let wi = self.frame.to_local(&unit_vector(next.p - self.p));
+                self.convert_density(hitrecord.material.pdf(hitrecord, &wo, &wi), next)
+            }
+        }
+    }
+
+    // Area density at `next` of a light path leaving this vertex, which is
+    // on a light.
+    fn pdf_light(&self, next: &Vertex) -> f64 {
+        let w = unit_vector(next.p - self.p);
+        self.convert_density(dot(&self.normal, &w).abs() / (2.0 * PI), next)
+    }
+
+    fn pdf_light_origin(&self, lights: &LightList) -> f64 {
+        if self.is_light() {
+            lights.pdf_area()
+        } else {
+            0.0
+        }
+    }
+
+    // Origin for rays leaving towards `target`, off the surface.
+    fn spawn_point(&self, target: &Point3) -> Point3 {
+        match &self.hitrecord {
+            Some(hitrecord) => hitrecord.offset_origin(&(*target - self.p)),
+            None => self.p,
+        }
+    }
+}
+
+// Bidirectional path tracing: every pixel sample traces a camera subpath and
+// a light subpath and combines all ways of connecting their vertices with
+// multiple importance sampling. Connections to the camera are splatted onto
+// the film. The camera is treated as a pinhole and the background as an
+// unsampled light, nested interiors are not tracked.
+pub struct Bdpt<'a> {
+    camera: &'a Camera,
+    world: &'a dyn Hittable,
+    lights: LightList<'a>,
+    background: Color,
+    max_depth: u32,
+}
+
+impl<'a> Bdpt<'a> {
+    pub fn new(
+        camera: &'a Camera,
+        world: &'a dyn Hittable,
+        background: Color,
+        max_depth: u32,
+    ) -> Self {
+        Self {
+            camera,
+            world,
+            lights: LightList::new(world),
+            background,
+            max_depth,
+        }
+    }
+
+    pub fn render(&self, width: u32, height: u32, samples_per_pixel: u32) -> Film {
+        let mut film = Film::new(width, height);
+
+        for j in (0..height).rev() {
+            eprint!("\r{} / {} lines rendered...", height - j, height);
+            for i in 0..width {
+                for _ in 0..samples_per_pixel {
+                    let s = (f64::from(i) + random_f64()) / f64::from(width);
+                    let t = (f64::from(j) + random_f64()) / f64::from(height);
+                    let color = self.sample(s, t, &mut film);
+                    film.add_sample((i, j), color);
+                }
+            }
+        }
+
+        film
+    }
+
+    // Radiance through film position (s, t), splatting light tracing
+    // contributions into `film` on the way.
+    fn sample(&self, s: f64, t: f64, film: &mut Film) -> Color {
+        let r = self.camera.get_pinhole_ray(s, t);
+        let time = r.time();
+
+        let mut camera_path = vec![Vertex::camera(r.origin())];
+        let pdf_dir = self.camera.pdf_direction(&r.direction());
+        let escaped = self.random_walk(
+            r,
+            Color::new(1.0, 1.0, 1.0),
+            pdf_dir,
+            self.max_depth as usize + 1,
+            TransportMode::Radiance,
+            &mut camera_path,
+        );
+
+        let mut light_path = vec![];
+        self.generate_light_path(time, &mut light_path);
+
+        let mut radiance = match escaped {
+            Some(beta) => beta * self.background,
+            None => Color::default(),
+        };
+
+        for t in 2..=camera_path.len() {
+            for s in 0..=light_path.len() {
+                if s + t - 1 > self.max_depth as usize {
+                    continue;
+                }
+                radiance += self.connect(&light_path, &camera_path, s, t, time, film);
+            }
+        }
+        for s in 2..=light_path.len() {
+            if s > self.max_depth as usize {
+                continue;
+            }
+            self.connect(&light_path, &camera_path, s, 1, time, film);
+        }
+
+        radiance
+    }
+
+    fn generate_light_path(&self, time: f64, path: &mut Vec<Vertex>) {
+        let pdf_pos = self.lights.pdf_area();
+        let hitrecord = match self.lights.sample((random_f64(), random_f64()), time) {
+            Some(hitrecord) if pdf_pos > 0.0 => hitrecord,
+            _ => return,
+        };
+
+        let le = hitrecord
+            .material
+            .emitted(hitrecord.surface_coordinates, &hitrecord.p);
+        let normal = if random_f64() < 0.5 {
+            hitrecord.normal
+        } else {
+            -hitrecord.normal
+        };
+        let local = sample_cosine_hemisphere((random_f64(), random_f64()));
+        let direction = Onb::build_from_w(&normal).to_world(&local);
+        let pdf_dir = local.z() / (2.0 * PI);
+        if pdf_dir <= 0.0 {
+            return;
+        }
+
+        let r = Ray::new(hitrecord.offset_origin(&direction), direction, time);
+        let beta = le * (local.z() / (pdf_pos * pdf_dir));
+        path.push(Vertex::light(hitrecord, le / pdf_pos, pdf_pos));
+        self.random_walk(
+            r,
+            beta,
+            pdf_dir,
+            self.max_depth as usize,
+            TransportMode::Importance,
+            path,
+        );
+    }
+
+    // Extends `path` by scattering until it has `max_vertices` vertices.
+    // Returns the throughput of camera paths that leave the scene.
+    fn random_walk(
+        &self,
+        mut r: Ray,
+        mut beta: Color,
+        mut pdf_dir: f64,
+        max_vertices: usize,
+        mode: TransportMode,
+        path: &mut Vec<Vertex>,
+    ) -> Option<Color> {
+        while path.len() < max_vertices {
+            let hitrecord = match self.world.hit(&r, (0.001, f64::INFINITY)) {
+                Some(hitrecord) => hitrecord,
+                None => {
+                    return match mode {
+                        TransportMode::Radiance => Some(beta),
+                        TransportMode::Importance => None,
+                    }
+                }
+            };
+
+            let wo = -unit_vector(r.direction());
+            let mut vertex = Vertex::surface(hitrecord, wo, beta, mode);
+            vertex.pdf_fwd = path[path.len() - 1].convert_density(pdf_dir, &vertex);
+            path.push(vertex);
+            if path.len() >= max_vertices {
+                break;
+            }
+
+            let vertex = &path[path.len() - 1];
+            let hitrecord = vertex.hitrecord.as_ref().unwrap();
+            let wo_local = vertex.frame.to_local(&wo);
+            let u = (random_f64(), random_f64());
+            let (wi, f, pdf, flags) = match hitrecord.material.sample(hitrecord, &wo_local, u) {
+                Some(sample) if sample.2 > 0.0 => sample,
+                _ => break,
+            };
+
+            let direction = vertex.frame.to_world(&wi);
+            let cos_theta = if vertex.is_on_surface() {
+                wi.z().abs()
+            } else {
+                1.0
+            };
+            beta = beta * f * (cos_theta / pdf) * vertex.shading_correction(&direction);
+            if beta.near_zero() {
+                break;
+            }
+
+            // Specular lobes cannot be evaluated at connections, MIS skips
+            // their densities.
+            let delta = flags.is_specular();
+            let pdf_rev = if delta {
+                pdf_dir = 0.0;
+                0.0
+            } else {
+                pdf_dir = pdf;
+                hitrecord.material.pdf(hitrecord, &wi, &wo_local)
+            };
+
+            r = Ray::new(hitrecord.offset_origin(&direction), direction, r.time());
+
+            let last = path.len() - 1;
+            path[last].delta = delta;
+            path[last - 1].pdf_rev = path[last].convert_density(pdf_rev, &path[last - 1]);
+        }
+
+        None
+    }
+
+    // The weighted contribution of the path made of the first `s` light and
+    // first `t` camera vertices. Light tracing (t = 1) is splatted instead.
+    fn connect(
+        &self,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        s: usize,
+        t: usize,
+        time: f64,
+        film: &mut Film,
+    ) -> Color {
+        let mut sampled = None;
+        let mut film_position = None;
+
+        let radiance = if s == 0 {
+            // The camera path hit a light.
+            let pt = &camera_path[t - 1];
+            let radiance = pt.beta * pt.emitted();
+            if !radiance.near_zero() && !pt.is_light() {
+                // Emitters that are not lights, like glowing media or
+                // surfaces that cannot be sampled, can only be found this way.
+                return radiance;
+            }
+            radiance
+        } else if t == 1 {
+            // Connect a light vertex to the camera.
+            let qs = &light_path[s - 1];
+            let (position, importance) = match self.camera.sample_importance(&qs.p) {
+                Some(sample) => sample,
+                None => return Color::default(),
+            };
+            let mut vertex = Vertex::camera(self.camera.origin());
+            vertex.beta = Color::new(importance, importance, importance);
+            let w = vertex.p - qs.p;
+
+            let mut radiance = qs.beta * qs.f(&vertex) * vertex.beta;
+            if qs.is_on_surface() {
+                radiance *= dot(&unit_vector(w), &qs.shading_normal).abs();
+            }
+            radiance *= self.transmittance(qs, &vertex, time);
+
+            film_position = Some(position);
+            sampled = Some(vertex);
+            radiance
+        } else if s == 1 {
+            // Connect the camera path to a point sampled on a light.
+            let pt = &camera_path[t - 1];
+            let pdf_area = self.lights.pdf_area();
+            let hitrecord = match self.lights.sample((random_f64(), random_f64()), time) {
+                Some(hitrecord) if pdf_area > 0.0 => hitrecord,
+                _ => return Color::default(),
+            };
+
+            let w = hitrecord.p - pt.p;
+            let distance_squared = w.len_squared();
+            let cos_light = dot(&hitrecord.normal, &unit_vector(w)).abs();
+            if distance_squared == 0.0 || cos_light == 0.0 {
+                return Color::default();
+            }
+            let pdf = pdf_area * distance_squared / cos_light;
+
+            let le = hitrecord
+                .material
+                .emitted(hitrecord.surface_coordinates, &hitrecord.p);
+            let vertex = Vertex::light(hitrecord, le / pdf, pdf_area);
+
+            let mut radiance = pt.beta * pt.f(&vertex) * vertex.beta;
+            if pt.is_on_surface() {
+                radiance *= dot(&unit_vector(w), &pt.shading_normal).abs();
+            }
+            radiance *= self.transmittance(pt, &vertex, time);
+
+            sampled = Some(vertex);
+            radiance
+        } else {
+            let qs = &light_path[s - 1];
+            let pt = &camera_path[t - 1];
+            let radiance = qs.beta * qs.f(pt) * pt.f(qs) * pt.beta * geometry(qs, pt);
+            if radiance.near_zero() {
+                return Color::default();
+            }
+            radiance * self.transmittance(qs, pt, time)
+        };
+
+        if radiance.near_zero() {
+            return Color::default();
+        }
+
+        let weight = self.mis_weight(light_path, camera_path, sampled.as_ref(), s, t);
+        match film_position {
+            Some(position) => {
+                film.add_splat(position, radiance * weight);
+                Color::default()
+            }
+            None => radiance * weight,
+        }
+    }
+
+    // Balance heuristic over all strategies that could have produced the
+    // same path, found by walking the density ratios out from the
+    // connection.
+    fn mis_weight(
+        &self,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        sampled: Option<&Vertex>,
+        s: usize,
+        t: usize,
+    ) -> f64 {
+        if s + t == 2 {
+            return 1.0;
+        }
+
+        let qs = match (s, sampled) {
+            (0, _) => None,
+            (1, Some(sampled)) => Some(sampled),
+            _ => Some(&light_path[s - 1]),
+        };
+        let pt = match (t, sampled) {
+            (1, Some(sampled)) => sampled,
+            _ => &camera_path[t - 1],
+        };
+        let qs_minus = (s > 1).then(|| &light_path[s - 2]);
+        let pt_minus = (t > 1).then(|| &camera_path[t - 2]);
+
+        // (pdf_fwd, pdf_rev, delta) of each vertex, with the ones around the
+        // connection updated for it.
+        let mut light: Vec<(f64, f64, bool)> = light_path[..s]
+            .iter()
+            .map(|vertex| (vertex.pdf_fwd, vertex.pdf_rev, vertex.delta))
+            .collect();
+        let mut camera: Vec<(f64, f64, bool)> = camera_path[..t]
+            .iter()
+            .map(|vertex| (vertex.pdf_fwd, vertex.pdf_rev, vertex.delta))
+            .collect();
+        if let (1, Some(sampled)) = (s, sampled) {
+            light[0] = (sampled.pdf_fwd, sampled.pdf_rev, sampled.delta);
+        }
+
+        camera[t - 1].1 = match qs {
+            Some(qs) => qs.pdf(self.camera, qs_minus, pt),
+            None => pt.pdf_light_origin(&self.lights),
+        };
+        camera[t - 1].2 = false;
+        if let Some(pt_minus) = pt_minus {
+            camera[t - 2].1 = match qs {
+                Some(qs) => pt.pdf(self.camera, Some(qs), pt_minus),
+                None => pt.pdf_light(pt_minus),
+            };
+        }
+        if let Some(qs) = qs {
+            light[s - 1].1 = pt.pdf(self.camera, pt_minus, qs);
+            light[s - 1].2 = false;
+            if let Some(qs_minus) = qs_minus {
+                light[s - 2].1 = qs.pdf(self.camera, Some(pt), qs_minus);
+            }
+        }
+
+        let remap = |pdf: f64| if pdf == 0.0 { 1.0 } else { pdf };
+        let mut sum = 0.0;
+
+        let mut ratio = 1.0;
+        for i in (1..t).rev() {
+            ratio *= remap(camera[i].1) / remap(camera[i].0);
+            if !camera[i].2 && !camera[i - 1].2 {
+                sum += ratio;
+            }
+        }
+
+        let mut ratio = 1.0;
+        for i in (0..s).rev() {
+            ratio *= remap(light[i].1) / remap(light[i].0);
+            let delta_before = i > 0 && light[i - 1].2;
+            if !light[i].2 && !delta_before {
+                sum += ratio;
+            }
+        }
+
+        1.0 / (1.0 + sum)
+    }
+
+    // How much of the light between two vertices gets through, with media
+    // along the way estimated by ratio tracking.
+    fn transmittance(&self, from: &Vertex, to: &Vertex, time: f64) -> f64 {
+        let origin = from.spawn_point(&to.p);
+        let target = to.spawn_point(&from.p);
+        let r = Ray::new(origin, target - origin, time);
+        self.world
+            .transmittance(&r, (SHADOW_EPSILON, 1.0 - SHADOW_EPSILON))
+    }
+}
+
+fn geometry(v0: &Vertex, v1: &Vertex) -> f64 {
+    let w = v1.p - v0.p;
+    let distance_squared = w.len_squared();
+    if distance_squared == 0.0 {
+        return 0.0;
+    }
+    let w = w / distance_squared.sqrt();
+    let mut g = 1.0 / distance_squared;
+    if v0.is_on_surface() {
+        g *= dot(&v0.shading_normal, &w).abs();
+    }
+    if v1.is_on_surface() {
+        g *= dot(&v1.shading_normal, &w).abs();
+    }
+    g
+}
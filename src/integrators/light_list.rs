@@ -0,0 +1,41 @@
+use crate::hits::hittable::{HitRecord, Hittable};
+
+// The emitting objects of a scene, sampled uniformly by area so that every
+// point on a light has the same density.
+pub struct LightList<'a> {
+    lights: Vec<&'a dyn Hittable>,
+    area: f64,
+}
+
+impl<'a> LightList<'a> {
+    pub fn new(world: &'a dyn Hittable) -> Self {
+        let mut lights = vec![];
+        world.lights(&mut lights);
+        let area = lights.iter().map(|light| light.area()).sum();
+        Self { lights, area }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty() || self.area <= 0.0
+    }
+
+    pub fn sample(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let mut remaining = u.0 * self.area;
+        for light in &self.lights {
+            let area = light.area();
+            if area > 0.0 && remaining < area {
+                return light.sample_surface((remaining / area, u.1), time);
+            }
+            remaining -= area;
+        }
+        None
+    }
+
+    pub fn pdf_area(&self) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            1.0 / self.area
+        }
+    }
+}
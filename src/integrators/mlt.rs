@@ -0,0 +1,348 @@
+use std::{
+    f64::consts::PI,
+    num::NonZeroUsize,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    camera::Camera, film::Film, hits::hittable::Hittable, ray_color_with_sampler,
+    textures::scalar::luminance, vec3::Color, DepthLimits, Sampler,
+};
+
+#[derive(Clone, Copy, Default)]
+struct PrimarySample {
+    value: f64,
+    last_modified: u64,
+    value_backup: f64,
+    modified_backup: u64,
+}
+
+impl PrimarySample {
+    fn backup(&mut self) {
+        self.value_backup = self.value;
+        self.modified_backup = self.last_modified;
+    }
+
+    fn restore(&mut self) {
+        self.value = self.value_backup;
+        self.last_modified = self.modified_backup;
+    }
+}
+
+// A point in primary sample space that is mutated lazily: each coordinate
+// catches up on the mutations it missed when it is next used.
+struct MltSampler {
+    rng: SmallRng,
+    sigma: f64,
+    large_step_probability: f64,
+    samples: Vec<PrimarySample>,
+    iteration: u64,
+    large_step: bool,
+    last_large_step: u64,
+    index: usize,
+}
+
+impl MltSampler {
+    fn new(seed: u64, sigma: f64, large_step_probability: f64) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(seed),
+            sigma,
+            large_step_probability,
+            samples: vec![],
+            iteration: 0,
+            large_step: true,
+            last_large_step: 0,
+            index: 0,
+        }
+    }
+
+    fn start_iteration(&mut self) {
+        self.iteration += 1;
+        self.large_step = self.rng.gen::<f64>() < self.large_step_probability;
+        self.index = 0;
+    }
+
+    fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step = self.iteration;
+        }
+    }
+
+    fn reject(&mut self) {
+        for sample in &mut self.samples {
+            if sample.last_modified == self.iteration {
+                sample.restore();
+            }
+        }
+        self.iteration -= 1;
+    }
+}
+
+impl Sampler for MltSampler {
+    fn next_f64(&mut self) -> f64 {
+        let index = self.index;
+        self.index += 1;
+        // Coordinates a path has not used before start out uniform, as if set
+        // by the last large step.
+        while self.samples.len() <= index {
+            self.samples.push(PrimarySample {
+                value: self.rng.gen(),
+                last_modified: self.last_large_step,
+                ..Default::default()
+            });
+        }
+
+        let sample = &mut self.samples[index];
+        if sample.last_modified < self.last_large_step {
+            sample.value = self.rng.gen();
+            sample.last_modified = self.last_large_step;
+        }
+
+        sample.backup();
+        if self.large_step {
+            sample.value = self.rng.gen();
+        } else {
+            // All the small steps since the last use at once, their sum is
+            // again normally distributed.
+            let steps = (self.iteration - sample.last_modified) as f64;
+            let u1: f64 = self.rng.gen();
+            let u2: f64 = self.rng.gen();
+            let normal = (-2.0 * (1.0 - u1).ln()).sqrt() * (2.0 * PI * u2).cos();
+            sample.value += normal * self.sigma * steps.sqrt();
+            sample.value -= sample.value.floor();
+        }
+        sample.last_modified = self.iteration;
+
+        sample.value
+    }
+}
+
+// Primary sample space Metropolis light transport: the path tracer is run on
+// sample vectors that are mutated with small perturbations and independent
+// large steps, so that paths carrying much light are explored locally. The
+// chains are independent and run on all available threads.
+//
+// Participating media (`ConstantMedium`, `HeterogeneousMedium`) sample their
+// scattering distances from the thread's generator rather than from the
+// primary samples, so in scenes with media a sample vector does not always
+// retrace the same path. The chains stay unbiased, since they then see a
+// noisy estimate of each path, but mutate less coherently.
+pub struct Mlt<'a> {
+    camera: &'a Camera,
+    world: &'a dyn Hittable,
+    background: Color,
+    max_depth: u32,
+    bootstrap_samples: usize,
+    chains: NonZeroUsize,
+    sigma: f64,
+    large_step_probability: f64,
+}
+
+impl<'a> Mlt<'a> {
+    pub fn new(
+        camera: &'a Camera,
+        world: &'a dyn Hittable,
+        background: Color,
+        max_depth: u32,
+        bootstrap_samples: usize,
+        chains: NonZeroUsize,
+    ) -> Self {
+        Self {
+            camera,
+            world,
+            background,
+            max_depth,
+            bootstrap_samples,
+            chains,
+            sigma: 0.01,
+            large_step_probability: 0.3,
+        }
+    }
+
+    pub fn new_with_mutation(
+        camera: &'a Camera,
+        world: &'a dyn Hittable,
+        background: Color,
+        max_depth: u32,
+        bootstrap_samples: usize,
+        chains: NonZeroUsize,
+        mutation: (f64, f64),
+    ) -> Self {
+        let (sigma, large_step_probability) = mutation;
+        Self {
+            sigma,
+            large_step_probability,
+            ..Self::new(
+                camera,
+                world,
+                background,
+                max_depth,
+                bootstrap_samples,
+                chains,
+            )
+        }
+    }
+
+    // Makes `samples_per_pixel` mutations per pixel on average, the film is
+    // scaled to be written with that many samples.
+    pub fn render(&self, width: u32, height: u32, samples_per_pixel: u32) -> Film {
+        // Bootstrap: the average contribution of independent paths normalizes
+        // the chains, and chains start at paths picked in proportion to it.
+        let mut weights = Vec::with_capacity(self.bootstrap_samples);
+        for seed in 0..self.bootstrap_samples {
+            let mut sampler = MltSampler::new(seed as u64, self.sigma, self.large_step_probability);
+            let (_, radiance) = self.evaluate(&mut sampler);
+            weights.push(luminance(&radiance).max(0.0));
+        }
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 || !total.is_finite() {
+            return Film::new(width, height);
+        }
+        let normalization = total / self.bootstrap_samples as f64;
+
+        // Every chain gets its starting path and acceptance generator up
+        // front, so the result does not depend on how threads pick chains.
+        let chains = self.chains.get();
+        let mut rng = SmallRng::from_entropy();
+        let starts: Vec<(usize, u64)> = (0..chains)
+            .map(|_| (pick(&weights, total, rng.gen()), rng.gen()))
+            .collect();
+        let mutations = u64::from(width * height) * u64::from(samples_per_pixel);
+
+        let next_chain = AtomicUsize::new(0);
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let films: Vec<Film> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.min(chains))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut film = Film::new(width, height);
+                        loop {
+                            let chain = next_chain.fetch_add(1, Ordering::Relaxed);
+                            if chain >= chains {
+                                break;
+                            }
+                            eprint!("\r{} / {} chains...", chain + 1, chains);
+
+                            // The remainder is spread over the first chains.
+                            let share = mutations / chains as u64
+                                + u64::from((chain as u64) < mutations % chains as u64);
+                            self.run_chain(&mut film, starts[chain], share, normalization);
+                        }
+                        film
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect()
+        });
+
+        let mut film = Film::new(width, height);
+        for chain_film in &films {
+            film.merge(chain_film);
+        }
+        film
+    }
+
+    // Runs one chain from the bootstrap path `start.0`, deciding acceptance
+    // with a generator seeded by `start.1`.
+    fn run_chain(&self, film: &mut Film, start: (usize, u64), mutations: u64, normalization: f64) {
+        let (seed, acceptance_seed) = start;
+        let mut rng = SmallRng::seed_from_u64(acceptance_seed);
+        let mut sampler = MltSampler::new(seed as u64, self.sigma, self.large_step_probability);
+        let (mut current_position, mut current) = self.evaluate(&mut sampler);
+        let mut current_weight = luminance(&current);
+
+        for _ in 0..mutations {
+            sampler.start_iteration();
+            let (proposed_position, proposed) = self.evaluate(&mut sampler);
+            let proposed_weight = luminance(&proposed);
+
+            // A start that carries no light, possible when media retrace the
+            // bootstrap path differently, is left for the first proposal that
+            // does and never recorded.
+            let current_valid = current_weight > 0.0 && current_weight.is_finite();
+            let acceptance = if !(proposed_weight > 0.0 && proposed_weight.is_finite()) {
+                0.0
+            } else if current_valid {
+                (proposed_weight / current_weight).min(1.0)
+            } else {
+                1.0
+            };
+
+            // Both states are recorded with their expected share, which
+            // reduces the variance of rejected proposals.
+            if acceptance > 0.0 {
+                film.add_splat(
+                    proposed_position,
+                    proposed * (acceptance * normalization / proposed_weight),
+                );
+            }
+            if current_valid && acceptance < 1.0 {
+                film.add_splat(
+                    current_position,
+                    current * ((1.0 - acceptance) * normalization / current_weight),
+                );
+            }
+
+            if rng.gen::<f64>() < acceptance {
+                current_position = proposed_position;
+                current = proposed;
+                current_weight = proposed_weight;
+                sampler.accept();
+            } else {
+                sampler.reject();
+            }
+        }
+    }
+
+    // Traces a camera path with `sampler` supplying every random number,
+    // starting with the film position.
+    fn evaluate(&self, sampler: &mut MltSampler) -> ((f64, f64), Color) {
+        let s = sampler.next_f64();
+        let t = sampler.next_f64();
+        let r = self.camera.get_ray_with_sampler(s, t, sampler);
+        let radiance = ray_color_with_sampler(
+            r,
+            &self.background,
+            self.world,
+            &DepthLimits::new(self.max_depth),
+            sampler,
+        );
+        ((s, t), radiance)
+    }
+}
+
+// The index whose weight covers `u` of the total. Rounding can leave `u`
+// past the end, which falls back to the last index with any weight.
+fn pick(weights: &[f64], total: f64, u: f64) -> usize {
+    let mut remaining = u * total;
+    for (index, weight) in weights.iter().enumerate() {
+        if remaining < *weight {
+            return index;
+        }
+        remaining -= weight;
+    }
+    weights
+        .iter()
+        .rposition(|&weight| weight > 0.0)
+        .unwrap_or(weights.len() - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pick;
+
+    #[test]
+    fn pick_skips_weightless_paths() {
+        let weights = [0.0, 1.0, 3.0, 0.0];
+        assert_eq!(pick(&weights, 4.0, 0.0), 1);
+        assert_eq!(pick(&weights, 4.0, 0.5), 2);
+        // Past the end the last path with weight is taken.
+        assert_eq!(pick(&weights, 4.0, 1.0), 2);
+    }
+}
@@ -0,0 +1,411 @@
+use std::{collections::HashMap, f64::consts::PI};
+
+use crate::{
+    camera::Camera,
+    film::Film,
+    hits::hittable::{HitRecord, Hittable},
+    onb::Onb,
+    random_f64,
+    ray::Ray,
+    vec3::{dot, sample_cosine_hemisphere, unit_vector, Color, Point3, Vec3},
+};
+
+use super::light_list::LightList;
+
+// Fraction of the photons found in an iteration that are kept when the
+// radius shrinks.
+const RADIUS_REDUCTION: f64 = 2.0 / 3.0;
+
+const SHADOW_EPSILON: f64 = 0.0001;
+
+// Where a camera path first reached a surface that is not purely specular.
+struct VisiblePoint {
+    hitrecord: HitRecord,
+    frame: Onb,
+    wo: Vec3,
+    beta: Color,
+}
+
+impl VisiblePoint {
+    fn f(&self, wi: &Vec3) -> Color {
+        self.hitrecord.material.eval(
+            &self.hitrecord,
+            &self.frame.to_local(&self.wo),
+            &self.frame.to_local(wi),
+        )
+    }
+}
+
+// What a pixel keeps between iterations.
+#[derive(Clone, Default)]
+struct PixelStatistics {
+    radius: f64,
+    photons: f64,
+    flux: Color,
+    direct: Color,
+}
+
+// Stochastic progressive photon mapping: every iteration traces one camera
+// path per pixel to a visible point, then shoots photons from the lights and
+// gathers those landing within each pixel's radius, which shrinks as photons
+// accumulate. Direct lighting is computed by sampling the lights, and by
+// following one scattered direction for emitters that cannot be sampled.
+// Those emit no photons, so the light they bounce off other surfaces is
+// missing.
+pub struct Sppm<'a> {
+    camera: &'a Camera,
+    world: &'a dyn Hittable,
+    lights: LightList<'a>,
+    background: Color,
+    max_depth: u32,
+    initial_radius: f64,
+    photons_per_iteration: usize,
+}
+
+impl<'a> Sppm<'a> {
+    pub fn new(
+        camera: &'a Camera,
+        world: &'a dyn Hittable,
+        background: Color,
+        max_depth: u32,
+        initial_radius: f64,
+        photons_per_iteration: usize,
+    ) -> Self {
+        Self {
+            camera,
+            world,
+            lights: LightList::new(world),
+            background,
+            max_depth,
+            initial_radius,
+            photons_per_iteration,
+        }
+    }
+
+    // Renders the final estimate into the film, so it is written with a
+    // single sample per pixel.
+    pub fn render(&self, width: u32, height: u32, iterations: u32) -> Film {
+        let mut pixels = vec![
+            PixelStatistics {
+                radius: self.initial_radius,
+                ..Default::default()
+            };
+            (width * height) as usize
+        ];
+
+        for iteration in 0..iterations {
+            eprint!("\r{} / {} iterations...", iteration + 1, iterations);
+
+            let mut visible_points = Vec::with_capacity(pixels.len());
+            for j in 0..height {
+                for i in 0..width {
+                    let s = (f64::from(i) + random_f64()) / f64::from(width);
+                    let t = (f64::from(j) + random_f64()) / f64::from(height);
+                    let (direct, visible_point) = self.visible_point(self.camera.get_ray(s, t));
+                    pixels[visible_points.len()].direct += direct;
+                    visible_points.push(visible_point);
+                }
+            }
+
+            let grid = PhotonGrid::new(&visible_points, &pixels);
+            let mut flux = vec![Color::default(); pixels.len()];
+            let mut counts = vec![0usize; pixels.len()];
+            for _ in 0..self.photons_per_iteration {
+                self.trace_photon(&grid, &visible_points, &pixels, &mut flux, &mut counts);
+            }
+
+            for (index, pixel) in pixels.iter_mut().enumerate() {
+                if counts[index] == 0 {
+                    continue;
+                }
+                let found = counts[index] as f64;
+                let photons = pixel.photons + RADIUS_REDUCTION * found;
+                let radius = pixel.radius * (photons / (pixel.photons + found)).sqrt();
+                let shrink = (radius * radius) / (pixel.radius * pixel.radius);
+                pixel.flux = (pixel.flux + flux[index]) * shrink;
+                pixel.photons = photons;
+                pixel.radius = radius;
+            }
+        }
+
+        let mut film = Film::new(width, height);
+        let emitted = f64::from(iterations) * self.photons_per_iteration as f64;
+        for j in 0..height {
+            for i in 0..width {
+                let pixel = &pixels[(j * width + i) as usize];
+                let indirect = pixel.flux / (emitted * PI * pixel.radius * pixel.radius);
+                film.add_sample((i, j), pixel.direct / f64::from(iterations) + indirect);
+            }
+        }
+        film
+    }
+
+    // Follows specular bounces from the camera, collecting emission and the
+    // direct lighting at the visible point it ends on.
+    fn visible_point(&self, mut r: Ray) -> (Color, Option<VisiblePoint>) {
+        let mut beta = Color::new(1.0, 1.0, 1.0);
+        let mut radiance = Color::default();
+
+        for _ in 0..self.max_depth {
+            let hitrecord = match self.world.hit(&r, (0.001, f64::INFINITY)) {
+                Some(hitrecord) => hitrecord,
+                None => return (radiance + beta * self.background, None),
+            };
+
+            let wo = -unit_vector(r.direction());
+            radiance += beta
+                * hitrecord
+                    .material
+                    .emitted(hitrecord.surface_coordinates, &hitrecord.p);
+
+            let shading_normal = hitrecord.material.shading_normal(&hitrecord);
+            let frame = hitrecord.shading_frame(&shading_normal);
+
+            if !hitrecord.material.is_specular(&hitrecord) {
+                let visible_point = VisiblePoint {
+                    hitrecord,
+                    frame,
+                    wo,
+                    beta,
+                };
+                radiance += beta * self.direct_lighting(&visible_point, r.time());
+                radiance += beta * self.unsampled_emission(&visible_point, r.time());
+                return (radiance, Some(visible_point));
+            }
+
+            let wo_local = frame.to_local(&wo);
+            let u = (random_f64(), random_f64());
+            let (wi, f, pdf, _) = match hitrecord.material.sample(&hitrecord, &wo_local, u) {
+                Some(sample) if sample.2 > 0.0 => sample,
+                _ => return (radiance, None),
+            };
+            beta = beta * f * (wi.z().abs() / pdf);
+
+            let direction = frame.to_world(&wi);
+            r = Ray::new(hitrecord.offset_origin(&direction), direction, r.time());
+        }
+
+        (radiance, None)
+    }
+
+    fn direct_lighting(&self, visible_point: &VisiblePoint, time: f64) -> Color {
+        let pdf_area = self.lights.pdf_area();
+        let light = match self.lights.sample((random_f64(), random_f64()), time) {
+            Some(light) if pdf_area > 0.0 => light,
+            _ => return Color::default(),
+        };
+
+        let hitrecord = &visible_point.hitrecord;
+        let w = light.p - hitrecord.p;
+        let distance_squared = w.len_squared();
+        let wi = unit_vector(w);
+        let cos_light = dot(&light.normal, &wi).abs();
+        if distance_squared == 0.0 || cos_light == 0.0 {
+            return Color::default();
+        }
+
+        let f = visible_point.f(&wi);
+        if f.near_zero() {
+            return Color::default();
+        }
+        let cos_theta = if hitrecord.material.is_phase_function() {
+            1.0
+        } else {
+            dot(&wi, &visible_point.frame.w()).abs()
+        };
+
+        let origin = hitrecord.offset_origin(&w);
+        let target = light.offset_origin(&-w);
+        let shadow = Ray::new(origin, target - origin, time);
+        let transmittance = self
+            .world
+            .transmittance(&shadow, (SHADOW_EPSILON, 1.0 - SHADOW_EPSILON));
+        if transmittance == 0.0 {
+            return Color::default();
+        }
+
+        let le = light.material.emitted(light.surface_coordinates, &light.p);
+        le * f * (transmittance * cos_theta * cos_light / (pdf_area * distance_squared))
+    }
+
+    // Light reaching the visible point from emitters that light sampling
+    // cannot pick, found along a scattered direction.
+    fn unsampled_emission(&self, visible_point: &VisiblePoint, time: f64) -> Color {
+        let hitrecord = &visible_point.hitrecord;
+        let wo = visible_point.frame.to_local(&visible_point.wo);
+        let u = (random_f64(), random_f64());
+        let (wi, f, pdf, _) = match hitrecord.material.sample(hitrecord, &wo, u) {
+            Some(sample) if sample.2 > 0.0 => sample,
+            _ => return Color::default(),
+        };
+
+        let direction = visible_point.frame.to_world(&wi);
+        let r = Ray::new(hitrecord.offset_origin(&direction), direction, time);
+        let emitter = match self.world.hit(&r, (0.001, f64::INFINITY)) {
+            Some(emitter) if !emitter.samplable => emitter,
+            _ => return Color::default(),
+        };
+
+        let cos_theta = if hitrecord.material.is_phase_function() {
+            1.0
+        } else {
+            wi.z().abs()
+        };
+        let le = emitter
+            .material
+            .emitted(emitter.surface_coordinates, &emitter.p);
+        le * f * (cos_theta / pdf)
+    }
+
+    fn trace_photon(
+        &self,
+        grid: &PhotonGrid,
+        visible_points: &[Option<VisiblePoint>],
+        pixels: &[PixelStatistics],
+        flux: &mut [Color],
+        counts: &mut [usize],
+    ) {
+        let pdf_pos = self.lights.pdf_area();
+        let light = match self
+            .lights
+            .sample((random_f64(), random_f64()), self.camera.sample_time())
+        {
+            Some(light) if pdf_pos > 0.0 => light,
+            _ => return,
+        };
+
+        // Lights emit from both sides.
+        let normal = if random_f64() < 0.5 {
+            light.normal
+        } else {
+            -light.normal
+        };
+        let local = sample_cosine_hemisphere((random_f64(), random_f64()));
+        let pdf_dir = local.z() / (2.0 * PI);
+        if pdf_dir <= 0.0 {
+            return;
+        }
+        let direction = Onb::build_from_w(&normal).to_world(&local);
+
+        let le = light.material.emitted(light.surface_coordinates, &light.p);
+        let mut beta = le * (local.z() / (pdf_pos * pdf_dir));
+        let mut r = Ray::new(
+            light.offset_origin(&direction),
+            direction,
+            self.camera.sample_time(),
+        );
+
+        for depth in 0..self.max_depth {
+            let hitrecord = match self.world.hit(&r, (0.001, f64::INFINITY)) {
+                Some(hitrecord) => hitrecord,
+                None => return,
+            };
+            let wi = -unit_vector(r.direction());
+
+            // Direct lighting is sampled at the visible points instead.
+            if depth > 0 && !hitrecord.material.is_specular(&hitrecord) {
+                for &index in grid.candidates(&hitrecord.p) {
+                    let visible_point = match &visible_points[index] {
+                        Some(visible_point) => visible_point,
+                        None => continue,
+                    };
+                    let radius = pixels[index].radius;
+                    if (visible_point.hitrecord.p - hitrecord.p).len_squared() > radius * radius {
+                        continue;
+                    }
+                    flux[index] += visible_point.beta * visible_point.f(&wi) * beta;
+                    counts[index] += 1;
+                }
+            }
+
+            let shading_normal = hitrecord.material.shading_normal(&hitrecord);
+            let frame = hitrecord.shading_frame(&shading_normal);
+            let u = (random_f64(), random_f64());
+            let (wi, f, pdf, _) =
+                match hitrecord
+                    .material
+                    .sample(&hitrecord, &frame.to_local(&wi), u)
+                {
+                    Some(sample) if sample.2 > 0.0 => sample,
+                    _ => return,
+                };
+            let cos_theta = if hitrecord.material.is_phase_function() {
+                1.0
+            } else {
+                wi.z().abs()
+            };
+            let scattered = beta * f * (cos_theta / pdf);
+
+            // Russian roulette on the change in throughput keeps the photon
+            // power roughly constant.
+            let survival = (max_component(&scattered) / max_component(&beta)).min(1.0);
+            if survival <= 0.0 || random_f64() >= survival {
+                return;
+            }
+            beta = scattered / survival;
+
+            let direction = frame.to_world(&wi);
+            r = Ray::new(hitrecord.offset_origin(&direction), direction, r.time());
+        }
+    }
+}
+
+fn max_component(c: &Color) -> f64 {
+    c.x().max(c.y()).max(c.z())
+}
+
+// Uniform grid hashing the visible points into every cell their radius
+// overlaps. Cells are as large as the largest radius.
+struct PhotonGrid {
+    cell_size: f64,
+    cells: HashMap<(i64, i64, i64), Vec<usize>>,
+}
+
+impl PhotonGrid {
+    fn new(visible_points: &[Option<VisiblePoint>], pixels: &[PixelStatistics]) -> Self {
+        let cell_size = pixels
+            .iter()
+            .map(|pixel| pixel.radius)
+            .fold(0.0, f64::max)
+            .max(f64::EPSILON);
+        let mut grid = Self {
+            cell_size,
+            cells: HashMap::new(),
+        };
+
+        for (index, visible_point) in visible_points.iter().enumerate() {
+            let visible_point = match visible_point {
+                Some(visible_point) => visible_point,
+                None => continue,
+            };
+            let p = visible_point.hitrecord.p;
+            let radius = pixels[index].radius;
+            let extent = Vec3::new(radius, radius, radius);
+            let (x0, y0, z0) = grid.cell(&(p - extent));
+            let (x1, y1, z1) = grid.cell(&(p + extent));
+            for x in x0..=x1 {
+                for y in y0..=y1 {
+                    for z in z0..=z1 {
+                        grid.cells.entry((x, y, z)).or_default().push(index);
+                    }
+                }
+            }
+        }
+
+        grid
+    }
+
+    fn cell(&self, p: &Point3) -> (i64, i64, i64) {
+        (
+            (p.x() / self.cell_size).floor() as i64,
+            (p.y() / self.cell_size).floor() as i64,
+            (p.z() / self.cell_size).floor() as i64,
+        )
+    }
+
+    fn candidates(&self, p: &Point3) -> &[usize] {
+        self.cells
+            .get(&self.cell(p))
+            .map_or(&[], |indices| indices.as_slice())
+    }
+}
@@ -2,17 +2,28 @@ use std::f64::consts::PI;
 
 pub mod bvh_tree;
 pub mod camera;
+pub mod film;
 pub mod hits;
+pub mod import;
+pub mod integrators;
 pub mod materials;
+pub mod noise;
 pub mod objects;
+pub mod onb;
 pub mod ray;
+pub mod sdf;
+pub mod spectrum;
 pub mod textures;
 pub mod vec3;
+pub mod volumes;
 
 use hits::hittable::Hittable;
+use materials::bsdf::BsdfFlags;
+use materials::interior::MediumStack;
 use rand::{thread_rng, Rng};
 use ray::Ray;
-use vec3::Color;
+use spectrum::{PathRadiance, SampledSpectrum, SampledWavelengths};
+use vec3::{dot, unit_vector, Color};
 
 pub fn write_color(list: &mut Vec<u8>, color: Color, samples_per_pixel: u32) {
     let scale = 1.0 / f64::from(samples_per_pixel);
@@ -37,44 +48,226 @@ fn clamp(x: f64, range: (f64, f64)) -> f64 {
     x
 }
 
-pub fn ray_color(r: Ray, background: &Color, world: &dyn Hittable, depth: u32) -> Color {
-    if depth == 0 {
-        return Color::default();
+// Bounce limits of a path, per kind of scattering event and in total.
+// Paths that have made `russian_roulette` bounces end at random, the dimmer
+// their throughput the likelier. Passing through boundaries between nested
+// interiors is not a bounce and has its own, larger limit.
+#[derive(Clone, Copy, Debug)]
+pub struct DepthLimits {
+    pub total: u32,
+    pub diffuse: u32,
+    pub specular: u32,
+    pub transmission: u32,
+    pub volume: u32,
+    pub russian_roulette: u32,
+    pub crossings: u32,
+}
+
+impl DepthLimits {
+    pub fn new(max_depth: u32) -> Self {
+        Self {
+            total: max_depth,
+            diffuse: max_depth,
+            specular: max_depth,
+            transmission: max_depth,
+            volume: max_depth,
+            russian_roulette: 3,
+            crossings: 1000,
+        }
     }
+}
 
-    /*
-    if let Some(hitrecord) = world.hit(&r, (0.001, f64::INFINITY)) {
-        match hitrecord.material.scatter(r, &hitrecord) {
-            Some((scattered, attenuation)) => {
-                return attenuation * ray_color(scattered, world, depth - 1)
-            }
-            None => return Color::default(),
+#[derive(Default)]
+struct Bounces {
+    diffuse: u32,
+    specular: u32,
+    transmission: u32,
+    volume: u32,
+}
+
+impl Bounces {
+    // Counts a scattering event, unless it would exceed its limit.
+    fn try_add(&mut self, limits: &DepthLimits, volume: bool, flags: BsdfFlags) -> bool {
+        let (count, limit) = if volume {
+            (&mut self.volume, limits.volume)
+        } else if flags.is_transmissive() {
+            (&mut self.transmission, limits.transmission)
+        } else if flags.is_specular() || flags.is_glossy() {
+            (&mut self.specular, limits.specular)
+        } else {
+            (&mut self.diffuse, limits.diffuse)
+        };
+        if *count >= limit {
+            return false;
         }
+        *count += 1;
+        true
+    }
+}
+
+// A source of the uniform numbers in [0, 1) a path is built from.
+pub trait Sampler {
+    fn next_f64(&mut self) -> f64;
+}
+
+// Independent numbers from the thread's generator.
+pub struct IndependentSampler;
+
+impl Sampler for IndependentSampler {
+    fn next_f64(&mut self) -> f64 {
+        random_f64()
     }
+}
 
-    */
+pub fn ray_color(r: Ray, background: &Color, world: &dyn Hittable, depth: u32) -> Color {
+    ray_color_with_limits(r, background, world, &DepthLimits::new(depth))
+}
 
-    match world.hit(&r, (0.001, f64::INFINITY)) {
-        None => *background,
+pub fn ray_color_with_limits(
+    r: Ray,
+    background: &Color,
+    world: &dyn Hittable,
+    limits: &DepthLimits,
+) -> Color {
+    ray_color_with_sampler(r, background, world, limits, &mut IndependentSampler)
+}
 
-        Some(hitrecord) => {
-            let emitted = hitrecord
-                .material
-                .emitted(hitrecord.surface_coordinates, &hitrecord.p);
-            match hitrecord.material.scatter(r, &hitrecord) {
-                None => emitted,
+// Like `ray_color_with_limits`, with `sampler` supplying the numbers the path
+// is built from. Media pick their scattering distances on their own.
+pub fn ray_color_with_sampler(
+    r: Ray,
+    background: &Color,
+    world: &dyn Hittable,
+    limits: &DepthLimits,
+    sampler: &mut dyn Sampler,
+) -> Color {
+    trace(r, background, world, limits, &mut (), sampler)
+}
 
-                Some((scattered, attenuation)) => {
-                    emitted + attenuation * ray_color(scattered, background, world, depth - 1)
+// Traces the path at a few sampled wavelengths and converts the result to
+// linear sRGB, so it can be used in place of `ray_color`.
+pub fn ray_color_spectral(r: Ray, background: &Color, world: &dyn Hittable, depth: u32) -> Color {
+    ray_color_spectral_with_limits(r, background, world, &DepthLimits::new(depth))
+}
+
+pub fn ray_color_spectral_with_limits(
+    r: Ray,
+    background: &Color,
+    world: &dyn Hittable,
+    limits: &DepthLimits,
+) -> Color {
+    let mut lambda = SampledWavelengths::sample_visible(random_f64());
+    let radiance: SampledSpectrum = trace(
+        r,
+        background,
+        world,
+        limits,
+        &mut lambda,
+        &mut IndependentSampler,
+    );
+    radiance.to_rgb(&lambda)
+}
+
+// Follows one path, keeping track of the nested interiors it is inside.
+fn trace<S: PathRadiance>(
+    mut r: Ray,
+    background: &Color,
+    world: &dyn Hittable,
+    limits: &DepthLimits,
+    lambda: &mut S::Wavelengths,
+    sampler: &mut dyn Sampler,
+) -> S {
+    let mut media = MediumStack::new();
+    let mut bounces = Bounces::default();
+    let mut beta = S::from_reflectance(Color::new(1.0, 1.0, 1.0), lambda);
+    let mut radiance = S::default();
+
+    let mut depth = 0;
+    let mut crossings = 0;
+    while depth < limits.total {
+        let mut hit = world.hit(&r, (0.001, f64::INFINITY));
+
+        // Absorption by, and scattering inside, the interior the ray travels in.
+        if let Some(interior) = media.current().cloned() {
+            let ray_length = r.direction().len();
+            let t_hit = hit.as_ref().map_or(f64::INFINITY, |hitrecord| hitrecord.t);
+            let t_scatter = interior.sample_distance(sampler.next_f64()) / ray_length;
+
+            let transmittance = interior.transmittance(t_scatter.min(t_hit) * ray_length);
+            beta = beta * S::from_reflectance(transmittance, lambda);
+            if t_scatter < t_hit {
+                hit = interior.scattering_record(&r, t_scatter);
+            }
+        }
+
+        let mut hitrecord = match hit {
+            Some(hitrecord) => hitrecord,
+            None => {
+                radiance = radiance + beta * S::from_illuminant(*background, lambda);
+                break;
+            }
+        };
+
+        let interior = hitrecord.material.interior();
+        if let Some(interior) = &interior {
+            if !media.is_interface(interior, hitrecord.front_face) {
+                media.cross(interior, hitrecord.front_face);
+                r = Ray::new(
+                    hitrecord.offset_origin(&r.direction()),
+                    r.direction(),
+                    r.time(),
+                );
+                crossings += 1;
+                if crossings > limits.crossings {
+                    break;
                 }
+                continue;
             }
         }
+        hitrecord.exterior_ior = media.exterior_ior(interior.as_ref(), hitrecord.front_face);
+
+        let emitted = hitrecord
+            .material
+            .emitted(hitrecord.surface_coordinates, &hitrecord.p);
+        radiance = radiance + beta * S::from_illuminant(emitted, lambda);
+
+        let shading_normal = hitrecord.material.shading_normal(&hitrecord);
+        let frame = hitrecord.shading_frame(&shading_normal);
+        let wo = frame.to_local(&-unit_vector(r.direction()));
+        let u = (sampler.next_f64(), sampler.next_f64());
+        let (wi, f, pdf, flags) = match S::sample(&*hitrecord.material, &hitrecord, &wo, u, lambda)
+        {
+            Some(sample) if sample.2 > 0.0 => sample,
+            _ => break,
+        };
+
+        let is_phase_function = hitrecord.material.is_phase_function();
+        if !bounces.try_add(limits, is_phase_function, flags) {
+            break;
+        }
+
+        let cos_theta = if is_phase_function { 1.0 } else { wi.z().abs() };
+        beta = beta * f * (cos_theta / pdf);
+
+        let direction = frame.to_world(&wi);
+        if let Some(interior) = &interior {
+            if flags.is_transmissive() && dot(&direction, &hitrecord.normal) < 0.0 {
+                media.cross(interior, hitrecord.front_face);
+            }
+        }
+        r = Ray::new(hitrecord.offset_origin(&direction), direction, r.time());
+        depth += 1;
+
+        if depth >= limits.russian_roulette {
+            let survival = beta.max_value().min(1.0);
+            if survival <= 0.0 || sampler.next_f64() >= survival {
+                break;
+            }
+            beta = beta * (1.0 / survival);
+        }
     }
-    /*
-    let unit_direction = unit_vector(r.direction());
-    let t = 0.5 * (unit_direction.y() + 1.0);
-    (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0)
-    */
+
+    radiance
 }
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
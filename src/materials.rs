@@ -1,20 +1,318 @@
+pub mod bsdf;
+pub mod bump_map;
 pub mod dielectric;
 pub mod diffuse_light;
+pub mod dispersion;
+pub mod hair;
+pub mod henyey_greenstein;
+pub mod interior;
 pub mod isotropic;
 pub mod lambertian;
+pub mod layered;
 pub mod metal;
+pub mod microfacet;
+pub mod mix_material;
+pub mod normal_map;
+pub mod principled;
+pub mod rayleigh;
+
+use std::sync::Arc;
 
 use crate::{
     hits::hittable::HitRecord,
-    ray::Ray,
-    vec3::{Color, Point3},
+    spectrum::{rgb_to_spectrum, SampledWavelengths},
+    vec3::{Color, Point3, Vec3},
+};
+
+use self::{
+    bsdf::{BsdfSample, SpectralSample},
+    interior::Interior,
 };
 
-pub trait Material {
-    fn scatter(&self, r_in: Ray, hitrecord: &HitRecord) -> Option<(Ray, Color)>;
+// All directions are given in the local shading frame of the hit, where the
+// shading normal is +z. `wo` points back along the incoming ray.
+pub trait Material: Send + Sync {
+    #[allow(unused_variables)]
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        Color::default()
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample>;
+
+    // Spectral mode counterpart of `sample`, which may terminate the path's
+    // secondary wavelengths. By default the RGB value is upsampled.
+    fn sample_spectral(
+        &self,
+        hitrecord: &HitRecord,
+        wo: &Vec3,
+        u: (f64, f64),
+        lambda: &mut SampledWavelengths,
+    ) -> Option<SpectralSample> {
+        let (wi, f, pdf, flags) = self.sample(hitrecord, wo, u)?;
+        Some((wi, rgb_to_spectrum::unbounded(f, lambda), pdf, flags))
+    }
+
+    #[allow(unused_variables)]
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        0.0
+    }
+
+    fn shading_normal(&self, hitrecord: &HitRecord) -> Vec3 {
+        hitrecord.shading_normal
+    }
 
     #[allow(unused_variables)]
     fn emitted(&self, uv: (f64, f64), p: &Point3) -> Color {
         Color::default()
     }
+
+    // Whether all of the scattering is in delta directions, so `eval` is
+    // always zero and the material can only be sampled.
+    #[allow(unused_variables)]
+    fn is_specular(&self, hitrecord: &HitRecord) -> bool {
+        false
+    }
+
+    // Whether the material is a light that can be sampled directly.
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    // Phase functions scatter inside media, where there is no surface to
+    // foreshorten, so integrators leave out the cosine term for them.
+    fn is_phase_function(&self) -> bool {
+        false
+    }
+
+    // The medium enclosed by a closed surface of this material, if it takes
+    // part in nested dielectric tracking.
+    fn interior(&self) -> Option<Arc<Interior>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, sync::Arc};
+
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use crate::{
+        hits::hittable::HitRecord,
+        spectrum::SampledWavelengths,
+        vec3::{sample_uniform_sphere, unit_vector, Color, Point3, Vec3},
+    };
+
+    use super::{
+        dielectric::Dielectric, interior::Interior, lambertian::Lambertian, layered::Layered,
+        metal::Metal, mix_material::MixMaterial, Material,
+    };
+
+    const SAMPLES: u32 = 100_000;
+
+    fn hitrecord(material: Arc<dyn Material>, front_face: bool) -> HitRecord {
+        HitRecord {
+            p: Point3::default(),
+            normal: Vec3::new(0.0, 0.0, 1.0),
+            shading_normal: Vec3::new(0.0, 0.0, 1.0),
+            dpdu: Vec3::new(1.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, 1.0, 0.0),
+            t: 0.0,
+            surface_coordinates: (0.5, 0.5),
+            front_face,
+            samplable: true,
+            material,
+            exterior_ior: 1.0,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+    }
+
+    // Checks that every sample outside delta directions reports what `eval`
+    // and `pdf` give for its direction. Returns the albedo estimated by
+    // sampling, the part of it in delta directions, the albedo estimated by
+    // integrating `eval` uniformly and the integral of `pdf` over the sphere.
+    fn scattering(material: &Arc<dyn Material>, wo: &Vec3) -> (f64, f64, f64, f64) {
+        let hitrecord = hitrecord(material.clone(), true);
+        let mut rng = SmallRng::seed_from_u64(7);
+
+        let mut sampled = 0.0;
+        let mut specular = 0.0;
+        for _ in 0..SAMPLES {
+            if let Some((wi, f, pdf, flags)) =
+                material.sample(&hitrecord, wo, (rng.gen(), rng.gen()))
+            {
+                if flags.is_specular() {
+                    specular += f.y() * wi.z().abs() / pdf;
+                    sampled += f.y() * wi.z().abs() / pdf;
+                    continue;
+                }
+                let eval = material.eval(&hitrecord, wo, &wi);
+                assert!(close(f.y(), eval.y()), "f {} != eval {}", f.y(), eval.y());
+                let expected = material.pdf(&hitrecord, wo, &wi);
+                assert!(close(pdf, expected), "pdf {pdf} != {expected}");
+                sampled += f.y() * wi.z().abs() / pdf;
+            }
+        }
+
+        let mut uniform = 0.0;
+        let mut pdf_integral = 0.0;
+        for _ in 0..SAMPLES {
+            let wi = sample_uniform_sphere((rng.gen(), rng.gen()));
+            uniform += material.eval(&hitrecord, wo, &wi).y() * wi.z().abs() * 4.0 * PI;
+            pdf_integral += material.pdf(&hitrecord, wo, &wi) * 4.0 * PI;
+        }
+
+        let n = f64::from(SAMPLES);
+        (sampled / n, specular / n, uniform / n, pdf_integral / n)
+    }
+
+    // A white Lambertian surface scatters all light and samples exactly its
+    // cosine distribution.
+    #[test]
+    fn lambertian_furnace() {
+        let material: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(1.0, 1.0, 1.0)));
+        for wo in [
+            Vec3::new(0.0, 0.0, 1.0),
+            unit_vector(Vec3::new(0.8, 0.1, 0.3)),
+        ] {
+            let (sampled, _, uniform, pdf_integral) = scattering(&material, &wo);
+            assert!((sampled - 1.0).abs() < 1e-9, "sampled {sampled}");
+            assert!((uniform - 1.0).abs() < 0.02, "uniform {uniform}");
+            assert!((pdf_integral - 1.0).abs() < 0.02, "pdf {pdf_integral}");
+        }
+    }
+
+    // Single scattering GGX loses some energy, more so at grazing angles,
+    // but never creates any.
+    #[test]
+    fn metal_furnace() {
+        let material: Arc<dyn Material> = Arc::new(Metal::new(Color::new(1.0, 1.0, 1.0), 0.3));
+        for wo in [
+            Vec3::new(0.0, 0.0, 1.0),
+            unit_vector(Vec3::new(0.8, 0.1, 0.3)),
+        ] {
+            let (sampled, _, uniform, pdf_integral) = scattering(&material, &wo);
+            assert!(sampled <= 1.0 && sampled > 0.75, "sampled {sampled}");
+            assert!((uniform - sampled).abs() < 0.05, "uniform {uniform}");
+            assert!(pdf_integral < 1.02, "pdf {pdf_integral}");
+        }
+
+        // A mirror reflects everything.
+        let mirror: Arc<dyn Material> = Arc::new(Metal::new(Color::new(1.0, 1.0, 1.0), 0.0));
+        let hitrecord = hitrecord(mirror.clone(), true);
+        let wo = unit_vector(Vec3::new(0.8, 0.1, 0.3));
+        assert!(mirror.is_specular(&hitrecord));
+        let (wi, f, pdf, _) = mirror.sample(&hitrecord, &wo, (0.5, 0.5)).unwrap();
+        assert!(close(f.y() * wi.z().abs() / pdf, 1.0));
+    }
+
+    // Glass splits light between reflection and refraction without losing
+    // any, from either side and under total internal reflection.
+    #[test]
+    fn dielectric_furnace() {
+        let material: Arc<dyn Material> = Arc::new(Dielectric::new(1.5));
+        for front_face in [true, false] {
+            let hitrecord = hitrecord(material.clone(), front_face);
+            assert!(material.is_specular(&hitrecord));
+            for wo in [
+                Vec3::new(0.0, 0.0, 1.0),
+                unit_vector(Vec3::new(0.9, 0.0, 0.2)),
+            ] {
+                let mut pdfs = vec![];
+                for u in [0.0, 1.0 - 1e-12] {
+                    let (wi, f, pdf, _) = material.sample(&hitrecord, &wo, (u, 0.5)).unwrap();
+                    assert!(close(f.y() * wi.z().abs() / pdf, 1.0));
+                    assert!(material.eval(&hitrecord, &wo, &wi).near_zero());
+                    pdfs.push(pdf);
+                }
+                // Reflection and refraction are picked with probabilities
+                // summing to one, unless reflection takes all of it.
+                assert!(
+                    close(pdfs[0] + pdfs[1], 1.0) || close(pdfs[0], 1.0),
+                    "{pdfs:?}"
+                );
+            }
+        }
+    }
+
+    // A mix of two materials that each conserve energy cannot create any,
+    // and its spectral samples agree with the RGB ones.
+    #[test]
+    fn mix_material_furnace() {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let material: Arc<dyn Material> = Arc::new(MixMaterial::new(
+            Arc::new(Lambertian::new(white)),
+            Arc::new(Metal::new(white, 0.3)),
+            0.4,
+        ));
+        for wo in [
+            Vec3::new(0.0, 0.0, 1.0),
+            unit_vector(Vec3::new(0.8, 0.1, 0.3)),
+        ] {
+            let (sampled, specular, uniform, pdf_integral) = scattering(&material, &wo);
+            assert_eq!(specular, 0.0);
+            assert!(sampled <= 1.0 && sampled > 0.9, "sampled {sampled}");
+            assert!((uniform - sampled).abs() < 0.05, "uniform {uniform}");
+            assert!(pdf_integral < 1.02, "pdf {pdf_integral}");
+
+            let hitrecord = hitrecord(material.clone(), true);
+            let mut lambda = SampledWavelengths::sample_visible(0.3);
+            let (wi, _, pdf, _) = material
+                .sample_spectral(&hitrecord, &wo, (0.2, 0.7), &mut lambda)
+                .unwrap();
+            assert!(close(pdf, material.pdf(&hitrecord, &wo, &wi)));
+        }
+
+        // Delta lobes keep their share of the mix.
+        let glass: Arc<dyn Material> = Arc::new(Dielectric::new(1.5));
+        let mix: Arc<dyn Material> = Arc::new(MixMaterial::new(glass.clone(), glass, 0.5));
+        let hitrecord = hitrecord(mix.clone(), true);
+        assert!(mix.is_specular(&hitrecord));
+        let wo = Vec3::new(0.0, 0.0, 1.0);
+        let (wi, f, pdf, _) = mix.sample(&hitrecord, &wo, (0.3, 0.5)).unwrap();
+        assert!(close(f.y() * wi.z().abs() / pdf, 1.0));
+    }
+
+    // Light the smooth coat reflects plus what the base returns through it
+    // never exceeds what arrived. Light the coat reflects back down is not
+    // followed, so a white base under glass returns only about half.
+    #[test]
+    fn layered_furnace() {
+        let base = Arc::new(Lambertian::new(Color::new(1.0, 1.0, 1.0)));
+        let material: Arc<dyn Material> = Arc::new(Layered::new(base, 1.5));
+        for wo in [
+            Vec3::new(0.0, 0.0, 1.0),
+            unit_vector(Vec3::new(0.8, 0.1, 0.3)),
+        ] {
+            let (sampled, specular, uniform, pdf_integral) = scattering(&material, &wo);
+            assert!(specular > 0.0, "the coat reflects");
+            assert!(sampled <= 1.0 && sampled > 0.3, "sampled {sampled}");
+            assert!(
+                (uniform - (sampled - specular)).abs() < 0.03,
+                "uniform {uniform}"
+            );
+            assert!(pdf_integral < 1.02, "pdf {pdf_integral}");
+        }
+
+        let hitrecord = hitrecord(material.clone(), true);
+        assert!(!material.is_specular(&hitrecord));
+        let on_glass = Layered::new(Arc::new(Dielectric::new(1.5)), 1.5);
+        assert!(on_glass.is_specular(&hitrecord));
+    }
+
+    // The medium inside a mix is the first one that has any.
+    #[test]
+    fn mix_material_interior() {
+        let interior = Arc::new(Interior::new(1.33, 1));
+        let water: Arc<dyn Material> = Arc::new(Dielectric::new_with_interior(interior.clone()));
+        let diffuse: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+        let mix = MixMaterial::new(diffuse.clone(), water, 0.5);
+        assert!(Arc::ptr_eq(&mix.interior().unwrap(), &interior));
+        let plain = MixMaterial::new(diffuse.clone(), diffuse, 0.5);
+        assert!(plain.interior().is_none());
+    }
 }
@@ -0,0 +1,102 @@
+use std::ops::BitOr;
+
+use crate::{
+    onb::Onb,
+    spectrum::SampledSpectrum,
+    vec3::{Color, Vec3},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BsdfFlags(u8);
+
+impl BsdfFlags {
+    pub const REFLECTION: Self = Self(1);
+    pub const TRANSMISSION: Self = Self(1 << 1);
+    pub const DIFFUSE: Self = Self(1 << 2);
+    pub const GLOSSY: Self = Self(1 << 3);
+    pub const SPECULAR: Self = Self(1 << 4);
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_reflective(&self) -> bool {
+        self.contains(Self::REFLECTION)
+    }
+
+    pub fn is_transmissive(&self) -> bool {
+        self.contains(Self::TRANSMISSION)
+    }
+
+    pub fn is_diffuse(&self) -> bool {
+        self.contains(Self::DIFFUSE)
+    }
+
+    pub fn is_glossy(&self) -> bool {
+        self.contains(Self::GLOSSY)
+    }
+
+    pub fn is_specular(&self) -> bool {
+        self.contains(Self::SPECULAR)
+    }
+}
+
+impl BitOr for BsdfFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+// Direction sampled by `Material::sample`, all directions in the local shading frame.
+pub type BsdfSample = (Vec3, Color, f64, BsdfFlags);
+pub type SpectralSample = (Vec3, SampledSpectrum, f64, BsdfFlags);
+
+pub fn cos_theta(w: &Vec3) -> f64 {
+    w.z()
+}
+
+pub fn abs_cos_theta(w: &Vec3) -> f64 {
+    w.z().abs()
+}
+
+pub fn same_hemisphere(w: &Vec3, wp: &Vec3) -> bool {
+    w.z() * wp.z() > 0.0
+}
+
+pub fn schlick_fresnel(f0: Color, cosine: f64) -> Color {
+    let weight = (1.0 - cosine).clamp(0.0, 1.0).powi(5);
+    f0 + weight * (Color::new(1.0, 1.0, 1.0) - f0)
+}
+
+pub fn fresnel_dielectric(cos_theta_i: f64, eta: f64) -> f64 {
+    let cos_theta_i = cos_theta_i.clamp(-1.0, 1.0);
+    let (cos_theta_i, eta) = if cos_theta_i < 0.0 {
+        (-cos_theta_i, 1.0 / eta)
+    } else {
+        (cos_theta_i, eta)
+    };
+
+    let sin2_theta_i = 1.0 - cos_theta_i * cos_theta_i;
+    let sin2_theta_t = sin2_theta_i / (eta * eta);
+    if sin2_theta_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).max(0.0).sqrt();
+
+    let r_parl = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let r_perp = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    (r_parl * r_parl + r_perp * r_perp) / 2.0
+}
+
+// Direction at polar angle acos(cos_theta) and azimuth phi around `axis`.
+pub fn direction_around(axis: &Vec3, cos_theta: f64, phi: f64) -> Vec3 {
+    let frame = Onb::build_from_w(axis);
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    frame.to_world(&Vec3::new(
+        sin_theta * phi.cos(),
+        sin_theta * phi.sin(),
+        cos_theta,
+    ))
+}
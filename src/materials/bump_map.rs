@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::hittable::HitRecord,
+    spectrum::SampledWavelengths,
+    textures::ScalarTexture,
+    vec3::{cross, dot, unit_vector, Color, Point3, Vec3},
+};
+
+use super::{
+    bsdf::{BsdfSample, SpectralSample},
+    interior::Interior,
+    Material,
+};
+
+const DELTA: f64 = 0.0005;
+
+// Perturbs the shading normal of `material` as if the surface was displaced
+// along its normal by `scale` times the height texture.
+pub struct BumpMap {
+    material: Arc<dyn Material>,
+    height: Arc<dyn ScalarTexture>,
+    scale: f64,
+}
+
+impl BumpMap {
+    pub fn new(material: Arc<dyn Material>, height: Arc<dyn ScalarTexture>, scale: f64) -> Self {
+        Self {
+            material,
+            height,
+            scale,
+        }
+    }
+
+    fn displacement(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> f64 {
+        self.scale * self.height.value_with_normal(uv, p, normal)
+    }
+}
+
+impl Material for BumpMap {
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        self.material.eval(hitrecord, wo, wi)
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        self.material.sample(hitrecord, wo, u)
+    }
+
+    fn sample_spectral(
+        &self,
+        hitrecord: &HitRecord,
+        wo: &Vec3,
+        u: (f64, f64),
+        lambda: &mut SampledWavelengths,
+    ) -> Option<SpectralSample> {
+        self.material.sample_spectral(hitrecord, wo, u, lambda)
+    }
+
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        self.material.pdf(hitrecord, wo, wi)
+    }
+
+    fn shading_normal(&self, hitrecord: &HitRecord) -> Vec3 {
+        let base = self.material.shading_normal(hitrecord);
+        let (u, v) = hitrecord.surface_coordinates;
+        let p = hitrecord.p;
+
+        let displace = self.displacement((u, v), &p, &base);
+        let u_displace = self.displacement((u + DELTA, v), &(p + DELTA * hitrecord.dpdu), &base);
+        let v_displace = self.displacement((u, v + DELTA), &(p + DELTA * hitrecord.dpdv), &base);
+
+        let dpdu = hitrecord.dpdu + (u_displace - displace) / DELTA * base;
+        let dpdv = hitrecord.dpdv + (v_displace - displace) / DELTA * base;
+
+        let perturbed = cross(&dpdu, &dpdv);
+        if perturbed.near_zero() {
+            return base;
+        }
+        let perturbed = unit_vector(perturbed);
+        if dot(&perturbed, &base) < 0.0 {
+            -perturbed
+        } else {
+            perturbed
+        }
+    }
+
+    fn emitted(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.material.emitted(uv, p)
+    }
+
+    fn is_specular(&self, hitrecord: &HitRecord) -> bool {
+        self.material.is_specular(hitrecord)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn interior(&self) -> Option<Arc<Interior>> {
+        self.material.interior()
+    }
+}
@@ -1,48 +1,133 @@
+use std::sync::Arc;
+
 use crate::{
     hits::hittable::HitRecord,
-    random_f64,
-    ray::Ray,
-    vec3::{dot, reflect, refract, unit_vector, Color},
+    spectrum::{SampledSpectrum, SampledWavelengths},
+    vec3::{refract, Color, Vec3},
 };
 
-use super::Material;
+use super::{
+    bsdf::{abs_cos_theta, BsdfFlags, BsdfSample, SpectralSample},
+    dispersion::{Dispersion, REFERENCE_WAVELENGTH},
+    interior::Interior,
+    Material,
+};
 
 pub struct Dielectric {
-    refraction_index: f64,
+    dispersion: Dispersion,
+    interior: Option<Arc<Interior>>,
 }
 
 impl Dielectric {
     pub fn new(refraction_index: f64) -> Self {
-        Self { refraction_index }
+        Self::new_dispersive(Dispersion::Constant(refraction_index))
     }
-}
 
-impl Material for Dielectric {
-    fn scatter(&self, r_in: Ray, hitrecord: &HitRecord) -> Option<(Ray, Color)> {
+    // In spectral mode the index varies with wavelength and splits white
+    // light; RGB mode uses the index at `REFERENCE_WAVELENGTH`.
+    pub fn new_dispersive(dispersion: Dispersion) -> Self {
+        Self {
+            dispersion,
+            interior: None,
+        }
+    }
+
+    // The surface bounds `interior`, which takes part in nested dielectric
+    // tracking and provides the refraction index.
+    pub fn new_with_interior(interior: Arc<Interior>) -> Self {
+        Self {
+            dispersion: Dispersion::Constant(interior.refraction_index()),
+            interior: Some(interior),
+        }
+    }
+
+    fn sample_with_index(
+        &self,
+        hitrecord: &HitRecord,
+        wo: &Vec3,
+        u: (f64, f64),
+        refraction_index: f64,
+    ) -> Option<BsdfSample> {
         let refraction_ratio = if hitrecord.front_face {
-            1.0 / self.refraction_index
+            hitrecord.exterior_ior / refraction_index
         } else {
-            self.refraction_index
+            refraction_index / hitrecord.exterior_ior
         };
 
-        let unit_direction = unit_vector(r_in.direction());
+        // Index-matched boundaries neither reflect nor bend light.
+        if refraction_ratio == 1.0 {
+            let wi = -*wo;
+            return Some((
+                wi,
+                Color::new(1.0, 1.0, 1.0) / abs_cos_theta(&wi),
+                1.0,
+                BsdfFlags::SPECULAR | BsdfFlags::TRANSMISSION,
+            ));
+        }
 
-        let cos_theta = dot(&-unit_direction, &hitrecord.normal).min(1.0);
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        let cos_theta = wo.z().min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
-
-        let direction = if cannot_refract || reflectance(cos_theta, refraction_ratio) > random_f64()
-        {
-            reflect(&unit_direction, &hitrecord.normal)
+        let reflect_probability = if cannot_refract {
+            1.0
         } else {
-            refract(&unit_direction, &hitrecord.normal, refraction_ratio)
+            reflectance(cos_theta, refraction_ratio)
         };
 
-        Some((
-            Ray::new(hitrecord.p, direction, r_in.time()),
-            Color::new(1.0, 1.0, 1.0),
-        ))
+        if u.0 < reflect_probability {
+            let wi = Vec3::new(-wo.x(), -wo.y(), wo.z());
+            let f = reflect_probability * Color::new(1.0, 1.0, 1.0) / abs_cos_theta(&wi);
+            Some((
+                wi,
+                f,
+                reflect_probability,
+                BsdfFlags::SPECULAR | BsdfFlags::REFLECTION,
+            ))
+        } else {
+            let wi = refract(&-*wo, &normal, refraction_ratio);
+            let transmit_probability = 1.0 - reflect_probability;
+            let f = transmit_probability * Color::new(1.0, 1.0, 1.0) / abs_cos_theta(&wi);
+            Some((
+                wi,
+                f,
+                transmit_probability,
+                BsdfFlags::SPECULAR | BsdfFlags::TRANSMISSION,
+            ))
+        }
+    }
+}
+
+impl Material for Dielectric {
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        let refraction_index = self.dispersion.refraction_index(REFERENCE_WAVELENGTH);
+        self.sample_with_index(hitrecord, wo, u, refraction_index)
+    }
+
+    // Only the hero wavelength can follow a dispersed direction.
+    fn sample_spectral(
+        &self,
+        hitrecord: &HitRecord,
+        wo: &Vec3,
+        u: (f64, f64),
+        lambda: &mut SampledWavelengths,
+    ) -> Option<SpectralSample> {
+        if self.dispersion.is_dispersive() {
+            lambda.terminate_secondary();
+        }
+        let refraction_index = self.dispersion.refraction_index(lambda.hero());
+        let (wi, f, pdf, flags) = self.sample_with_index(hitrecord, wo, u, refraction_index)?;
+        Some((wi, SampledSpectrum::constant(f.x()), pdf, flags))
+    }
+
+    fn interior(&self) -> Option<Arc<Interior>> {
+        self.interior.clone()
+    }
+
+    #[allow(unused_variables)]
+    fn is_specular(&self, hitrecord: &HitRecord) -> bool {
+        true
     }
 }
 
@@ -1,26 +1,25 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::{
     hits::hittable::HitRecord,
-    ray::Ray,
     textures::{solid_color::SolidColor, Texture},
-    vec3::Color,
+    vec3::{Color, Vec3},
 };
 
-use super::Material;
+use super::{bsdf::BsdfSample, Material};
 
 pub struct DiffuseLight {
-    emit: Rc<dyn Texture>,
+    emit: Arc<dyn Texture>,
 }
 
 impl DiffuseLight {
     pub fn new(c: Color) -> Self {
         Self {
-            emit: Rc::new(SolidColor::new_from_color(c)),
+            emit: Arc::new(SolidColor::new_from_color(c)),
         }
     }
 
-    pub fn new_from_texture(a: Rc<dyn Texture>) -> Self {
+    pub fn new_from_texture(a: Arc<dyn Texture>) -> Self {
         Self { emit: a }
     }
 }
@@ -31,7 +30,11 @@ impl Material for DiffuseLight {
     }
 
     #[allow(unused_variables)]
-    fn scatter(&self, r_in: Ray, hitrecord: &HitRecord) -> Option<(Ray, Color)> {
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
         None
     }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,45 @@
+// Wavelength dependence of a refraction index. Wavelengths are in
+// nanometers, the fitted formulas take micrometers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dispersion {
+    Constant(f64),
+    // n = a + b / λ²
+    Cauchy { a: f64, b: f64 },
+    // n² = 1 + Σ b_i λ² / (λ² - c_i)
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+// Wavelength used where only a single index is needed, e.g. in RGB mode: the
+// sodium d-line that catalogue indices are quoted at.
+pub const REFERENCE_WAVELENGTH: f64 = 587.6;
+
+impl Dispersion {
+    // Schott N-BK7 crown glass.
+    pub const BK7: Self = Self::Sellmeier {
+        b: [1.039_612_12, 0.231_792_344, 1.010_469_45],
+        c: [0.006_000_698_67, 0.020_017_914_4, 103.560_653],
+    };
+
+    // Schott SF11 dense flint glass, strongly dispersive.
+    pub const SF11: Self = Self::Sellmeier {
+        b: [1.737_596_95, 0.313_747_346, 1.898_781_01],
+        c: [0.013_188_707, 0.062_306_814_2, 155.236_29],
+    };
+
+    pub fn refraction_index(&self, lambda: f64) -> f64 {
+        let micrometers = lambda / 1000.0;
+        let l2 = micrometers * micrometers;
+        match self {
+            Dispersion::Constant(n) => *n,
+            Dispersion::Cauchy { a, b } => a + b / l2,
+            Dispersion::Sellmeier { b, c } => {
+                let sum: f64 = (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum();
+                (1.0 + sum).sqrt()
+            }
+        }
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, Dispersion::Constant(_))
+    }
+}
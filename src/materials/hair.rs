@@ -0,0 +1,469 @@
+use std::f64::consts::PI;
+
+use crate::{
+    hits::hittable::HitRecord,
+    textures::scalar::luminance,
+    vec3::{Color, Vec3},
+};
+
+use super::{
+    bsdf::{abs_cos_theta, fresnel_dielectric, BsdfFlags, BsdfSample},
+    Material,
+};
+
+// Scattering orders modelled separately: R, TT and TRT. Higher orders are
+// lumped into one more term.
+const P_MAX: usize = 3;
+
+const SQRT_PI_OVER_8: f64 = 0.626_657_068_657_750_1;
+
+// Absorption of the two melanin pigments per unit concentration.
+const EUMELANIN_SIGMA_A: (f64, f64, f64) = (0.419, 0.697, 1.37);
+const PHEOMELANIN_SIGMA_A: (f64, f64, f64) = (0.187, 0.4, 1.05);
+
+// Scattering from a rough dielectric cylinder in the style of Marschner et
+// al. with d'Eon et al.'s energy conserving lobes. It expects curves that
+// run along the tangent and report where across their width they were hit
+// as the second surface coordinate.
+pub struct Hair {
+    sigma_a: Color,
+    eta: f64,
+    // Longitudinal variances per order.
+    v: [f64; P_MAX + 1],
+    // Azimuthal logistic scale.
+    s: f64,
+    // Sines and cosines of the scale tilt doubled 0, 1 and 2 times.
+    sin_2k_alpha: [f64; 3],
+    cos_2k_alpha: [f64; 3],
+}
+
+impl Hair {
+    // `beta_m` and `beta_n` are the longitudinal and azimuthal roughness in
+    // [0, 1], `alpha` the tilt of the cuticle scales in degrees.
+    pub fn new(sigma_a: Color, eta: f64, beta_m: f64, beta_n: f64, alpha: f64) -> Self {
+        let v0 = (0.726 * beta_m + 0.812 * beta_m.powi(2) + 3.7 * beta_m.powi(20)).powi(2);
+        let v = [v0, 0.25 * v0, 4.0 * v0, 4.0 * v0];
+        let s =
+            SQRT_PI_OVER_8 * (0.265 * beta_n + 1.194 * beta_n.powi(2) + 5.372 * beta_n.powi(22));
+
+        let mut sin_2k_alpha = [alpha.to_radians().sin(), 0.0, 0.0];
+        let mut cos_2k_alpha = [safe_sqrt(1.0 - sin_2k_alpha[0].powi(2)), 0.0, 0.0];
+        for i in 1..3 {
+            sin_2k_alpha[i] = 2.0 * cos_2k_alpha[i - 1] * sin_2k_alpha[i - 1];
+            cos_2k_alpha[i] = cos_2k_alpha[i - 1].powi(2) - sin_2k_alpha[i - 1].powi(2);
+        }
+
+        Self {
+            sigma_a,
+            eta,
+            v,
+            s,
+            sin_2k_alpha,
+            cos_2k_alpha,
+        }
+    }
+
+    // Hair coloured by the concentrations of eumelanin, which makes it brown
+    // to black, and pheomelanin, which makes it red.
+    pub fn new_from_melanin(eumelanin: f64, pheomelanin: f64, beta_m: f64, beta_n: f64) -> Self {
+        let sigma_a = eumelanin
+            * Color::new(
+                EUMELANIN_SIGMA_A.0,
+                EUMELANIN_SIGMA_A.1,
+                EUMELANIN_SIGMA_A.2,
+            )
+            + pheomelanin
+                * Color::new(
+                    PHEOMELANIN_SIGMA_A.0,
+                    PHEOMELANIN_SIGMA_A.1,
+                    PHEOMELANIN_SIGMA_A.2,
+                );
+        Self::new(sigma_a, 1.55, beta_m, beta_n, 2.0)
+    }
+
+    // Hair that looks roughly like `color` after multiple scattering.
+    pub fn new_from_color(color: Color, beta_m: f64, beta_n: f64) -> Self {
+        let denominator = 5.969 - 0.215 * beta_n + 2.532 * beta_n.powi(2) - 10.73 * beta_n.powi(3)
+            + 5.574 * beta_n.powi(4)
+            + 0.245 * beta_n.powi(5);
+        let sigma_a = |c: f64| (c.max(1e-4).ln() / denominator).powi(2);
+        Self::new(
+            Color::new(sigma_a(color.x()), sigma_a(color.y()), sigma_a(color.z())),
+            1.55,
+            beta_m,
+            beta_n,
+            2.0,
+        )
+    }
+
+    // The angle `theta_o` tilted by the cuticle scales for order `p`.
+    fn tilted(&self, p: usize, sin_theta_o: f64, cos_theta_o: f64) -> (f64, f64) {
+        let (sin_theta, cos_theta) = match p {
+            0 => (
+                sin_theta_o * self.cos_2k_alpha[1] - cos_theta_o * self.sin_2k_alpha[1],
+                cos_theta_o * self.cos_2k_alpha[1] + sin_theta_o * self.sin_2k_alpha[1],
+            ),
+            1 => (
+                sin_theta_o * self.cos_2k_alpha[0] + cos_theta_o * self.sin_2k_alpha[0],
+                cos_theta_o * self.cos_2k_alpha[0] - sin_theta_o * self.sin_2k_alpha[0],
+            ),
+            2 => (
+                sin_theta_o * self.cos_2k_alpha[2] + cos_theta_o * self.sin_2k_alpha[2],
+                cos_theta_o * self.cos_2k_alpha[2] - sin_theta_o * self.sin_2k_alpha[2],
+            ),
+            _ => (sin_theta_o, cos_theta_o),
+        };
+        (sin_theta, cos_theta.abs())
+    }
+
+    // What the fiber's interior does to light leaving towards `wo` from the
+    // offset `h` across it: the attenuation per order and the refracted
+    // azimuth.
+    fn geometry(&self, wo: &Vec3, h: f64) -> ([Color; P_MAX + 1], f64, f64) {
+        let sin_theta_o = wo.x();
+        let cos_theta_o = safe_sqrt(1.0 - sin_theta_o * sin_theta_o);
+
+        let sin_theta_t = sin_theta_o / self.eta;
+        let cos_theta_t = safe_sqrt(1.0 - sin_theta_t * sin_theta_t);
+        let etap = (self.eta * self.eta - sin_theta_o * sin_theta_o).sqrt() / cos_theta_o;
+        let sin_gamma_t = h / etap;
+        let cos_gamma_t = safe_sqrt(1.0 - sin_gamma_t * sin_gamma_t);
+        let gamma_t = sin_gamma_t.clamp(-1.0, 1.0).asin();
+
+        let transmittance = exp(-(2.0 * cos_gamma_t / cos_theta_t) * self.sigma_a);
+        (
+            attenuation(cos_theta_o, self.eta, h, transmittance),
+            h.clamp(-1.0, 1.0).asin(),
+            gamma_t,
+        )
+    }
+
+    // How likely each order is to be sampled.
+    fn order_pdf(attenuation: &[Color; P_MAX + 1]) -> [f64; P_MAX + 1] {
+        let weights = attenuation.map(|a| luminance(&a).max(0.0));
+        let sum: f64 = weights.iter().sum();
+        if sum <= 0.0 {
+            return [1.0 / (P_MAX + 1) as f64; P_MAX + 1];
+        }
+        weights.map(|w| w / sum)
+    }
+}
+
+impl Material for Hair {
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        let h = offset(hitrecord);
+        let (ap, gamma_o, gamma_t) = self.geometry(wo, h);
+
+        let sin_theta_o = wo.x();
+        let cos_theta_o = safe_sqrt(1.0 - sin_theta_o * sin_theta_o);
+        let phi_o = wo.z().atan2(wo.y());
+        let sin_theta_i = wi.x();
+        let cos_theta_i = safe_sqrt(1.0 - sin_theta_i * sin_theta_i);
+        let phi_i = wi.z().atan2(wi.y());
+        let phi = phi_i - phi_o;
+
+        let mut f = Color::default();
+        for (p, a) in ap.iter().enumerate().take(P_MAX) {
+            let (sin_theta_op, cos_theta_op) = self.tilted(p, sin_theta_o, cos_theta_o);
+            f += longitudinal(
+                cos_theta_i,
+                cos_theta_op,
+                sin_theta_i,
+                sin_theta_op,
+                self.v[p],
+            ) * azimuthal(phi, p, self.s, gamma_o, gamma_t)
+                * *a;
+        }
+        f += longitudinal(
+            cos_theta_i,
+            cos_theta_o,
+            sin_theta_i,
+            sin_theta_o,
+            self.v[P_MAX],
+        ) / (2.0 * PI)
+            * ap[P_MAX];
+
+        let cos_theta = abs_cos_theta(wi);
+        if cos_theta > 0.0 {
+            f /= cos_theta;
+        }
+        f
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        let h = offset(hitrecord);
+        let (ap, gamma_o, gamma_t) = self.geometry(wo, h);
+        let order_pdf = Self::order_pdf(&ap);
+
+        // Four samples out of two.
+        let (mut u_order, u_azimuth) = demux(u.0);
+        let (u_longitudinal, u_phi) = demux(u.1);
+
+        let mut p = P_MAX;
+        for (order, pdf) in order_pdf.iter().enumerate().take(P_MAX) {
+            if u_order < *pdf {
+                p = order;
+                break;
+            }
+            u_order -= pdf;
+        }
+
+        let sin_theta_o = wo.x();
+        let cos_theta_o = safe_sqrt(1.0 - sin_theta_o * sin_theta_o);
+        let phi_o = wo.z().atan2(wo.y());
+        let (sin_theta_op, cos_theta_op) = self.tilted(p, sin_theta_o, cos_theta_o);
+
+        let u_longitudinal = u_longitudinal.max(1e-5);
+        let cos_theta = 1.0
+            + self.v[p] * (u_longitudinal + (1.0 - u_longitudinal) * (-2.0 / self.v[p]).exp()).ln();
+        let sin_theta = safe_sqrt(1.0 - cos_theta * cos_theta);
+        let cos_phi = (2.0 * PI * u_phi).cos();
+        let sin_theta_i = -cos_theta * sin_theta_op + sin_theta * cos_phi * cos_theta_op;
+        let cos_theta_i = safe_sqrt(1.0 - sin_theta_i * sin_theta_i);
+
+        let dphi = if p < P_MAX {
+            phi_function(p, gamma_o, gamma_t) + sample_trimmed_logistic(u_azimuth, self.s)
+        } else {
+            2.0 * PI * u_azimuth
+        };
+        let phi_i = phi_o + dphi;
+        let wi = Vec3::new(
+            sin_theta_i,
+            cos_theta_i * phi_i.cos(),
+            cos_theta_i * phi_i.sin(),
+        );
+
+        let pdf = self.pdf(hitrecord, wo, &wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some((
+            wi,
+            self.eval(hitrecord, wo, &wi),
+            pdf,
+            BsdfFlags::GLOSSY | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION,
+        ))
+    }
+
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        let h = offset(hitrecord);
+        let (ap, gamma_o, gamma_t) = self.geometry(wo, h);
+        let order_pdf = Self::order_pdf(&ap);
+
+        let sin_theta_o = wo.x();
+        let cos_theta_o = safe_sqrt(1.0 - sin_theta_o * sin_theta_o);
+        let phi_o = wo.z().atan2(wo.y());
+        let sin_theta_i = wi.x();
+        let cos_theta_i = safe_sqrt(1.0 - sin_theta_i * sin_theta_i);
+        let phi_i = wi.z().atan2(wi.y());
+        let phi = phi_i - phi_o;
+
+        let mut pdf = 0.0;
+        for (p, order) in order_pdf.iter().enumerate().take(P_MAX) {
+            let (sin_theta_op, cos_theta_op) = self.tilted(p, sin_theta_o, cos_theta_o);
+            pdf += longitudinal(
+                cos_theta_i,
+                cos_theta_op,
+                sin_theta_i,
+                sin_theta_op,
+                self.v[p],
+            ) * order
+                * azimuthal(phi, p, self.s, gamma_o, gamma_t);
+        }
+        pdf += longitudinal(
+            cos_theta_i,
+            cos_theta_o,
+            sin_theta_i,
+            sin_theta_o,
+            self.v[P_MAX],
+        ) * order_pdf[P_MAX]
+            / (2.0 * PI);
+        pdf
+    }
+}
+
+// Where across the fiber the hit is, from -1 to 1.
+fn offset(hitrecord: &HitRecord) -> f64 {
+    (2.0 * hitrecord.surface_coordinates.1 - 1.0).clamp(-1.0, 1.0)
+}
+
+fn safe_sqrt(x: f64) -> f64 {
+    x.max(0.0).sqrt()
+}
+
+fn exp(c: Color) -> Color {
+    Color::new(c.x().exp(), c.y().exp(), c.z().exp())
+}
+
+// Fresnel and absorption along each order's path through the fiber.
+fn attenuation(cos_theta_o: f64, eta: f64, h: f64, transmittance: Color) -> [Color; P_MAX + 1] {
+    let cos_gamma_o = safe_sqrt(1.0 - h * h);
+    let f = fresnel_dielectric(cos_theta_o * cos_gamma_o, eta);
+    let white = Color::new(1.0, 1.0, 1.0);
+
+    let r = f * white;
+    let tt = (1.0 - f) * (1.0 - f) * transmittance;
+    let trt = f * tt * transmittance;
+    let ft = f * transmittance;
+    let rest = trt
+        * ft
+        * Color::new(
+            1.0 / (1.0 - ft.x()),
+            1.0 / (1.0 - ft.y()),
+            1.0 / (1.0 - ft.z()),
+        );
+    [r, tt, trt, rest]
+}
+
+fn bessel_i0(x: f64) -> f64 {
+    let mut value = 0.0;
+    let mut x2i = 1.0;
+    let mut factorial = 1.0;
+    let mut four_i = 1.0;
+    for i in 0..10 {
+        if i > 1 {
+            factorial *= f64::from(i);
+        }
+        value += x2i / (four_i * factorial * factorial);
+        x2i *= x * x;
+        four_i *= 4.0;
+    }
+    value
+}
+
+fn log_bessel_i0(x: f64) -> f64 {
+    if x > 12.0 {
+        x + 0.5 * (-(2.0 * PI).ln() + (1.0 / x).ln() + 1.0 / (8.0 * x))
+    } else {
+        bessel_i0(x).ln()
+    }
+}
+
+// The longitudinal scattering function with variance `v`.
+fn longitudinal(
+    cos_theta_i: f64,
+    cos_theta_o: f64,
+    sin_theta_i: f64,
+    sin_theta_o: f64,
+    v: f64,
+) -> f64 {
+    let a = cos_theta_i * cos_theta_o / v;
+    let b = sin_theta_i * sin_theta_o / v;
+    if v <= 0.1 {
+        (log_bessel_i0(a) - b - 1.0 / v + std::f64::consts::LN_2 + (1.0 / (2.0 * v)).ln()).exp()
+    } else {
+        (-b).exp() * bessel_i0(a) / ((1.0 / v).sinh() * 2.0 * v)
+    }
+}
+
+// The azimuth order `p` leaves at relative to where it entered.
+fn phi_function(p: usize, gamma_o: f64, gamma_t: f64) -> f64 {
+    let p = p as f64;
+    2.0 * p * gamma_t - 2.0 * gamma_o + p * PI
+}
+
+// The azimuthal scattering function of order `p`.
+fn azimuthal(phi: f64, p: usize, s: f64, gamma_o: f64, gamma_t: f64) -> f64 {
+    let mut dphi = phi - phi_function(p, gamma_o, gamma_t);
+    while dphi > PI {
+        dphi -= 2.0 * PI;
+    }
+    while dphi < -PI {
+        dphi += 2.0 * PI;
+    }
+    trimmed_logistic(dphi, s)
+}
+
+fn logistic(x: f64, s: f64) -> f64 {
+    let x = x.abs();
+    (-x / s).exp() / (s * (1.0 + (-x / s).exp()).powi(2))
+}
+
+fn logistic_cdf(x: f64, s: f64) -> f64 {
+    1.0 / (1.0 + (-x / s).exp())
+}
+
+// The logistic distribution limited to [-π, π].
+fn trimmed_logistic(x: f64, s: f64) -> f64 {
+    logistic(x, s) / (logistic_cdf(PI, s) - logistic_cdf(-PI, s))
+}
+
+fn sample_trimmed_logistic(u: f64, s: f64) -> f64 {
+    let k = logistic_cdf(PI, s) - logistic_cdf(-PI, s);
+    let x = -s * (1.0 / (u * k + logistic_cdf(-PI, s)) - 1.0).ln();
+    x.clamp(-PI, PI)
+}
+
+// Splits one sample into two by separating the odd and even bits of its
+// binary expansion.
+fn demux(u: f64) -> (f64, f64) {
+    let bits = (u * (1u64 << 32) as f64) as u64;
+    let compact = |mut x: u64| {
+        x &= 0x5555_5555_5555_5555;
+        x = (x ^ (x >> 1)) & 0x3333_3333_3333_3333;
+        x = (x ^ (x >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+        x = (x ^ (x >> 4)) & 0x00ff_00ff_00ff_00ff;
+        x = (x ^ (x >> 8)) & 0x0000_ffff_0000_ffff;
+        x = (x ^ (x >> 16)) & 0x0000_0000_ffff_ffff;
+        x
+    };
+    let scale = 1.0 / (1u64 << 16) as f64;
+    (
+        compact(bits) as f64 * scale,
+        compact(bits >> 1) as f64 * scale,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, sync::Arc};
+
+    use crate::{
+        hits::hittable::HitRecord,
+        materials::Material,
+        random_f64,
+        vec3::{sample_uniform_sphere, Color, Point3, Vec3},
+    };
+
+    use super::Hair;
+
+    // A white fiber without absorption scatters all light: the sampled
+    // weights average to one and agree with integrating `eval` uniformly.
+    #[test]
+    fn white_furnace() {
+        let hair = Arc::new(Hair::new(Color::default(), 1.55, 0.3, 0.3, 2.0));
+        let wo = Vec3::new(0.3, 0.6, (1.0f64 - 0.09 - 0.36).sqrt());
+
+        let samples = 20000;
+        let mut sampled = 0.0;
+        let mut uniform = 0.0;
+        for _ in 0..samples {
+            let hitrecord = HitRecord {
+                p: Point3::default(),
+                normal: Vec3::new(0.0, 0.0, 1.0),
+                shading_normal: Vec3::new(0.0, 0.0, 1.0),
+                dpdu: Vec3::new(1.0, 0.0, 0.0),
+                dpdv: Vec3::new(0.0, 1.0, 0.0),
+                t: 0.0,
+                surface_coordinates: (0.0, random_f64()),
+                front_face: true,
+                samplable: true,
+                material: hair.clone(),
+                exterior_ior: 1.0,
+            };
+
+            if let Some((wi, f, pdf, _)) =
+                hair.sample(&hitrecord, &wo, (random_f64(), random_f64()))
+            {
+                sampled += f.y() * wi.z().abs() / pdf;
+            }
+            let wi = sample_uniform_sphere((random_f64(), random_f64()));
+            uniform += hair.eval(&hitrecord, &wo, &wi).y() * wi.z().abs() * 4.0 * PI;
+        }
+
+        let sampled = sampled / f64::from(samples);
+        let uniform = uniform / f64::from(samples);
+        assert!((sampled - 1.0).abs() < 0.05, "sampled {sampled}");
+        assert!((uniform - 1.0).abs() < 0.1, "uniform {uniform}");
+    }
+}
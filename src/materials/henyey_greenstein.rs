@@ -0,0 +1,142 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hits::hittable::HitRecord,
+    textures::{solid_color::SolidColor, Texture},
+    vec3::{dot, Color, Vec3},
+};
+
+use super::{
+    bsdf::{direction_around, BsdfFlags, BsdfSample},
+    Material,
+};
+
+// Phase function with a single asymmetry parameter `g` in (-1, 1): positive
+// values scatter forward, negative ones backward and zero is isotropic.
+pub struct HenyeyGreenstein {
+    albedo: Arc<dyn Texture>,
+    g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Arc<dyn Texture>, g: f64) -> Self {
+        Self {
+            albedo,
+            g: g.clamp(-0.99, 0.99),
+        }
+    }
+
+    pub fn new_from_color(c: Color, g: f64) -> Self {
+        Self::new(Arc::new(SolidColor::new_from_color(c)), g)
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        let albedo = self
+            .albedo
+            .value(hitrecord.surface_coordinates, &hitrecord.p);
+        henyey_greenstein(dot(&-*wo, wi), self.g) * albedo
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        let wi = sample_henyey_greenstein(wo, self.g, u);
+        Some((
+            wi,
+            self.eval(hitrecord, wo, &wi),
+            self.pdf(hitrecord, wo, &wi),
+            BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION,
+        ))
+    }
+
+    #[allow(unused_variables)]
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        henyey_greenstein(dot(&-*wo, wi), self.g)
+    }
+
+    fn is_phase_function(&self) -> bool {
+        true
+    }
+}
+
+// A blend of two Henyey-Greenstein lobes, typically one forward and one
+// backward, weighted by `weight` and `1 - weight`.
+pub struct DoubleHenyeyGreenstein {
+    albedo: Arc<dyn Texture>,
+    g1: f64,
+    g2: f64,
+    weight: f64,
+}
+
+impl DoubleHenyeyGreenstein {
+    pub fn new(albedo: Arc<dyn Texture>, g1: f64, g2: f64, weight: f64) -> Self {
+        Self {
+            albedo,
+            g1: g1.clamp(-0.99, 0.99),
+            g2: g2.clamp(-0.99, 0.99),
+            weight: weight.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn new_from_color(c: Color, g1: f64, g2: f64, weight: f64) -> Self {
+        Self::new(Arc::new(SolidColor::new_from_color(c)), g1, g2, weight)
+    }
+
+    fn phase(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        let cos_theta = dot(&-*wo, wi);
+        self.weight * henyey_greenstein(cos_theta, self.g1)
+            + (1.0 - self.weight) * henyey_greenstein(cos_theta, self.g2)
+    }
+}
+
+impl Material for DoubleHenyeyGreenstein {
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        let albedo = self
+            .albedo
+            .value(hitrecord.surface_coordinates, &hitrecord.p);
+        self.phase(wo, wi) * albedo
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        let wi = if u.0 < self.weight {
+            sample_henyey_greenstein(wo, self.g1, (u.0 / self.weight, u.1))
+        } else {
+            let remapped = (u.0 - self.weight) / (1.0 - self.weight);
+            sample_henyey_greenstein(wo, self.g2, (remapped, u.1))
+        };
+
+        Some((
+            wi,
+            self.eval(hitrecord, wo, &wi),
+            self.pdf(hitrecord, wo, &wi),
+            BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION,
+        ))
+    }
+
+    #[allow(unused_variables)]
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        self.phase(wo, wi)
+    }
+
+    fn is_phase_function(&self) -> bool {
+        true
+    }
+}
+
+// `cos_theta` is measured between the propagation directions before and
+// after scattering.
+pub fn henyey_greenstein(cos_theta: f64, g: f64) -> f64 {
+    let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+    (1.0 - g * g) / (4.0 * PI * denominator * denominator.max(0.0).sqrt())
+}
+
+pub fn sample_henyey_greenstein(wo: &Vec3, g: f64, u: (f64, f64)) -> Vec3 {
+    let cos_theta = if g.abs() < 1e-3 {
+        1.0 - 2.0 * u.0
+    } else {
+        let square = (1.0 - g * g) / (1.0 - g + 2.0 * g * u.0);
+        ((1.0 + g * g - square * square) / (2.0 * g)).clamp(-1.0, 1.0)
+    };
+
+    direction_around(&-*wo, cos_theta, 2.0 * PI * u.1)
+}
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::hittable::HitRecord,
+    ray::Ray,
+    vec3::{Color, Vec3},
+};
+
+use super::Material;
+
+// What a closed surface encloses: a refraction index, a Beer-Lambert
+// absorption coefficient per unit length and optionally a homogeneous
+// scattering medium. Where interiors overlap, the one with the highest
+// priority wins, so e.g. water with a lower priority than its glass can be
+// modelled slightly larger than the glass' inner wall.
+pub struct Interior {
+    refraction_index: f64,
+    priority: u32,
+    absorption: Color,
+    scattering: f64,
+    phase_function: Option<Arc<dyn Material>>,
+}
+
+impl Interior {
+    pub fn new(refraction_index: f64, priority: u32) -> Self {
+        Self::new_with_absorption(refraction_index, priority, Color::default())
+    }
+
+    pub fn new_with_absorption(refraction_index: f64, priority: u32, absorption: Color) -> Self {
+        Self {
+            refraction_index,
+            priority,
+            absorption,
+            scattering: 0.0,
+            phase_function: None,
+        }
+    }
+
+    // `scattering` is the scattering coefficient; the phase function's albedo
+    // tints every scattering event.
+    pub fn new_with_scattering(
+        refraction_index: f64,
+        priority: u32,
+        absorption: Color,
+        scattering: f64,
+        phase_function: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            refraction_index,
+            priority,
+            absorption,
+            scattering,
+            phase_function: Some(phase_function),
+        }
+    }
+
+    pub fn refraction_index(&self) -> f64 {
+        self.refraction_index
+    }
+
+    pub fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    // Fraction of light surviving absorption over `distance`.
+    pub fn transmittance(&self, distance: f64) -> Color {
+        let channel = |absorption: f64| {
+            if absorption <= 0.0 {
+                1.0
+            } else {
+                (-absorption * distance).exp()
+            }
+        };
+        Color::new(
+            channel(self.absorption.x()),
+            channel(self.absorption.y()),
+            channel(self.absorption.z()),
+        )
+    }
+
+    // Samples the distance to the next scattering event. Absorption is left
+    // to `transmittance`, so the estimate stays unbiased with colored media.
+    pub fn sample_distance(&self, u: f64) -> f64 {
+        if self.scattering <= 0.0 || self.phase_function.is_none() {
+            return f64::INFINITY;
+        }
+        -(1.0 - u).ln() / self.scattering
+    }
+
+    pub fn scattering_record(&self, r: &Ray, t: f64) -> Option<HitRecord> {
+        let phase_function = self.phase_function.as_ref()?;
+        Some(HitRecord {
+            t,
+            p: r.at(t),
+            normal: Vec3::new(1.0, 0.0, 0.0),
+            shading_normal: Vec3::new(1.0, 0.0, 0.0),
+            dpdu: Vec3::new(0.0, 1.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, 1.0),
+            front_face: true,
+            samplable: false,
+            material: phase_function.clone(),
+            surface_coordinates: (0.0, 0.0),
+            exterior_ior: 1.0,
+        })
+    }
+}
+
+// The interiors a path is currently inside, in the order they were entered.
+#[derive(Default, Clone)]
+pub struct MediumStack {
+    entries: Vec<Arc<Interior>>,
+}
+
+impl MediumStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The interior that determines the optical properties at the current
+    // position: the highest priority, and the most recently entered on ties.
+    pub fn current(&self) -> Option<&Arc<Interior>> {
+        self.entries.iter().max_by_key(|interior| interior.priority)
+    }
+
+    pub fn refraction_index(&self) -> f64 {
+        self.current()
+            .map_or(1.0, |interior| interior.refraction_index)
+    }
+
+    // Whether crossing the boundary of `interior` changes the current medium.
+    // Surfaces of lower priority interiors inside a higher priority one are
+    // false intersections and are passed through.
+    pub fn is_interface(&self, interior: &Arc<Interior>, entering: bool) -> bool {
+        match self.current() {
+            None => true,
+            Some(current) if entering => interior.priority >= current.priority,
+            Some(current) => Arc::ptr_eq(current, interior) || !self.contains(interior),
+        }
+    }
+
+    // Refraction index on the other side of a boundary of `interior`, or
+    // around a surface without one.
+    pub fn exterior_ior(&self, interior: Option<&Arc<Interior>>, entering: bool) -> f64 {
+        match interior {
+            Some(interior) if !entering => {
+                let mut rest = self.clone();
+                rest.exit(interior);
+                rest.refraction_index()
+            }
+            _ => self.refraction_index(),
+        }
+    }
+
+    pub fn cross(&mut self, interior: &Arc<Interior>, entering: bool) {
+        if entering {
+            self.entries.push(interior.clone());
+        } else {
+            self.exit(interior);
+        }
+    }
+
+    fn exit(&mut self, interior: &Arc<Interior>) {
+        if let Some(index) = self.entries.iter().rposition(|e| Arc::ptr_eq(e, interior)) {
+            self.entries.remove(index);
+        }
+    }
+
+    fn contains(&self, interior: &Arc<Interior>) -> bool {
+        self.entries.iter().any(|e| Arc::ptr_eq(e, interior))
+    }
+}
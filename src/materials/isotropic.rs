@@ -1,36 +1,56 @@
-use std::rc::Rc;
+use std::{f64::consts::PI, sync::Arc};
 
 use crate::{
     hits::hittable::HitRecord,
-    ray::Ray,
     textures::{solid_color::SolidColor, Texture},
-    vec3::{random_in_unit_sphere, Color},
+    vec3::{sample_uniform_sphere, Color, Vec3},
 };
 
-use super::Material;
+use super::{
+    bsdf::{BsdfFlags, BsdfSample},
+    Material,
+};
 
 pub struct Isotropic {
-    albedo: Rc<dyn Texture>,
+    albedo: Arc<dyn Texture>,
 }
 
 impl Isotropic {
     pub fn new_from_color(c: Color) -> Self {
         Self {
-            albedo: Rc::new(SolidColor::new_from_color(c)),
+            albedo: Arc::new(SolidColor::new_from_color(c)),
         }
     }
 
-    pub fn new(a: Rc<dyn Texture>) -> Self {
+    pub fn new(a: Arc<dyn Texture>) -> Self {
         Self { albedo: a }
     }
 }
 
 impl Material for Isotropic {
-    fn scatter(&self, r_in: Ray, hitrecord: &HitRecord) -> Option<(Ray, Color)> {
-        let scattered = Ray::new(hitrecord.p, random_in_unit_sphere(), r_in.time());
-        let attenuation = self
-            .albedo
-            .value(hitrecord.surface_coordinates, &hitrecord.p);
-        Some((scattered, attenuation))
+    #[allow(unused_variables)]
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        self.albedo
+            .value(hitrecord.surface_coordinates, &hitrecord.p)
+            / (4.0 * PI)
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        let wi = sample_uniform_sphere(u);
+        Some((
+            wi,
+            self.eval(hitrecord, wo, &wi),
+            self.pdf(hitrecord, wo, &wi),
+            BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION,
+        ))
+    }
+
+    #[allow(unused_variables)]
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn is_phase_function(&self) -> bool {
+        true
     }
 }
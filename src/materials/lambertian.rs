@@ -1,43 +1,71 @@
-use std::rc::Rc;
+use std::{f64::consts::FRAC_1_PI, sync::Arc};
 
 use crate::{
     hits::hittable::HitRecord,
-    ray::Ray,
     textures::{solid_color::SolidColor, Texture},
-    vec3::{random_unit_vector, Color},
+    vec3::{sample_cosine_hemisphere, Color, Vec3},
 };
 
-use super::Material;
+use super::{
+    bsdf::{abs_cos_theta, same_hemisphere, BsdfFlags, BsdfSample},
+    Material,
+};
 
 pub struct Lambertian {
-    albedo: Rc<dyn Texture>,
+    albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
         Self {
-            albedo: Rc::new(SolidColor::new_from_color(albedo)),
+            albedo: Arc::new(SolidColor::new_from_color(albedo)),
         }
     }
 
-    pub fn new_from_texture(albedo: Rc<dyn Texture>) -> Self {
+    pub fn new_from_texture(albedo: Arc<dyn Texture>) -> Self {
         Self { albedo }
     }
 }
 
 impl Material for Lambertian {
-    #[allow(unused_variables)]
-    fn scatter(&self, r_in: Ray, hitrecord: &HitRecord) -> Option<(Ray, Color)> {
-        let mut scatter_direction = hitrecord.normal + random_unit_vector();
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        if !same_hemisphere(wo, wi) {
+            return Color::default();
+        }
+
+        FRAC_1_PI
+            * self.albedo.value_with_normal(
+                hitrecord.surface_coordinates,
+                &hitrecord.p,
+                &hitrecord.normal,
+            )
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        let mut wi = sample_cosine_hemisphere(u);
+        if wo.z() < 0.0 {
+            wi[2] *= -1.0;
+        }
+
+        let pdf = self.pdf(hitrecord, wo, &wi);
+        if pdf == 0.0 {
+            return None;
+        }
 
-        if scatter_direction.near_zero() {
-            scatter_direction = hitrecord.normal;
+        Some((
+            wi,
+            self.eval(hitrecord, wo, &wi),
+            pdf,
+            BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION,
+        ))
+    }
+
+    #[allow(unused_variables)]
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        if !same_hemisphere(wo, wi) {
+            return 0.0;
         }
 
-        let scattered_ray = Ray::new(hitrecord.p, scatter_direction, r_in.time());
-        let attenuation = self
-            .albedo
-            .value(hitrecord.surface_coordinates, &hitrecord.p);
-        Some((scattered_ray, attenuation))
+        abs_cos_theta(wi) * FRAC_1_PI
     }
 }
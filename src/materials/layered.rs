@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::hittable::HitRecord,
+    vec3::{Color, Point3, Vec3},
+};
+
+use super::{
+    bsdf::{abs_cos_theta, fresnel_dielectric, BsdfFlags, BsdfSample},
+    Material,
+};
+
+// A smooth dielectric coat of the given thickness on top of an arbitrary base
+// material. Light is refracted into the coat, attenuated on the way down and
+// back up, and scattered by the base in between.
+pub struct Layered {
+    base: Arc<dyn Material>,
+    refraction_index: f64,
+    absorption: Color,
+    thickness: f64,
+}
+
+impl Layered {
+    pub fn new(base: Arc<dyn Material>, refraction_index: f64) -> Self {
+        Self {
+            base,
+            refraction_index,
+            absorption: Color::default(),
+            thickness: 0.0,
+        }
+    }
+
+    pub fn new_with_absorption(
+        base: Arc<dyn Material>,
+        refraction_index: f64,
+        absorption: Color,
+        thickness: f64,
+    ) -> Self {
+        Self {
+            base,
+            refraction_index,
+            absorption,
+            thickness,
+        }
+    }
+
+    fn refract_in(&self, w: &Vec3) -> Vec3 {
+        let eta = self.refraction_index;
+        let sin2_theta_t = (1.0 - w.z() * w.z()) / (eta * eta);
+        Vec3::new(
+            w.x() / eta,
+            w.y() / eta,
+            (1.0 - sin2_theta_t).max(0.0).sqrt(),
+        )
+    }
+
+    fn refract_out(&self, w: &Vec3) -> Option<Vec3> {
+        let eta = self.refraction_index;
+        let sin2_theta_t = eta * eta * (w.x() * w.x() + w.y() * w.y());
+        if sin2_theta_t >= 1.0 || w.z() <= 0.0 {
+            return None;
+        }
+        Some(Vec3::new(
+            w.x() * eta,
+            w.y() * eta,
+            (1.0 - sin2_theta_t).sqrt(),
+        ))
+    }
+
+    fn transmittance(&self, wo_inside: &Vec3, wi_inside: &Vec3) -> Color {
+        if self.thickness == 0.0 {
+            return Color::new(1.0, 1.0, 1.0);
+        }
+
+        let path_length =
+            self.thickness * (1.0 / abs_cos_theta(wo_inside) + 1.0 / abs_cos_theta(wi_inside));
+        Color::new(
+            (-self.absorption.x() * path_length).exp(),
+            (-self.absorption.y() * path_length).exp(),
+            (-self.absorption.z() * path_length).exp(),
+        )
+    }
+}
+
+impl Material for Layered {
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return Color::default();
+        }
+
+        let eta = self.refraction_index;
+        let wo_inside = self.refract_in(wo);
+        let wi_inside = self.refract_in(wi);
+
+        let fresnel_o = fresnel_dielectric(wo.z(), eta);
+        let fresnel_i = fresnel_dielectric(wi.z(), eta);
+
+        (1.0 - fresnel_o) * (1.0 - fresnel_i) / (eta * eta)
+            * self.transmittance(&wo_inside, &wi_inside)
+            * self.base.eval(hitrecord, &wo_inside, &wi_inside)
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let fresnel_o = fresnel_dielectric(wo.z(), self.refraction_index);
+        if u.0 < fresnel_o {
+            let wi = Vec3::new(-wo.x(), -wo.y(), wo.z());
+            let f = fresnel_o * Color::new(1.0, 1.0, 1.0) / abs_cos_theta(&wi);
+            return Some((
+                wi,
+                f,
+                fresnel_o,
+                BsdfFlags::SPECULAR | BsdfFlags::REFLECTION,
+            ));
+        }
+
+        let remapped = ((u.0 - fresnel_o) / (1.0 - fresnel_o), u.1);
+        let wo_inside = self.refract_in(wo);
+        let (wi_inside, f_base, pdf_base, flags) =
+            self.base.sample(hitrecord, &wo_inside, remapped)?;
+        let wi = self.refract_out(&wi_inside)?;
+
+        if flags.is_specular() {
+            let fresnel_i = fresnel_dielectric(wi.z(), self.refraction_index);
+            let f = (1.0 - fresnel_o) * (1.0 - fresnel_i) * abs_cos_theta(&wi_inside)
+                / abs_cos_theta(&wi)
+                * self.transmittance(&wo_inside, &wi_inside)
+                * f_base;
+            return Some((wi, f, (1.0 - fresnel_o) * pdf_base, flags));
+        }
+
+        let pdf = self.pdf(hitrecord, wo, &wi);
+        if pdf == 0.0 {
+            return None;
+        }
+        Some((wi, self.eval(hitrecord, wo, &wi), pdf, flags))
+    }
+
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+
+        let eta = self.refraction_index;
+        let wo_inside = self.refract_in(wo);
+        let wi_inside = self.refract_in(wi);
+        let fresnel_o = fresnel_dielectric(wo.z(), eta);
+
+        // Change of variables from the solid angle inside the coat to the one outside.
+        let jacobian = abs_cos_theta(wi) / (eta * eta * abs_cos_theta(&wi_inside));
+        (1.0 - fresnel_o) * self.base.pdf(hitrecord, &wo_inside, &wi_inside) * jacobian
+    }
+
+    fn emitted(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.base.emitted(uv, p)
+    }
+
+    // The base is shaded under the coat with its own normal.
+    fn shading_normal(&self, hitrecord: &HitRecord) -> Vec3 {
+        self.base.shading_normal(hitrecord)
+    }
+
+    // The coat is smooth, so only a specular base leaves nothing to evaluate.
+    fn is_specular(&self, hitrecord: &HitRecord) -> bool {
+        self.base.is_specular(hitrecord)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.base.is_emissive()
+    }
+}
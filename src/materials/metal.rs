@@ -1,37 +1,127 @@
+use std::sync::Arc;
+
 use crate::{
     hits::hittable::HitRecord,
-    ray::Ray,
-    vec3::{dot, random_in_unit_sphere, reflect, Color},
+    textures::{scalar::ConstantScalar, solid_color::SolidColor, ScalarTexture, Texture},
+    vec3::{dot, reflect, unit_vector, Color, Vec3},
 };
 
-use super::Material;
+use super::{
+    bsdf::{abs_cos_theta, same_hemisphere, schlick_fresnel, BsdfFlags, BsdfSample},
+    microfacet::TrowbridgeReitz,
+    Material,
+};
 
 pub struct Metal {
-    albedo: Color,
-    fuzz: f64,
+    albedo: Arc<dyn Texture>,
+    roughness: Arc<dyn ScalarTexture>,
 }
 
 impl Metal {
     pub fn new(albedo: Color, fuzz: f64) -> Self {
         let fuzz = if fuzz < 1.0 { fuzz } else { 1.0 };
-        Self { albedo, fuzz }
+        Self {
+            albedo: Arc::new(SolidColor::new_from_color(albedo)),
+            roughness: Arc::new(ConstantScalar::new(fuzz)),
+        }
+    }
+
+    pub fn new_from_texture(albedo: Arc<dyn Texture>, roughness: Arc<dyn ScalarTexture>) -> Self {
+        Self { albedo, roughness }
+    }
+
+    fn albedo(&self, hitrecord: &HitRecord) -> Color {
+        self.albedo.value_with_normal(
+            hitrecord.surface_coordinates,
+            &hitrecord.p,
+            &hitrecord.normal,
+        )
+    }
+
+    fn distribution(&self, hitrecord: &HitRecord) -> TrowbridgeReitz {
+        let roughness = self.roughness.value_with_normal(
+            hitrecord.surface_coordinates,
+            &hitrecord.p,
+            &hitrecord.normal,
+        );
+        TrowbridgeReitz::new(roughness.clamp(0.0, 1.0))
     }
 }
 
 impl Material for Metal {
-    fn scatter(&self, r_in: Ray, hitrecord: &HitRecord) -> Option<(Ray, Color)> {
-        let reflected_direction = reflect(&r_in.direction(), &hitrecord.normal);
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        let distribution = self.distribution(hitrecord);
+        if !same_hemisphere(wo, wi) || distribution.effectively_smooth() {
+            return Color::default();
+        }
 
-        let scattered_ray = Ray::new(
-            hitrecord.p,
-            reflected_direction + self.fuzz * random_in_unit_sphere(),
-            r_in.time(),
-        );
-        let attenuation = self.albedo;
-        if dot(&scattered_ray.direction(), &hitrecord.normal) > 0.0 {
-            Some((scattered_ray, attenuation))
-        } else {
-            None
+        let cos_theta_o = abs_cos_theta(wo);
+        let cos_theta_i = abs_cos_theta(wi);
+        if cos_theta_o == 0.0 || cos_theta_i == 0.0 {
+            return Color::default();
+        }
+
+        let wm = *wi + *wo;
+        if wm.len_squared() == 0.0 {
+            return Color::default();
+        }
+        let wm = unit_vector(wm);
+
+        let fresnel = schlick_fresnel(self.albedo(hitrecord), dot(wo, &wm).abs());
+        distribution.d(&wm) * distribution.g(wo, wi) * fresnel / (4.0 * cos_theta_o * cos_theta_i)
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        if wo.z() == 0.0 {
+            return None;
+        }
+
+        let distribution = self.distribution(hitrecord);
+        if distribution.effectively_smooth() {
+            let wi = Vec3::new(-wo.x(), -wo.y(), wo.z());
+            let f =
+                schlick_fresnel(self.albedo(hitrecord), abs_cos_theta(&wi)) / abs_cos_theta(&wi);
+            return Some((wi, f, 1.0, BsdfFlags::SPECULAR | BsdfFlags::REFLECTION));
+        }
+
+        let wm = distribution.sample_wm(wo, u);
+        let wi = reflect(&-*wo, &wm);
+        if !same_hemisphere(wo, &wi) {
+            return None;
         }
+
+        let pdf = self.pdf(hitrecord, wo, &wi);
+        if pdf == 0.0 {
+            return None;
+        }
+
+        Some((
+            wi,
+            self.eval(hitrecord, wo, &wi),
+            pdf,
+            BsdfFlags::GLOSSY | BsdfFlags::REFLECTION,
+        ))
+    }
+
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        let distribution = self.distribution(hitrecord);
+        if !same_hemisphere(wo, wi) || distribution.effectively_smooth() {
+            return 0.0;
+        }
+
+        let wm = *wo + *wi;
+        if wm.len_squared() == 0.0 {
+            return 0.0;
+        }
+        let mut wm = unit_vector(wm);
+        if wm.z() < 0.0 {
+            wm = -wm;
+        }
+
+        distribution.pdf(wo, &wm) / (4.0 * dot(wo, &wm).abs())
+    }
+
+    fn is_specular(&self, hitrecord: &HitRecord) -> bool {
+        self.distribution(hitrecord).effectively_smooth()
     }
 }
@@ -0,0 +1,92 @@
+use std::f64::consts::PI;
+
+use crate::vec3::{cross, dot, sample_uniform_disk, unit_vector, Vec3};
+
+use super::bsdf::abs_cos_theta;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TrowbridgeReitz {
+    alpha: f64,
+}
+
+impl TrowbridgeReitz {
+    pub fn new(alpha: f64) -> Self {
+        Self { alpha }
+    }
+
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    pub fn effectively_smooth(&self) -> bool {
+        self.alpha < 1e-3
+    }
+
+    pub fn d(&self, wm: &Vec3) -> f64 {
+        let cos2_theta = wm.z() * wm.z();
+        let sin2_theta = (1.0 - cos2_theta).max(0.0);
+        if cos2_theta == 0.0 {
+            return 0.0;
+        }
+        let tan2_theta = sin2_theta / cos2_theta;
+        let alpha2 = self.alpha * self.alpha;
+        let e = tan2_theta / alpha2;
+
+        1.0 / (PI * alpha2 * cos2_theta * cos2_theta * (1.0 + e) * (1.0 + e))
+    }
+
+    pub fn lambda(&self, w: &Vec3) -> f64 {
+        let cos2_theta = w.z() * w.z();
+        if cos2_theta == 0.0 {
+            return 0.0;
+        }
+        let tan2_theta = (1.0 - cos2_theta).max(0.0) / cos2_theta;
+        let alpha2_tan2 = self.alpha * self.alpha * tan2_theta;
+
+        ((1.0 + alpha2_tan2).sqrt() - 1.0) / 2.0
+    }
+
+    pub fn g1(&self, w: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    pub fn g(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    pub fn pdf(&self, w: &Vec3, wm: &Vec3) -> f64 {
+        let cos_theta = abs_cos_theta(w);
+        if cos_theta == 0.0 {
+            return 0.0;
+        }
+        self.g1(w) / cos_theta * self.d(wm) * dot(w, wm).abs()
+    }
+
+    // Samples a microfacet normal from the distribution of normals visible from `w`.
+    pub fn sample_wm(&self, w: &Vec3, u: (f64, f64)) -> Vec3 {
+        let mut wh = unit_vector(Vec3::new(self.alpha * w.x(), self.alpha * w.y(), w.z()));
+        if wh.z() < 0.0 {
+            wh = -wh;
+        }
+
+        let t1 = if wh.z() < 0.99999 {
+            unit_vector(cross(&Vec3::new(0.0, 0.0, 1.0), &wh))
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = cross(&wh, &t1);
+
+        let p = sample_uniform_disk(u);
+        let h = (1.0 - p.x() * p.x()).sqrt();
+        let t = (1.0 + wh.z()) / 2.0;
+        let py = (1.0 - t) * h + t * p.y();
+        let pz = (1.0 - p.x() * p.x() - py * py).max(0.0).sqrt();
+
+        let nh = p.x() * t1 + py * t2 + pz * wh;
+        unit_vector(Vec3::new(
+            self.alpha * nh.x(),
+            self.alpha * nh.y(),
+            nh.z().max(1e-6),
+        ))
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::hittable::HitRecord,
+    spectrum::{rgb_to_spectrum, SampledWavelengths},
+    textures::{scalar::ConstantScalar, ScalarTexture},
+    vec3::{unit_vector, Color, Point3, Vec3},
+};
+
+use super::{
+    bsdf::{BsdfSample, SpectralSample},
+    interior::Interior,
+    Material,
+};
+
+pub struct MixMaterial {
+    first: Arc<dyn Material>,
+    second: Arc<dyn Material>,
+    mask: Arc<dyn ScalarTexture>,
+}
+
+impl MixMaterial {
+    pub fn new(first: Arc<dyn Material>, second: Arc<dyn Material>, amount: f64) -> Self {
+        Self {
+            first,
+            second,
+            mask: Arc::new(ConstantScalar::new(amount)),
+        }
+    }
+
+    pub fn new_from_texture(
+        first: Arc<dyn Material>,
+        second: Arc<dyn Material>,
+        mask: Arc<dyn ScalarTexture>,
+    ) -> Self {
+        Self {
+            first,
+            second,
+            mask,
+        }
+    }
+
+    fn amount(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.mask.value(uv, p).clamp(0.0, 1.0)
+    }
+
+    // The material to sample, the probability of picking it and `u` remapped
+    // for it.
+    fn choose(&self, hitrecord: &HitRecord, u: (f64, f64)) -> (&dyn Material, f64, (f64, f64)) {
+        let amount = self.amount(hitrecord.surface_coordinates, &hitrecord.p);
+        if u.0 < amount {
+            (&*self.second, amount, (u.0 / amount, u.1))
+        } else {
+            (
+                &*self.first,
+                1.0 - amount,
+                ((u.0 - amount) / (1.0 - amount), u.1),
+            )
+        }
+    }
+}
+
+impl Material for MixMaterial {
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        let amount = self.amount(hitrecord.surface_coordinates, &hitrecord.p);
+        (1.0 - amount) * self.first.eval(hitrecord, wo, wi)
+            + amount * self.second.eval(hitrecord, wo, wi)
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        let (chosen, probability, remapped) = self.choose(hitrecord, u);
+        let (wi, f, pdf, flags) = chosen.sample(hitrecord, wo, remapped)?;
+
+        if flags.is_specular() {
+            return Some((wi, probability * f, probability * pdf, flags));
+        }
+
+        Some((
+            wi,
+            self.eval(hitrecord, wo, &wi),
+            self.pdf(hitrecord, wo, &wi),
+            flags,
+        ))
+    }
+
+    fn sample_spectral(
+        &self,
+        hitrecord: &HitRecord,
+        wo: &Vec3,
+        u: (f64, f64),
+        lambda: &mut SampledWavelengths,
+    ) -> Option<SpectralSample> {
+        let (chosen, probability, remapped) = self.choose(hitrecord, u);
+        let (wi, f, pdf, flags) = chosen.sample_spectral(hitrecord, wo, remapped, lambda)?;
+
+        if flags.is_specular() {
+            return Some((wi, f * probability, probability * pdf, flags));
+        }
+
+        let f = rgb_to_spectrum::unbounded(self.eval(hitrecord, wo, &wi), lambda);
+        Some((wi, f, self.pdf(hitrecord, wo, &wi), flags))
+    }
+
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        let amount = self.amount(hitrecord.surface_coordinates, &hitrecord.p);
+        (1.0 - amount) * self.first.pdf(hitrecord, wo, wi)
+            + amount * self.second.pdf(hitrecord, wo, wi)
+    }
+
+    fn emitted(&self, uv: (f64, f64), p: &Point3) -> Color {
+        let amount = self.amount(uv, p);
+        (1.0 - amount) * self.first.emitted(uv, p) + amount * self.second.emitted(uv, p)
+    }
+
+    // Normals the two materials perturb differently are blended, so where
+    // the mask picks one of them its normal is kept exactly.
+    fn shading_normal(&self, hitrecord: &HitRecord) -> Vec3 {
+        let amount = self.amount(hitrecord.surface_coordinates, &hitrecord.p);
+        if amount == 0.0 {
+            return self.first.shading_normal(hitrecord);
+        }
+        if amount == 1.0 {
+            return self.second.shading_normal(hitrecord);
+        }
+
+        let blended = (1.0 - amount) * self.first.shading_normal(hitrecord)
+            + amount * self.second.shading_normal(hitrecord);
+        if blended.near_zero() {
+            hitrecord.shading_normal
+        } else {
+            unit_vector(blended)
+        }
+    }
+
+    fn is_specular(&self, hitrecord: &HitRecord) -> bool {
+        self.first.is_specular(hitrecord) && self.second.is_specular(hitrecord)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.first.is_emissive() || self.second.is_emissive()
+    }
+
+    // Nested dielectric tracking needs one medium for the whole surface,
+    // the first material's takes precedence.
+    fn interior(&self) -> Option<Arc<Interior>> {
+        self.first.interior().or_else(|| self.second.interior())
+    }
+}
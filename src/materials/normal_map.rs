@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::hittable::HitRecord,
+    spectrum::SampledWavelengths,
+    textures::Texture,
+    vec3::{cross, dot, unit_vector, Color, Point3, Vec3},
+};
+
+use super::{
+    bsdf::{BsdfSample, SpectralSample},
+    interior::Interior,
+    Material,
+};
+
+// Perturbs the shading normal of `material` with a tangent-space normal map,
+// where the red and green channels follow dp/du and dp/dv and blue the normal.
+pub struct NormalMap {
+    material: Arc<dyn Material>,
+    normal_map: Arc<dyn Texture>,
+    // Multiplies the tangent components, to strengthen or soften the map.
+    scale: f64,
+}
+
+impl NormalMap {
+    pub fn new(material: Arc<dyn Material>, normal_map: Arc<dyn Texture>) -> Self {
+        Self::new_with_scale(material, normal_map, 1.0)
+    }
+
+    pub fn new_with_scale(
+        material: Arc<dyn Material>,
+        normal_map: Arc<dyn Texture>,
+        scale: f64,
+    ) -> Self {
+        Self {
+            material,
+            normal_map,
+            scale,
+        }
+    }
+}
+
+impl Material for NormalMap {
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        self.material.eval(hitrecord, wo, wi)
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        self.material.sample(hitrecord, wo, u)
+    }
+
+    fn sample_spectral(
+        &self,
+        hitrecord: &HitRecord,
+        wo: &Vec3,
+        u: (f64, f64),
+        lambda: &mut SampledWavelengths,
+    ) -> Option<SpectralSample> {
+        self.material.sample_spectral(hitrecord, wo, u, lambda)
+    }
+
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        self.material.pdf(hitrecord, wo, wi)
+    }
+
+    fn shading_normal(&self, hitrecord: &HitRecord) -> Vec3 {
+        let base = self.material.shading_normal(hitrecord);
+        let outward = if hitrecord.front_face { base } else { -base };
+
+        let frame = hitrecord.shading_frame(&outward);
+        let tangent = frame.u();
+        // Green follows dp/dv also where (dp/du, dp/dv, n) is left-handed.
+        let mut bitangent = unit_vector(cross(&outward, &tangent));
+        if dot(&bitangent, &hitrecord.dpdv) < 0.0 {
+            bitangent = -bitangent;
+        }
+
+        let c = self.normal_map.value_with_normal(
+            hitrecord.surface_coordinates,
+            &hitrecord.p,
+            &hitrecord.normal,
+        );
+        let local = Vec3::new(
+            self.scale * (2.0 * c.x() - 1.0),
+            self.scale * (2.0 * c.y() - 1.0),
+            2.0 * c.z() - 1.0,
+        );
+        if local.near_zero() {
+            return base;
+        }
+
+        let perturbed =
+            unit_vector(local.x() * tangent + local.y() * bitangent + local.z() * outward);
+        if hitrecord.front_face {
+            perturbed
+        } else {
+            -perturbed
+        }
+    }
+
+    fn emitted(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.material.emitted(uv, p)
+    }
+
+    fn is_specular(&self, hitrecord: &HitRecord) -> bool {
+        self.material.is_specular(hitrecord)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn interior(&self) -> Option<Arc<Interior>> {
+        self.material.interior()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hits::hittable::HitRecord,
+        materials::{lambertian::Lambertian, Material},
+        textures::solid_color::SolidColor,
+        vec3::{dot, Color, Point3, Vec3},
+    };
+
+    use super::NormalMap;
+
+    // On an XZ rectangle dp/du, dp/dv and the normal are left-handed, green
+    // must still tilt the normal towards dp/dv.
+    #[test]
+    fn green_follows_dpdv() {
+        let map = NormalMap::new(
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+            Arc::new(SolidColor::new_from_color(Color::new(0.5, 0.75, 0.75))),
+        );
+        let hitrecord = HitRecord {
+            p: Point3::default(),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+            shading_normal: Vec3::new(0.0, 1.0, 0.0),
+            dpdu: Vec3::new(1.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, 1.0),
+            t: 1.0,
+            surface_coordinates: (0.5, 0.5),
+            front_face: true,
+            samplable: true,
+            material: Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+            exterior_ior: 1.0,
+        };
+
+        let normal = map.shading_normal(&hitrecord);
+        assert!(dot(&normal, &hitrecord.dpdv) > 0.1, "{normal:?}");
+        assert!(dot(&normal, &hitrecord.dpdu).abs() < 1e-9, "{normal:?}");
+    }
+}
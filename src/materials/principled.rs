@@ -0,0 +1,183 @@
+use std::{f64::consts::FRAC_1_PI, sync::Arc};
+
+use crate::{
+    hits::hittable::HitRecord,
+    textures::{scalar::ConstantScalar, solid_color::SolidColor, ScalarTexture, Texture},
+    vec3::{dot, reflect, sample_cosine_hemisphere, unit_vector, Color, Point3, Vec3},
+};
+
+use super::{
+    bsdf::{abs_cos_theta, same_hemisphere, schlick_fresnel, BsdfFlags, BsdfSample},
+    microfacet::TrowbridgeReitz,
+    Material,
+};
+
+// Reflectance of dielectrics at normal incidence, as for an index of 1.5.
+const DIELECTRIC_F0: f64 = 0.04;
+
+// The metallic-roughness model of glTF and most real-time engines: a diffuse
+// base under a dielectric coat that blends into a conductor tinted by the
+// base color as `metallic` goes to one. Roughness is perceptual, the
+// microfacet alpha is its square.
+pub struct Principled {
+    base_color: Arc<dyn Texture>,
+    metallic: Arc<dyn ScalarTexture>,
+    roughness: Arc<dyn ScalarTexture>,
+    emission: Option<Arc<dyn Texture>>,
+}
+
+impl Principled {
+    pub fn new(base_color: Color, metallic: f64, roughness: f64) -> Self {
+        Self::new_from_texture(
+            Arc::new(SolidColor::new_from_color(base_color)),
+            Arc::new(ConstantScalar::new(metallic)),
+            Arc::new(ConstantScalar::new(roughness)),
+        )
+    }
+
+    pub fn new_from_texture(
+        base_color: Arc<dyn Texture>,
+        metallic: Arc<dyn ScalarTexture>,
+        roughness: Arc<dyn ScalarTexture>,
+    ) -> Self {
+        Self {
+            base_color,
+            metallic,
+            roughness,
+            emission: None,
+        }
+    }
+
+    // A surface that also glows with `emission` and is sampled as a light.
+    pub fn new_with_emission(
+        base_color: Arc<dyn Texture>,
+        metallic: Arc<dyn ScalarTexture>,
+        roughness: Arc<dyn ScalarTexture>,
+        emission: Arc<dyn Texture>,
+    ) -> Self {
+        Self {
+            emission: Some(emission),
+            ..Self::new_from_texture(base_color, metallic, roughness)
+        }
+    }
+
+    // The base color, metallic and microfacet distribution at the hit.
+    fn parameters(&self, hitrecord: &HitRecord) -> (Color, f64, TrowbridgeReitz) {
+        let (uv, p, n) = (
+            hitrecord.surface_coordinates,
+            &hitrecord.p,
+            &hitrecord.normal,
+        );
+        let base_color = self.base_color.value_with_normal(uv, p, n);
+        let metallic = self.metallic.value_with_normal(uv, p, n).clamp(0.0, 1.0);
+        let roughness = self.roughness.value_with_normal(uv, p, n).clamp(0.0, 1.0);
+        // Kept just rough enough to stay glossy so the diffuse and specular
+        // lobes can be mixed.
+        let alpha = (roughness * roughness).max(2e-3);
+        (base_color, metallic, TrowbridgeReitz::new(alpha))
+    }
+
+    // How often the specular lobe is sampled rather than the diffuse one.
+    fn specular_probability(metallic: f64) -> f64 {
+        0.5 + 0.5 * metallic
+    }
+}
+
+impl Material for Principled {
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        if !same_hemisphere(wo, wi) {
+            return Color::default();
+        }
+        let cos_theta_o = abs_cos_theta(wo);
+        let cos_theta_i = abs_cos_theta(wi);
+        let wm = *wi + *wo;
+        if cos_theta_o == 0.0 || cos_theta_i == 0.0 || wm.len_squared() == 0.0 {
+            return Color::default();
+        }
+        let wm = unit_vector(wm);
+        let cos_theta_h = dot(wo, &wm).abs();
+
+        let (base_color, metallic, distribution) = self.parameters(hitrecord);
+        let f0 = (1.0 - metallic) * Color::new(DIELECTRIC_F0, DIELECTRIC_F0, DIELECTRIC_F0)
+            + metallic * base_color;
+        let specular =
+            distribution.d(&wm) * distribution.g(wo, wi) * schlick_fresnel(f0, cos_theta_h)
+                / (4.0 * cos_theta_o * cos_theta_i);
+
+        // The diffuse base sees what the dielectric coat lets through on the
+        // way in and out.
+        let coat = |cos: f64| DIELECTRIC_F0 + (1.0 - DIELECTRIC_F0) * (1.0 - cos).powi(5);
+        let diffuse = (1.0 - metallic)
+            * (1.0 - coat(cos_theta_o))
+            * (1.0 - coat(cos_theta_i))
+            * FRAC_1_PI
+            * base_color;
+
+        specular + diffuse
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        if wo.z() == 0.0 {
+            return None;
+        }
+
+        let (_, metallic, distribution) = self.parameters(hitrecord);
+        let specular_probability = Self::specular_probability(metallic);
+        let (wi, flags) = if u.0 < specular_probability {
+            let u = (u.0 / specular_probability, u.1);
+            let wm = distribution.sample_wm(wo, u);
+            (
+                reflect(&-*wo, &wm),
+                BsdfFlags::GLOSSY | BsdfFlags::REFLECTION,
+            )
+        } else {
+            let u = (
+                (u.0 - specular_probability) / (1.0 - specular_probability),
+                u.1,
+            );
+            let mut wi = sample_cosine_hemisphere(u);
+            if wo.z() < 0.0 {
+                wi[2] *= -1.0;
+            }
+            (wi, BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION)
+        };
+        if !same_hemisphere(wo, &wi) {
+            return None;
+        }
+
+        let pdf = self.pdf(hitrecord, wo, &wi);
+        if pdf == 0.0 {
+            return None;
+        }
+        Some((wi, self.eval(hitrecord, wo, &wi), pdf, flags))
+    }
+
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        if !same_hemisphere(wo, wi) {
+            return 0.0;
+        }
+        let wm = *wo + *wi;
+        if wm.len_squared() == 0.0 {
+            return 0.0;
+        }
+        let mut wm = unit_vector(wm);
+        if wm.z() < 0.0 {
+            wm = -wm;
+        }
+
+        let (_, metallic, distribution) = self.parameters(hitrecord);
+        let specular_probability = Self::specular_probability(metallic);
+        specular_probability * distribution.pdf(wo, &wm) / (4.0 * dot(wo, &wm).abs())
+            + (1.0 - specular_probability) * abs_cos_theta(wi) * FRAC_1_PI
+    }
+
+    fn emitted(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.emission
+            .as_ref()
+            .map_or(Color::default(), |emission| emission.value(uv, p))
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.emission.is_some()
+    }
+}
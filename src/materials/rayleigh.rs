@@ -0,0 +1,65 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hits::hittable::HitRecord,
+    textures::{solid_color::SolidColor, Texture},
+    vec3::{dot, Color, Vec3},
+};
+
+use super::{
+    bsdf::{direction_around, BsdfFlags, BsdfSample},
+    Material,
+};
+
+// Scattering by particles much smaller than the wavelength, e.g. air
+// molecules. Symmetric between forward and backward directions.
+pub struct Rayleigh {
+    albedo: Arc<dyn Texture>,
+}
+
+impl Rayleigh {
+    pub fn new(albedo: Arc<dyn Texture>) -> Self {
+        Self { albedo }
+    }
+
+    pub fn new_from_color(c: Color) -> Self {
+        Self::new(Arc::new(SolidColor::new_from_color(c)))
+    }
+}
+
+impl Material for Rayleigh {
+    fn eval(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        let albedo = self
+            .albedo
+            .value(hitrecord.surface_coordinates, &hitrecord.p);
+        rayleigh(dot(&-*wo, wi)) * albedo
+    }
+
+    fn sample(&self, hitrecord: &HitRecord, wo: &Vec3, u: (f64, f64)) -> Option<BsdfSample> {
+        // Inverts the CDF (cos^3 + 3 cos + 4) / 8 with Cardano's formula.
+        let q = 4.0 - 8.0 * u.0;
+        let root = (q * q / 4.0 + 1.0).sqrt();
+        let cos_theta = ((-q / 2.0 + root).cbrt() + (-q / 2.0 - root).cbrt()).clamp(-1.0, 1.0);
+
+        let wi = direction_around(&-*wo, cos_theta, 2.0 * PI * u.1);
+        Some((
+            wi,
+            self.eval(hitrecord, wo, &wi),
+            self.pdf(hitrecord, wo, &wi),
+            BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION,
+        ))
+    }
+
+    #[allow(unused_variables)]
+    fn pdf(&self, hitrecord: &HitRecord, wo: &Vec3, wi: &Vec3) -> f64 {
+        rayleigh(dot(&-*wo, wi))
+    }
+
+    fn is_phase_function(&self) -> bool {
+        true
+    }
+}
+
+pub fn rayleigh(cos_theta: f64) -> f64 {
+    3.0 / (16.0 * PI) * (1.0 + cos_theta * cos_theta)
+}
@@ -0,0 +1,50 @@
+pub mod fractal;
+pub mod gradient;
+pub mod simplex;
+pub mod value;
+pub mod warp;
+pub mod worley;
+
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::vec3::Point3;
+
+// Scalar noise over 3D space. Lattice noises return values in roughly [-1, 1].
+pub trait Noise: Send + Sync {
+    fn noise(&self, p: &Point3) -> f64;
+}
+
+#[derive(Clone)]
+pub struct PermutationTable {
+    perm: Vec<usize>,
+}
+
+impl PermutationTable {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut perm: Vec<usize> = (0..256).collect();
+        perm.shuffle(&mut rng);
+        perm.extend_from_within(..);
+
+        Self { perm }
+    }
+
+    pub fn hash(&self, i: i64, j: i64, k: i64) -> usize {
+        let a = self.perm[(i & 255) as usize];
+        let b = self.perm[a + (j & 255) as usize];
+        self.perm[b + (k & 255) as usize]
+    }
+
+    pub fn random_values(seed: u64) -> Vec<f64> {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        (0..256).map(|_| rng.gen_range(-1.0..1.0)).collect()
+    }
+}
+
+pub fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+pub fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use crate::vec3::Point3;
+
+use super::Noise;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FractalKind {
+    // Plain sum of octaves.
+    Fbm,
+    // Sharp creases where the source crosses zero, as in mountain ridges.
+    Ridged,
+    // Rounded puffs from the absolute value of each octave.
+    Billow,
+}
+
+// Sums `octaves` copies of a source noise, each `lacunarity` times higher in
+// frequency and `gain` times lower in amplitude than the previous one.
+pub struct Fractal {
+    source: Arc<dyn Noise>,
+    kind: FractalKind,
+    octaves: u32,
+    lacunarity: f64,
+    gain: f64,
+}
+
+impl Fractal {
+    pub fn new(
+        source: Arc<dyn Noise>,
+        kind: FractalKind,
+        octaves: u32,
+        lacunarity: f64,
+        gain: f64,
+    ) -> Self {
+        Self {
+            source,
+            kind,
+            octaves,
+            lacunarity,
+            gain,
+        }
+    }
+
+    pub fn new_fbm(source: Arc<dyn Noise>, octaves: u32) -> Self {
+        Self::new(source, FractalKind::Fbm, octaves, 2.0, 0.5)
+    }
+}
+
+impl Noise for Fractal {
+    fn noise(&self, p: &Point3) -> f64 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut normalization = 0.0;
+        let mut weight = 1.0;
+        let mut q = *p;
+
+        for _ in 0..self.octaves {
+            let n = self.source.noise(&q);
+            let octave = match self.kind {
+                FractalKind::Fbm => n,
+                FractalKind::Billow => 2.0 * n.abs() - 1.0,
+                FractalKind::Ridged => {
+                    let ridge = (1.0 - n.abs()).powi(2) * weight;
+                    weight = ridge.clamp(0.0, 1.0);
+                    ridge
+                }
+            };
+
+            sum += amplitude * octave;
+            normalization += amplitude;
+            amplitude *= self.gain;
+            q *= self.lacunarity;
+        }
+
+        if normalization == 0.0 {
+            return 0.0;
+        }
+        sum / normalization
+    }
+}
@@ -0,0 +1,62 @@
+use crate::vec3::Point3;
+
+use super::{fade, lerp, Noise, PermutationTable};
+
+// Improved Perlin noise with a seedable permutation table.
+pub struct GradientNoise {
+    table: PermutationTable,
+}
+
+impl GradientNoise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            table: PermutationTable::new(seed),
+        }
+    }
+}
+
+impl Noise for GradientNoise {
+    fn noise(&self, p: &Point3) -> f64 {
+        let (fi, fj, fk) = (p.x().floor(), p.y().floor(), p.z().floor());
+        let (x, y, z) = (p.x() - fi, p.y() - fj, p.z() - fk);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+        let (i, j, k) = (fi as i64, fj as i64, fk as i64);
+
+        let g = |di: i64, dj: i64, dk: i64| {
+            grad(
+                self.table.hash(i + di, j + dj, k + dk),
+                x - di as f64,
+                y - dj as f64,
+                z - dk as f64,
+            )
+        };
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, g(0, 0, 0), g(1, 0, 0)),
+                lerp(u, g(0, 1, 0), g(1, 1, 0)),
+            ),
+            lerp(
+                v,
+                lerp(u, g(0, 0, 1), g(1, 0, 1)),
+                lerp(u, g(0, 1, 1), g(1, 1, 1)),
+            ),
+        )
+    }
+}
+
+pub(crate) fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
@@ -0,0 +1,91 @@
+use crate::vec3::Point3;
+
+use super::{gradient::grad, Noise, PermutationTable};
+
+const F3: f64 = 1.0 / 3.0;
+const G3: f64 = 1.0 / 6.0;
+
+pub struct SimplexNoise {
+    table: PermutationTable,
+}
+
+impl SimplexNoise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            table: PermutationTable::new(seed),
+        }
+    }
+
+    fn corner(&self, hash: usize, x: f64, y: f64, z: f64) -> f64 {
+        let t = 0.6 - x * x - y * y - z * z;
+        if t < 0.0 {
+            return 0.0;
+        }
+        let t2 = t * t;
+        t2 * t2 * grad(hash, x, y, z)
+    }
+}
+
+impl Noise for SimplexNoise {
+    fn noise(&self, p: &Point3) -> f64 {
+        let (x, y, z) = (p.x(), p.y(), p.z());
+
+        // Skew into the simplex grid to find the containing cell.
+        let s = (x + y + z) * F3;
+        let (i, j, k) = ((x + s).floor(), (y + s).floor(), (z + s).floor());
+        let t = (i + j + k) * G3;
+        let (x0, y0, z0) = (x - (i - t), y - (j - t), z - (k - t));
+
+        let (i1, j1, k1, i2, j2, k2): (i32, i32, i32, i32, i32, i32) = if x0 >= y0 {
+            if y0 >= z0 {
+                (1, 0, 0, 1, 1, 0)
+            } else if x0 >= z0 {
+                (1, 0, 0, 1, 0, 1)
+            } else {
+                (0, 0, 1, 1, 0, 1)
+            }
+        } else if y0 < z0 {
+            (0, 0, 1, 0, 1, 1)
+        } else if x0 < z0 {
+            (0, 1, 0, 0, 1, 1)
+        } else {
+            (0, 1, 0, 1, 1, 0)
+        };
+
+        let (x1, y1, z1) = (
+            x0 - f64::from(i1) + G3,
+            y0 - f64::from(j1) + G3,
+            z0 - f64::from(k1) + G3,
+        );
+        let (x2, y2, z2) = (
+            x0 - f64::from(i2) + 2.0 * G3,
+            y0 - f64::from(j2) + 2.0 * G3,
+            z0 - f64::from(k2) + 2.0 * G3,
+        );
+        let (x3, y3, z3) = (
+            x0 - 1.0 + 3.0 * G3,
+            y0 - 1.0 + 3.0 * G3,
+            z0 - 1.0 + 3.0 * G3,
+        );
+
+        let (i, j, k) = (i as i64, j as i64, k as i64);
+        let n0 = self.corner(self.table.hash(i, j, k), x0, y0, z0);
+        let n1 = self.corner(
+            self.table
+                .hash(i + i64::from(i1), j + i64::from(j1), k + i64::from(k1)),
+            x1,
+            y1,
+            z1,
+        );
+        let n2 = self.corner(
+            self.table
+                .hash(i + i64::from(i2), j + i64::from(j2), k + i64::from(k2)),
+            x2,
+            y2,
+            z2,
+        );
+        let n3 = self.corner(self.table.hash(i + 1, j + 1, k + 1), x3, y3, z3);
+
+        32.0 * (n0 + n1 + n2 + n3)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::vec3::Point3;
+
+use super::{fade, lerp, Noise, PermutationTable};
+
+// Interpolates random values stored at the integer lattice points.
+pub struct ValueNoise {
+    table: PermutationTable,
+    values: Vec<f64>,
+}
+
+impl ValueNoise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            table: PermutationTable::new(seed),
+            values: PermutationTable::random_values(seed.wrapping_add(1)),
+        }
+    }
+
+    fn lattice(&self, i: i64, j: i64, k: i64) -> f64 {
+        self.values[self.table.hash(i, j, k)]
+    }
+}
+
+impl Noise for ValueNoise {
+    fn noise(&self, p: &Point3) -> f64 {
+        let (i, j, k) = (p.x().floor(), p.y().floor(), p.z().floor());
+        let (u, v, w) = (fade(p.x() - i), fade(p.y() - j), fade(p.z() - k));
+        let (i, j, k) = (i as i64, j as i64, k as i64);
+
+        let x00 = lerp(u, self.lattice(i, j, k), self.lattice(i + 1, j, k));
+        let x10 = lerp(u, self.lattice(i, j + 1, k), self.lattice(i + 1, j + 1, k));
+        let x01 = lerp(u, self.lattice(i, j, k + 1), self.lattice(i + 1, j, k + 1));
+        let x11 = lerp(
+            u,
+            self.lattice(i, j + 1, k + 1),
+            self.lattice(i + 1, j + 1, k + 1),
+        );
+
+        lerp(w, lerp(v, x00, x10), lerp(v, x01, x11))
+    }
+}
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use crate::vec3::{Point3, Vec3};
+
+use super::Noise;
+
+// Looks up `source` at a position displaced by three decorrelated samples of
+// the `warp` noise.
+pub struct DomainWarp {
+    source: Arc<dyn Noise>,
+    warp: Arc<dyn Noise>,
+    strength: f64,
+}
+
+impl DomainWarp {
+    pub fn new(source: Arc<dyn Noise>, warp: Arc<dyn Noise>, strength: f64) -> Self {
+        Self {
+            source,
+            warp,
+            strength,
+        }
+    }
+}
+
+impl Noise for DomainWarp {
+    fn noise(&self, p: &Point3) -> f64 {
+        let offset = Vec3::new(
+            self.warp.noise(p),
+            self.warp.noise(&(*p + Vec3::new(5.2, 1.3, 7.1))),
+            self.warp.noise(&(*p + Vec3::new(1.7, 9.2, 3.4))),
+        );
+        self.source.noise(&(*p + self.strength * offset))
+    }
+}
@@ -0,0 +1,80 @@
+use crate::vec3::{Point3, Vec3};
+
+use super::{Noise, PermutationTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorleyMode {
+    // Distance to the closest feature point.
+    F1,
+    // Distance to the second closest feature point.
+    F2,
+    // Difference of the two, which outlines the cells.
+    F2MinusF1,
+}
+
+// Cellular noise with one jittered feature point per unit cell.
+pub struct WorleyNoise {
+    table: PermutationTable,
+    offsets: Vec<Vec3>,
+    mode: WorleyMode,
+    jitter: f64,
+}
+
+impl WorleyNoise {
+    pub fn new(seed: u64, mode: WorleyMode, jitter: f64) -> Self {
+        let xs = PermutationTable::random_values(seed.wrapping_add(1));
+        let ys = PermutationTable::random_values(seed.wrapping_add(2));
+        let zs = PermutationTable::random_values(seed.wrapping_add(3));
+        let offsets = (0..256)
+            .map(|n| 0.5 * Vec3::new(xs[n] + 1.0, ys[n] + 1.0, zs[n] + 1.0))
+            .collect();
+
+        Self {
+            table: PermutationTable::new(seed),
+            offsets,
+            mode,
+            jitter: jitter.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn distances(&self, p: &Point3) -> (f64, f64) {
+        let (i, j, k) = (
+            p.x().floor() as i64,
+            p.y().floor() as i64,
+            p.z().floor() as i64,
+        );
+        let (mut f1, mut f2) = (f64::INFINITY, f64::INFINITY);
+
+        for di in -1..=1 {
+            for dj in -1..=1 {
+                for dk in -1..=1 {
+                    let (ci, cj, ck) = (i + di, j + dj, k + dk);
+                    let jitter = self.jitter * self.offsets[self.table.hash(ci, cj, ck)]
+                        + (0.5 - 0.5 * self.jitter) * Vec3::new(1.0, 1.0, 1.0);
+                    let feature = Point3::new(ci as f64, cj as f64, ck as f64) + jitter;
+                    let distance = (feature - *p).len();
+
+                    if distance < f1 {
+                        f2 = f1;
+                        f1 = distance;
+                    } else if distance < f2 {
+                        f2 = distance;
+                    }
+                }
+            }
+        }
+
+        (f1, f2)
+    }
+}
+
+impl Noise for WorleyNoise {
+    fn noise(&self, p: &Point3) -> f64 {
+        let (f1, f2) = self.distances(p);
+        match self.mode {
+            WorleyMode::F1 => f1,
+            WorleyMode::F2 => f2,
+            WorleyMode::F2MinusF1 => f2 - f1,
+        }
+    }
+}
@@ -1,4 +1,20 @@
 pub mod aa_rect;
 pub mod block;
+pub mod capsule;
+pub mod cone;
+pub mod curve;
+pub mod cylinder;
+pub mod disk;
+pub mod hyperboloid;
+pub mod mesh;
 pub mod moving_sphere;
+mod object_frame;
+pub mod paraboloid;
+pub mod plane;
+pub mod point_cloud;
+mod polynomial;
+pub mod quad;
+pub mod sdf_object;
 pub mod sphere;
+pub mod subdivision;
+pub mod torus;
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::{
     hits::{
@@ -11,14 +11,14 @@ use crate::{
 };
 
 pub struct XYRect {
-    material: Rc<dyn Material>,
+    material: Arc<dyn Material>,
     x_boundaries: (f64, f64),
     y_boundaries: (f64, f64),
     k: f64,
 }
 
 impl XYRect {
-    pub fn new(x: (f64, f64), y: (f64, f64), k: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(x: (f64, f64), y: (f64, f64), k: f64, material: Arc<dyn Material>) -> Self {
         Self {
             material,
             x_boundaries: x,
@@ -65,25 +65,59 @@ impl Hittable for XYRect {
             t,
             p,
             normal,
+            shading_normal: normal,
+            dpdu: Vec3::new(self.x_boundaries.1 - self.x_boundaries.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, self.y_boundaries.1 - self.y_boundaries.0, 0.0),
             front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
             surface_coordinates: uv,
-            material: Rc::clone(&self.material),
+            material: Arc::clone(&self.material),
         };
         result.set_face_normal(r, normal);
 
         Some(result)
     }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let x = self.x_boundaries.0 + u.0 * (self.x_boundaries.1 - self.x_boundaries.0);
+        let y = self.y_boundaries.0 + u.1 * (self.y_boundaries.1 - self.y_boundaries.0);
+        Some(HitRecord {
+            t: 0.0,
+            p: Point3::new(x, y, self.k),
+            normal: Vec3::new(0.0, 0.0, 1.0),
+            shading_normal: Vec3::new(0.0, 0.0, 1.0),
+            dpdu: Vec3::new(self.x_boundaries.1 - self.x_boundaries.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, self.y_boundaries.1 - self.y_boundaries.0, 0.0),
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: u,
+            material: Arc::clone(&self.material),
+        })
+    }
+
+    fn area(&self) -> f64 {
+        (self.x_boundaries.1 - self.x_boundaries.0) * (self.y_boundaries.1 - self.y_boundaries.0)
+    }
 }
 
 pub struct XZRect {
-    material: Rc<dyn Material>,
+    material: Arc<dyn Material>,
     x_boundaries: (f64, f64),
     z_boundaries: (f64, f64),
     k: f64,
 }
 
 impl XZRect {
-    pub fn new(x: (f64, f64), z: (f64, f64), k: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(x: (f64, f64), z: (f64, f64), k: f64, material: Arc<dyn Material>) -> Self {
         Self {
             material,
             x_boundaries: x,
@@ -130,25 +164,59 @@ impl Hittable for XZRect {
             t,
             p,
             normal,
+            shading_normal: normal,
+            dpdu: Vec3::new(self.x_boundaries.1 - self.x_boundaries.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, self.z_boundaries.1 - self.z_boundaries.0),
             front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
             surface_coordinates: uv,
-            material: Rc::clone(&self.material),
+            material: Arc::clone(&self.material),
         };
         result.set_face_normal(r, normal);
 
         Some(result)
     }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let x = self.x_boundaries.0 + u.0 * (self.x_boundaries.1 - self.x_boundaries.0);
+        let z = self.z_boundaries.0 + u.1 * (self.z_boundaries.1 - self.z_boundaries.0);
+        Some(HitRecord {
+            t: 0.0,
+            p: Point3::new(x, self.k, z),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+            shading_normal: Vec3::new(0.0, 1.0, 0.0),
+            dpdu: Vec3::new(self.x_boundaries.1 - self.x_boundaries.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, self.z_boundaries.1 - self.z_boundaries.0),
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: u,
+            material: Arc::clone(&self.material),
+        })
+    }
+
+    fn area(&self) -> f64 {
+        (self.x_boundaries.1 - self.x_boundaries.0) * (self.z_boundaries.1 - self.z_boundaries.0)
+    }
 }
 
 pub struct YZRect {
-    material: Rc<dyn Material>,
+    material: Arc<dyn Material>,
     y_boundaries: (f64, f64),
     z_boundaries: (f64, f64),
     k: f64,
 }
 
 impl YZRect {
-    pub fn new(y: (f64, f64), z: (f64, f64), k: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(y: (f64, f64), z: (f64, f64), k: f64, material: Arc<dyn Material>) -> Self {
         Self {
             material,
             y_boundaries: y,
@@ -195,12 +263,46 @@ impl Hittable for YZRect {
             t,
             p,
             normal,
+            shading_normal: normal,
+            dpdu: Vec3::new(0.0, self.y_boundaries.1 - self.y_boundaries.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, self.z_boundaries.1 - self.z_boundaries.0),
             front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
             surface_coordinates: uv,
-            material: Rc::clone(&self.material),
+            material: Arc::clone(&self.material),
         };
         result.set_face_normal(r, normal);
 
         Some(result)
     }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let y = self.y_boundaries.0 + u.0 * (self.y_boundaries.1 - self.y_boundaries.0);
+        let z = self.z_boundaries.0 + u.1 * (self.z_boundaries.1 - self.z_boundaries.0);
+        Some(HitRecord {
+            t: 0.0,
+            p: Point3::new(self.k, y, z),
+            normal: Vec3::new(1.0, 0.0, 0.0),
+            shading_normal: Vec3::new(1.0, 0.0, 0.0),
+            dpdu: Vec3::new(0.0, self.y_boundaries.1 - self.y_boundaries.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, self.z_boundaries.1 - self.z_boundaries.0),
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: u,
+            material: Arc::clone(&self.material),
+        })
+    }
+
+    fn area(&self) -> f64 {
+        (self.y_boundaries.1 - self.y_boundaries.0) * (self.z_boundaries.1 - self.z_boundaries.0)
+    }
 }
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::{
     hits::{
@@ -8,7 +8,7 @@ use crate::{
     },
     materials::Material,
     ray::Ray,
-    vec3::Point3,
+    vec3::{Point3, Vec3},
 };
 
 use super::aa_rect::{XYRect, XZRect, YZRect};
@@ -20,7 +20,7 @@ pub struct Block {
 }
 
 impl Block {
-    pub fn new(p0: Point3, p1: Point3, material: Rc<dyn Material>) -> Self {
+    pub fn new(p0: Point3, p1: Point3, material: Arc<dyn Material>) -> Self {
         let mut new = Self {
             block_min: p0,
             block_max: p1,
@@ -68,6 +68,18 @@ impl Block {
 
         new
     }
+
+    // The normal of the side through `p` turned away from the block.
+    fn outward(&self, p: &Point3, normal: Vec3) -> Option<Vec3> {
+        let mut outward = normal;
+        let axis = (0..3).find(|a| outward[*a] != 0.0)?;
+        if (p[axis] - self.block_min[axis]).abs() < (p[axis] - self.block_max[axis]).abs() {
+            outward[axis] = -outward[axis].abs();
+        } else {
+            outward[axis] = outward[axis].abs();
+        }
+        Some(outward)
+    }
 }
 
 impl Hittable for Block {
@@ -77,6 +89,34 @@ impl Hittable for Block {
     }
 
     fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
-        self.sides.hit(r, interval)
+        let mut hitrecord = self.sides.hit(r, interval)?;
+
+        // The sides all face along the positive axes, those on the minimum
+        // side are turned outward so that `front_face` tells entering from
+        // leaving.
+        let normal = if hitrecord.front_face {
+            hitrecord.normal
+        } else {
+            -hitrecord.normal
+        };
+        let outward = self.outward(&hitrecord.p, normal)?;
+        hitrecord.set_face_normal(r, outward);
+
+        Some(hitrecord)
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        self.sides.lights(lights);
+    }
+
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let mut hitrecord = self.sides.sample_surface(u, time)?;
+        hitrecord.normal = self.outward(&hitrecord.p, hitrecord.normal)?;
+        hitrecord.shading_normal = hitrecord.normal;
+        Some(hitrecord)
+    }
+
+    fn area(&self) -> f64 {
+        self.sides.area()
     }
 }
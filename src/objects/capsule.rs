@@ -0,0 +1,166 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    ray::Ray,
+    vec3::{dot, sample_uniform_sphere, Point3, Vec3},
+};
+
+use super::{
+    object_frame::{azimuth, nearest_root, ObjectFrame},
+    polynomial::solve_quadratic,
+};
+
+// A cylinder from `base` along `axis` closed by hemispheres at both ends.
+// The surface coordinates are the angle around the axis and the distance
+// along the surface from the bottom pole, scaled to [0, 1].
+pub struct Capsule {
+    frame: ObjectFrame,
+    radius: f64,
+    height: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Capsule {
+    pub fn new(base: Point3, axis: Vec3, radius: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            frame: ObjectFrame::new(base, &axis),
+            radius,
+            height: axis.len(),
+            material,
+        }
+    }
+
+    fn meridian_length(&self) -> f64 {
+        PI * self.radius + self.height
+    }
+
+    fn side_area(&self) -> f64 {
+        2.0 * PI * self.radius * self.height
+    }
+
+    // The local normal, tangents and surface coordinates at `p`.
+    fn surface(&self, p: &Point3) -> ((Vec3, Vec3, Vec3), (f64, f64)) {
+        // Distance along the meridian from the bottom pole, and the unit
+        // direction in which it grows.
+        let quarter = PI * self.radius / 2.0;
+        let (normal, arc, meridian) = if p.z() < 0.0 || p.z() > self.height {
+            let z = if p.z() < 0.0 { 0.0 } else { self.height };
+            let n = (*p - Vec3::new(0.0, 0.0, z)) / self.radius;
+            let rho = (n.x() * n.x() + n.y() * n.y()).sqrt();
+            let elevation = n.z().atan2(rho);
+            let arc = if z == 0.0 {
+                quarter + self.radius * elevation
+            } else {
+                quarter + self.height + self.radius * elevation
+            };
+            let meridian = if rho > 0.0 {
+                Vec3::new(-n.x() * n.z() / rho, -n.y() * n.z() / rho, rho)
+            } else {
+                Vec3::new(1.0, 0.0, 0.0)
+            };
+            (n, arc, meridian)
+        } else {
+            (
+                Vec3::new(p.x(), p.y(), 0.0),
+                quarter + p.z(),
+                Vec3::new(0.0, 0.0, 1.0),
+            )
+        };
+
+        let length = self.meridian_length();
+        (
+            (
+                normal,
+                2.0 * PI * Vec3::new(-p.y(), p.x(), 0.0),
+                length * meridian,
+            ),
+            (azimuth(p) / (2.0 * PI), arc / length),
+        )
+    }
+}
+
+impl Hittable for Capsule {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        Some(self.frame.bounding_box(
+            Point3::new(-self.radius, -self.radius, -self.radius),
+            Point3::new(self.radius, self.radius, self.height + self.radius),
+        ))
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let (o, d) = self.frame.to_local(r);
+        let r2 = self.radius * self.radius;
+
+        let a = d.x() * d.x() + d.y() * d.y();
+        let b = 2.0 * (o.x() * d.x() + o.y() * d.y());
+        let c = o.x() * o.x() + o.y() * o.y() - r2;
+        let mut nearest = solve_quadratic(a, b, c).and_then(|(t0, t1)| {
+            nearest_root(&[t0, t1], interval, |t| {
+                (0.0..=self.height).contains(&(o.z() + t * d.z()))
+            })
+        });
+
+        // Each hemisphere only counts beyond its end of the cylinder.
+        for (z, below) in [(0.0, true), (self.height, false)] {
+            let oc = o - Vec3::new(0.0, 0.0, z);
+            let roots = solve_quadratic(dot(&d, &d), 2.0 * dot(&oc, &d), dot(&oc, &oc) - r2);
+            let t_max = nearest.unwrap_or(interval.1);
+            if let Some((t0, t1)) = roots {
+                let end = nearest_root(&[t0, t1], (interval.0, t_max), |t| {
+                    let pz = o.z() + t * d.z();
+                    if below {
+                        pz <= 0.0
+                    } else {
+                        pz >= self.height
+                    }
+                });
+                if end.is_some() {
+                    nearest = end;
+                }
+            }
+        }
+
+        let t = nearest?;
+        let (local, uv) = self.surface(&(o + t * d));
+        Some(self.frame.hit_record(r, t, local, uv, &self.material))
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        // The first coordinate picks the side or the two hemispheres, which
+        // make up a sphere, by area and is then reused within it.
+        let side = self.side_area();
+        let x = u.0 * self.area();
+        let p = if x < side {
+            let phi = 2.0 * PI * u.1;
+            Point3::new(
+                self.radius * phi.cos(),
+                self.radius * phi.sin(),
+                self.height * x / side,
+            )
+        } else {
+            let v = ((x - side) / (self.area() - side)).min(1.0);
+            let n = sample_uniform_sphere((v, u.1));
+            let z = if n.z() < 0.0 { 0.0 } else { self.height };
+            Point3::new(0.0, 0.0, z) + self.radius * n
+        };
+        let (local, uv) = self.surface(&p);
+        Some(self.frame.surface_record(&p, local, uv, &self.material))
+    }
+
+    fn area(&self) -> f64 {
+        self.side_area() + 4.0 * PI * self.radius * self.radius
+    }
+}
@@ -0,0 +1,159 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    ray::Ray,
+    vec3::{sample_uniform_disk, Point3, Vec3},
+};
+
+use super::{
+    cylinder::{cap_surface, hit_cap},
+    object_frame::{azimuth, nearest_root, ObjectFrame},
+    polynomial::solve_quadratic,
+};
+
+// A cone with its base of `radius` at `base` and its apex at the end of
+// `axis`. The side is parameterized by the angle around the axis and the
+// height, the base cap by the angle and the distance from the axis.
+pub struct Cone {
+    frame: ObjectFrame,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: Arc<dyn Material>,
+}
+
+impl Cone {
+    pub fn new(base: Point3, axis: Vec3, radius: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            frame: ObjectFrame::new(base, &axis),
+            radius,
+            height: axis.len(),
+            capped: true,
+            material,
+        }
+    }
+
+    // A cone open at the base.
+    pub fn new_uncapped(
+        base: Point3,
+        axis: Vec3,
+        radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            capped: false,
+            ..Self::new(base, axis, radius, material)
+        }
+    }
+
+    fn side_area(&self) -> f64 {
+        PI * self.radius * self.radius.hypot(self.height)
+    }
+
+    fn cap_area(&self) -> f64 {
+        if self.capped {
+            PI * self.radius * self.radius
+        } else {
+            0.0
+        }
+    }
+
+    // The local normal, tangents and surface coordinates at `p` on the side.
+    fn side_surface(&self, p: &Point3) -> ((Vec3, Vec3, Vec3), (f64, f64)) {
+        let k = self.radius / self.height;
+        let phi = azimuth(p);
+        let normal = Vec3::new(p.x(), p.y(), k * k * (self.height - p.z()));
+        let dpdu = 2.0 * PI * Vec3::new(-p.y(), p.x(), 0.0);
+        let dpdv = Vec3::new(
+            -self.radius * phi.cos(),
+            -self.radius * phi.sin(),
+            self.height,
+        );
+        (
+            (normal, dpdu, dpdv),
+            (phi / (2.0 * PI), p.z() / self.height),
+        )
+    }
+}
+
+impl Hittable for Cone {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        Some(self.frame.bounding_box(
+            Point3::new(-self.radius, -self.radius, 0.0),
+            Point3::new(self.radius, self.radius, self.height),
+        ))
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let (o, d) = self.frame.to_local(r);
+
+        // x² + y² = k² (h - z)²
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let h = self.height - o.z();
+        let a = d.x() * d.x() + d.y() * d.y() - k2 * d.z() * d.z();
+        let b = 2.0 * (o.x() * d.x() + o.y() * d.y() + k2 * h * d.z());
+        let c = o.x() * o.x() + o.y() * o.y() - k2 * h * h;
+        let side = solve_quadratic(a, b, c).and_then(|(t0, t1)| {
+            nearest_root(&[t0, t1], interval, |t| {
+                (0.0..=self.height).contains(&(o.z() + t * d.z()))
+            })
+        });
+
+        let mut nearest = side.map(|t| (t, false));
+        if self.capped {
+            let t_max = nearest.map_or(interval.1, |(t, _)| t);
+            if let Some(t) = hit_cap(&o, &d, 0.0, self.radius, (interval.0, t_max)) {
+                nearest = Some((t, true));
+            }
+        }
+
+        let (t, cap) = nearest?;
+        let p = o + t * d;
+        let (local, uv) = if cap {
+            cap_surface(&p, -1.0, self.radius)
+        } else {
+            self.side_surface(&p)
+        };
+        Some(self.frame.hit_record(r, t, local, uv, &self.material))
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let side = self.side_area();
+        let x = u.0 * self.area();
+        let (p, (local, uv)) = if x < side {
+            // The area up to a distance from the apex grows with its square.
+            let s = (x / side).sqrt();
+            let phi = 2.0 * PI * u.1;
+            let p = Point3::new(
+                s * self.radius * phi.cos(),
+                s * self.radius * phi.sin(),
+                (1.0 - s) * self.height,
+            );
+            (p, self.side_surface(&p))
+        } else {
+            let v = ((x - side) / self.cap_area()).min(1.0);
+            let d = sample_uniform_disk((v, u.1));
+            let p = Point3::new(self.radius * d.x(), self.radius * d.y(), 0.0);
+            (p, cap_surface(&p, -1.0, self.radius))
+        };
+        Some(self.frame.surface_record(&p, local, uv, &self.material))
+    }
+
+    fn area(&self) -> f64 {
+        self.side_area() + self.cap_area()
+    }
+}
@@ -0,0 +1,415 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    onb::Onb,
+    ray::Ray,
+    vec3::{cross, dot, unit_vector, Point3, Vec3},
+};
+
+// How the width of a curve is oriented.
+#[derive(Clone, Copy, Debug)]
+pub enum CurveType {
+    // A flat strip that always faces the ray.
+    Flat,
+    // A flat strip facing the ray, shaded as if it were a tube.
+    Cylinder,
+    // A flat strip with the given normals at its two ends.
+    Ribbon(Vec3, Vec3),
+}
+
+// What the segments of a split curve share.
+struct CurveCommon {
+    control: [Point3; 4],
+    widths: (f64, f64),
+    kind: CurveType,
+    material: Arc<dyn Material>,
+}
+
+// A cubic Bézier curve with a width that changes linearly along it, or the
+// part of one between `u_range.0` and `u_range.1`. Curves can't be sampled
+// as lights, emission on them is only found by hitting them.
+pub struct Curve {
+    common: Arc<CurveCommon>,
+    u_range: (f64, f64),
+}
+
+impl Curve {
+    pub fn new(
+        control: [Point3; 4],
+        widths: (f64, f64),
+        kind: CurveType,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let kind = match kind {
+            CurveType::Ribbon(n0, n1) => CurveType::Ribbon(unit_vector(n0), unit_vector(n1)),
+            kind => kind,
+        };
+        Self {
+            common: Arc::new(CurveCommon {
+                control,
+                widths,
+                kind,
+                material,
+            }),
+            u_range: (0.0, 1.0),
+        }
+    }
+
+    // The curve cut into `segments` pieces of equal parameter length, which
+    // bound it much more tightly in a BVH.
+    pub fn split(self, segments: usize) -> Vec<Curve> {
+        let segments = segments.max(1);
+        let (u0, u1) = self.u_range;
+        (0..segments)
+            .map(|i| Curve {
+                common: Arc::clone(&self.common),
+                u_range: (
+                    lerp(i as f64 / segments as f64, u0, u1),
+                    lerp((i + 1) as f64 / segments as f64, u0, u1),
+                ),
+            })
+            .collect()
+    }
+
+    fn width(&self, u: f64) -> f64 {
+        lerp(u, self.common.widths.0, self.common.widths.1)
+    }
+
+    // The control points of this segment alone.
+    fn control(&self) -> [Point3; 4] {
+        blossom_segment(&self.common.control, self.u_range.0, self.u_range.1)
+    }
+
+    fn max_width(&self) -> f64 {
+        self.width(self.u_range.0).max(self.width(self.u_range.1))
+    }
+
+    // Finds the closest crossing of the ray, which runs along the z axis of
+    // ray space, with the part of the segment from `u.0` to `u.1`. Halves the
+    // curve until the pieces are nearly straight and then tests them as
+    // strips. Returns the distance along the ray, u and v.
+    fn intersect(
+        &self,
+        control: &[Point3; 4],
+        ray_length: f64,
+        direction: &Vec3,
+        z_range: (f64, f64),
+        u: (f64, f64),
+        depth: u32,
+    ) -> Option<(f64, f64, f64)> {
+        let half_width = 0.5 * self.width(u.0).max(self.width(u.1));
+        let mut min = control[0];
+        let mut max = control[0];
+        for p in &control[1..] {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(p[axis]);
+                max[axis] = max[axis].max(p[axis]);
+            }
+        }
+        if max.x() + half_width < 0.0
+            || min.x() - half_width > 0.0
+            || max.y() + half_width < 0.0
+            || min.y() - half_width > 0.0
+            || max.z() + half_width < z_range.0
+            || min.z() - half_width > z_range.1
+        {
+            return None;
+        }
+
+        if depth > 0 {
+            let (left, right) = split_bezier(control);
+            let u_middle = 0.5 * (u.0 + u.1);
+            let near = self.intersect(
+                &left,
+                ray_length,
+                direction,
+                z_range,
+                (u.0, u_middle),
+                depth - 1,
+            );
+            let z_max = near.map_or(z_range.1, |(t, _, _)| t * ray_length);
+            let far = self.intersect(
+                &right,
+                ray_length,
+                direction,
+                (z_range.0, z_max),
+                (u_middle, u.1),
+                depth - 1,
+            );
+            return far.or(near);
+        }
+
+        // The ray must pass between the lines through the end points
+        // perpendicular to the curve there.
+        let edge = (control[1].y() - control[0].y()) * -control[0].y()
+            + control[0].x() * (control[0].x() - control[1].x());
+        if edge < 0.0 {
+            return None;
+        }
+        let edge = (control[2].y() - control[3].y()) * -control[3].y()
+            + control[3].x() * (control[3].x() - control[2].x());
+        if edge < 0.0 {
+            return None;
+        }
+
+        // The closest point of the nearly straight piece to the ray.
+        let segment = (
+            control[3].x() - control[0].x(),
+            control[3].y() - control[0].y(),
+        );
+        let denominator = segment.0 * segment.0 + segment.1 * segment.1;
+        if denominator == 0.0 {
+            return None;
+        }
+        let w = -(control[0].x() * segment.0 + control[0].y() * segment.1) / denominator;
+        let hit_u = lerp(w, u.0, u.1).clamp(u.0, u.1);
+
+        let mut hit_width = self.width(hit_u);
+        if let CurveType::Ribbon(n0, n1) = self.common.kind {
+            // Ribbons look narrower when seen edge on.
+            hit_width *= dot(&slerp(hit_u, &n0, &n1), direction).abs() / ray_length;
+        }
+
+        let (pc, dpcdw) = evaluate_bezier(control, w.clamp(0.0, 1.0));
+        let distance_squared = pc.x() * pc.x() + pc.y() * pc.y();
+        if distance_squared > hit_width * hit_width * 0.25 {
+            return None;
+        }
+        if pc.z() < z_range.0 || pc.z() > z_range.1 {
+            return None;
+        }
+
+        // Which side of the curve the ray passes on.
+        let distance = distance_squared.sqrt();
+        let edge = dpcdw.x() * -pc.y() + pc.x() * dpcdw.y();
+        let v = if edge > 0.0 {
+            0.5 + distance / hit_width
+        } else {
+            0.5 - distance / hit_width
+        };
+        Some((pc.z() / ray_length, hit_u, v))
+    }
+}
+
+impl Hittable for Curve {
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let ray_length = r.direction().len();
+        if ray_length == 0.0 {
+            return None;
+        }
+        let ray_space = Onb::build_from_w(&r.direction());
+        let control = self
+            .control()
+            .map(|p| ray_space.to_local(&(p - r.origin())));
+
+        // Enough halvings that the pieces deviate from straight lines by a
+        // small fraction of the width.
+        let mut l0: f64 = 0.0;
+        for window in control.windows(3) {
+            let second_difference = window[0] - 2.0 * window[1] + window[2];
+            for axis in 0..3 {
+                l0 = l0.max(second_difference[axis].abs());
+            }
+        }
+        let epsilon = 0.05 * self.max_width();
+        let depth = if epsilon > 0.0 && l0 > 0.0 {
+            ((std::f64::consts::SQRT_2 * 6.0 * l0 / (8.0 * epsilon)).log2() / 2.0)
+                .ceil()
+                .clamp(0.0, 10.0) as u32
+        } else {
+            0
+        };
+
+        let (t, u, v) = self.intersect(
+            &control,
+            ray_length,
+            &r.direction(),
+            (interval.0 * ray_length, interval.1 * ray_length),
+            self.u_range,
+            depth,
+        )?;
+
+        let (_, dpdu) = evaluate_bezier(&self.common.control, u);
+        let dpdu = if dpdu.near_zero() {
+            // Coincident end points leave only the chord for a tangent.
+            self.common.control[3] - self.common.control[0]
+        } else {
+            dpdu
+        };
+        let width = self.width(u);
+        let dpdv = match self.common.kind {
+            CurveType::Ribbon(n0, n1) => width * unit_vector(cross(&slerp(u, &n0, &n1), &dpdu)),
+            kind => {
+                // Across the curve as seen along the ray.
+                let dpdu_plane = ray_space.to_local(&dpdu);
+                let mut dpdv_plane =
+                    width * unit_vector(Vec3::new(-dpdu_plane.y(), dpdu_plane.x(), 0.0));
+                if let CurveType::Cylinder = kind {
+                    // Turns the normal across the width as on a tube.
+                    let theta = lerp(v, -90.0, 90.0).to_radians();
+                    dpdv_plane = rotate(&dpdv_plane, &unit_vector(dpdu_plane), -theta);
+                }
+                ray_space.to_world(&dpdv_plane)
+            }
+        };
+
+        let normal = unit_vector(cross(&dpdu, &dpdv));
+        let mut result = HitRecord {
+            t,
+            p: r.at(t),
+            normal,
+            shading_normal: normal,
+            dpdu,
+            dpdv,
+            front_face: true,
+            samplable: false,
+            exterior_ior: 1.0,
+            surface_coordinates: (u, v),
+            material: Arc::clone(&self.common.material),
+        };
+        result.set_face_normal(r, normal);
+        Some(result)
+    }
+
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        let control = self.control();
+        let half_width = 0.5 * self.max_width();
+        let mut min = control[0];
+        let mut max = control[0];
+        for p in &control[1..] {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(p[axis]);
+                max[axis] = max[axis].max(p[axis]);
+            }
+        }
+        let padding =
+            Vec3::new(half_width, half_width, half_width) + Vec3::new(0.0001, 0.0001, 0.0001);
+        Some(AABB::new(min - padding, max + padding))
+    }
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    (1.0 - t) * a + t * b
+}
+
+// Spherical interpolation between unit vectors.
+fn slerp(t: f64, a: &Vec3, b: &Vec3) -> Vec3 {
+    let cos_theta = dot(a, b).clamp(-1.0, 1.0);
+    let theta = cos_theta.acos();
+    if theta < 1e-6 {
+        return *a;
+    }
+    let sin_theta = theta.sin();
+    ((1.0 - t) * theta).sin() / sin_theta * *a + (t * theta).sin() / sin_theta * *b
+}
+
+// `v` rotated by `angle` around the unit vector `axis`.
+fn rotate(v: &Vec3, axis: &Vec3, angle: f64) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    cos * *v + sin * cross(axis, v) + (1.0 - cos) * dot(axis, v) * *axis
+}
+
+// The point and tangent at `u`.
+fn evaluate_bezier(control: &[Point3; 4], u: f64) -> (Point3, Vec3) {
+    let lerp3 = |a: Point3, b: Point3| (1.0 - u) * a + u * b;
+    let c1 = [
+        lerp3(control[0], control[1]),
+        lerp3(control[1], control[2]),
+        lerp3(control[2], control[3]),
+    ];
+    let c2 = [lerp3(c1[0], c1[1]), lerp3(c1[1], c1[2])];
+    (lerp3(c2[0], c2[1]), 3.0 * (c2[1] - c2[0]))
+}
+
+// The two halves of the curve.
+fn split_bezier(control: &[Point3; 4]) -> ([Point3; 4], [Point3; 4]) {
+    let [p0, p1, p2, p3] = *control;
+    let p01 = 0.5 * (p0 + p1);
+    let p12 = 0.5 * (p1 + p2);
+    let p23 = 0.5 * (p2 + p3);
+    let p012 = 0.5 * (p01 + p12);
+    let p123 = 0.5 * (p12 + p23);
+    let middle = 0.5 * (p012 + p123);
+    ([p0, p01, p012, middle], [middle, p123, p23, p3])
+}
+
+// The control points of the part of the curve from `u0` to `u1`.
+fn blossom_segment(control: &[Point3; 4], u0: f64, u1: f64) -> [Point3; 4] {
+    let blossom = |u: [f64; 3]| {
+        let lerp3 = |t: f64, a: Point3, b: Point3| (1.0 - t) * a + t * b;
+        let a = [
+            lerp3(u[0], control[0], control[1]),
+            lerp3(u[0], control[1], control[2]),
+            lerp3(u[0], control[2], control[3]),
+        ];
+        let b = [lerp3(u[1], a[0], a[1]), lerp3(u[1], a[1], a[2])];
+        lerp3(u[2], b[0], b[1])
+    };
+    [
+        blossom([u0, u0, u0]),
+        blossom([u0, u0, u1]),
+        blossom([u0, u1, u1]),
+        blossom([u1, u1, u1]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hits::hittable::Hittable,
+        materials::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    use super::{Curve, CurveType};
+
+    // A straight curve along x hit across its width, and missed just beside
+    // it, whole and split.
+    #[test]
+    fn straight_curve() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let control = [
+            Point3::new(-1.0, 0.0, 0.0),
+            Point3::new(-1.0 / 3.0, 0.0, 0.0),
+            Point3::new(1.0 / 3.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+        ];
+        let curve = Curve::new(control, (0.2, 0.2), CurveType::Cylinder, material);
+        let segments = Curve::new(
+            control,
+            (0.2, 0.2),
+            CurveType::Flat,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        )
+        .split(4);
+
+        let across = Ray::new(Point3::new(0.4, 0.05, 5.0), Vec3::new(0.0, 0.0, -2.0), 0.0);
+        let beside = Ray::new(Point3::new(0.4, 0.15, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+
+        let hit = curve.hit(&across, (0.001, f64::INFINITY)).unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-6);
+        assert!((hit.surface_coordinates.0 - 0.7).abs() < 1e-3);
+        assert!((hit.surface_coordinates.1 - 0.5).abs() > 0.2);
+        assert!(curve.hit(&beside, (0.001, f64::INFINITY)).is_none());
+
+        assert_eq!(
+            segments
+                .iter()
+                .filter(|segment| segment.hit(&across, (0.001, f64::INFINITY)).is_some())
+                .count(),
+            1
+        );
+        assert!(segments
+            .iter()
+            .all(|segment| segment.hit(&beside, (0.001, f64::INFINITY)).is_none()));
+    }
+}
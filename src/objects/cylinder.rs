@@ -0,0 +1,193 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    ray::Ray,
+    vec3::{sample_uniform_disk, Point3, Vec3},
+};
+
+use super::{
+    object_frame::{azimuth, nearest_root, ObjectFrame},
+    polynomial::solve_quadratic,
+};
+
+// A cylinder from `base` along `axis`, as long as the axis. The side is
+// parameterized by the angle around the axis and the height, the caps by the
+// angle and the distance from the axis.
+pub struct Cylinder {
+    frame: ObjectFrame,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: Arc<dyn Material>,
+}
+
+impl Cylinder {
+    pub fn new(base: Point3, axis: Vec3, radius: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            frame: ObjectFrame::new(base, &axis),
+            radius,
+            height: axis.len(),
+            capped: true,
+            material,
+        }
+    }
+
+    // A tube without the caps.
+    pub fn new_uncapped(
+        base: Point3,
+        axis: Vec3,
+        radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            capped: false,
+            ..Self::new(base, axis, radius, material)
+        }
+    }
+
+    fn side_area(&self) -> f64 {
+        2.0 * PI * self.radius * self.height
+    }
+
+    fn cap_area(&self) -> f64 {
+        if self.capped {
+            PI * self.radius * self.radius
+        } else {
+            0.0
+        }
+    }
+
+    // The local normal, tangents and surface coordinates at `p` on the side.
+    fn side_surface(&self, p: &Point3) -> ((Vec3, Vec3, Vec3), (f64, f64)) {
+        (
+            (
+                Vec3::new(p.x(), p.y(), 0.0),
+                2.0 * PI * Vec3::new(-p.y(), p.x(), 0.0),
+                Vec3::new(0.0, 0.0, self.height),
+            ),
+            (azimuth(p) / (2.0 * PI), p.z() / self.height),
+        )
+    }
+}
+
+impl Hittable for Cylinder {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        Some(self.frame.bounding_box(
+            Point3::new(-self.radius, -self.radius, 0.0),
+            Point3::new(self.radius, self.radius, self.height),
+        ))
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let (o, d) = self.frame.to_local(r);
+
+        let a = d.x() * d.x() + d.y() * d.y();
+        let b = 2.0 * (o.x() * d.x() + o.y() * d.y());
+        let c = o.x() * o.x() + o.y() * o.y() - self.radius * self.radius;
+        let side = solve_quadratic(a, b, c).and_then(|(t0, t1)| {
+            nearest_root(&[t0, t1], interval, |t| {
+                (0.0..=self.height).contains(&(o.z() + t * d.z()))
+            })
+        });
+
+        let mut nearest = side.map(|t| (t, None));
+        if self.capped {
+            for (z, sign) in [(0.0, -1.0), (self.height, 1.0)] {
+                let t_max = nearest.map_or(interval.1, |(t, _)| t);
+                if let Some(t) = hit_cap(&o, &d, z, self.radius, (interval.0, t_max)) {
+                    nearest = Some((t, Some(sign)));
+                }
+            }
+        }
+
+        let (t, cap) = nearest?;
+        let p = o + t * d;
+        let (local, uv) = match cap {
+            Some(sign) => cap_surface(&p, sign, self.radius),
+            None => self.side_surface(&p),
+        };
+        Some(self.frame.hit_record(r, t, local, uv, &self.material))
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        // The first coordinate picks the side or a cap by area and is then
+        // reused within it.
+        let side = self.side_area();
+        let cap = self.cap_area();
+        let x = u.0 * self.area();
+        let (p, (local, uv)) = if x < side {
+            let phi = 2.0 * PI * u.1;
+            let p = Point3::new(
+                self.radius * phi.cos(),
+                self.radius * phi.sin(),
+                self.height * x / side,
+            );
+            (p, self.side_surface(&p))
+        } else {
+            let (z, sign, v) = if x < side + cap {
+                (0.0, -1.0, (x - side) / cap)
+            } else {
+                (self.height, 1.0, (x - side - cap) / cap)
+            };
+            let d = sample_uniform_disk((v.min(1.0), u.1));
+            let p = Point3::new(self.radius * d.x(), self.radius * d.y(), z);
+            (p, cap_surface(&p, sign, self.radius))
+        };
+        Some(self.frame.surface_record(&p, local, uv, &self.material))
+    }
+
+    fn area(&self) -> f64 {
+        self.side_area() + 2.0 * self.cap_area()
+    }
+}
+
+// Where the local ray crosses the disk of `radius` at height `z`.
+pub(crate) fn hit_cap(
+    o: &Point3,
+    d: &Vec3,
+    z: f64,
+    radius: f64,
+    interval: (f64, f64),
+) -> Option<f64> {
+    if d.z() == 0.0 {
+        return None;
+    }
+    let t = (z - o.z()) / d.z();
+    if t < interval.0 || t > interval.1 {
+        return None;
+    }
+    let p = *o + t * *d;
+    (p.x() * p.x() + p.y() * p.y() <= radius * radius).then_some(t)
+}
+
+// The local normal, tangents and surface coordinates at `p` on a cap facing
+// along `sign` times the z axis.
+pub(crate) fn cap_surface(p: &Point3, sign: f64, radius: f64) -> ((Vec3, Vec3, Vec3), (f64, f64)) {
+    let distance = (p.x() * p.x() + p.y() * p.y()).sqrt();
+    let radial = if distance > 0.0 {
+        Vec3::new(p.x() / distance, p.y() / distance, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    (
+        (
+            Vec3::new(0.0, 0.0, sign),
+            2.0 * PI * Vec3::new(-p.y(), p.x(), 0.0),
+            radius * radial,
+        ),
+        (azimuth(p) / (2.0 * PI), distance / radius),
+    )
+}
@@ -0,0 +1,133 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    onb::Onb,
+    ray::Ray,
+    vec3::{dot, sample_uniform_disk, Point3, Vec3},
+};
+
+// A disk around `center` facing along `normal`. The surface coordinates are
+// the angle around the center and the distance from it, both scaled to [0, 1].
+pub struct Disk {
+    center: Point3,
+    radius: f64,
+    frame: Onb,
+    material: Arc<dyn Material>,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            center,
+            radius,
+            frame: Onb::build_from_w(&normal),
+            material,
+        }
+    }
+
+    // The surface coordinates and tangents at `p` on the disk.
+    fn surface_at(&self, p: &Point3) -> ((f64, f64), Vec3, Vec3) {
+        let local = self.frame.to_local(&(*p - self.center));
+        let distance = (local.x() * local.x() + local.y() * local.y()).sqrt();
+        let mut phi = local.y().atan2(local.x());
+        if phi < 0.0 {
+            phi += 2.0 * PI;
+        }
+
+        let dpdu = 2.0 * PI * self.frame.to_world(&Vec3::new(-local.y(), local.x(), 0.0));
+        let radial = if distance > 0.0 {
+            Vec3::new(local.x() / distance, local.y() / distance, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let dpdv = self.radius * self.frame.to_world(&radial);
+
+        ((phi / (2.0 * PI), distance / self.radius), dpdu, dpdv)
+    }
+}
+
+impl Hittable for Disk {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        // The disk extends along each axis by the radius times the sine of
+        // the angle between the axis and the normal.
+        let n = self.frame.w();
+        let extent = Vec3::new(
+            self.radius * (1.0 - n.x() * n.x()).max(0.0).sqrt() + 0.0001,
+            self.radius * (1.0 - n.y() * n.y()).max(0.0).sqrt() + 0.0001,
+            self.radius * (1.0 - n.z() * n.z()).max(0.0).sqrt() + 0.0001,
+        );
+        Some(AABB::new(self.center - extent, self.center + extent))
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let normal = self.frame.w();
+        let denominator = dot(&normal, &r.direction());
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = dot(&normal, &(self.center - r.origin())) / denominator;
+        if t < interval.0 || t > interval.1 {
+            return None;
+        }
+
+        let p = r.at(t);
+        if (p - self.center).len_squared() > self.radius * self.radius {
+            return None;
+        }
+
+        let (uv, dpdu, dpdv) = self.surface_at(&p);
+        let mut result = HitRecord {
+            t,
+            p,
+            normal,
+            shading_normal: normal,
+            dpdu,
+            dpdv,
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: uv,
+            material: Arc::clone(&self.material),
+        };
+        result.set_face_normal(r, normal);
+
+        Some(result)
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let d = sample_uniform_disk(u);
+        let p = self.center + self.radius * self.frame.to_world(&d);
+        let (uv, dpdu, dpdv) = self.surface_at(&p);
+        Some(HitRecord {
+            t: 0.0,
+            p,
+            normal: self.frame.w(),
+            shading_normal: self.frame.w(),
+            dpdu,
+            dpdv,
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: uv,
+            material: Arc::clone(&self.material),
+        })
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+}
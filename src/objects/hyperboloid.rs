@@ -0,0 +1,138 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    object_frame::{azimuth, invert_increasing, nearest_root, ObjectFrame},
+    polynomial::solve_quadratic,
+};
+
+// An open hyperboloid of one sheet from `base` along `axis`, `waist_radius`
+// wide halfway and `end_radius` wide at both ends. The surface coordinates
+// are the angle around the axis and the height.
+pub struct Hyperboloid {
+    frame: ObjectFrame,
+    waist_radius: f64,
+    end_radius: f64,
+    height: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Hyperboloid {
+    pub fn new(
+        base: Point3,
+        axis: Vec3,
+        waist_radius: f64,
+        end_radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            frame: ObjectFrame::new(base, &axis),
+            waist_radius,
+            end_radius,
+            height: axis.len(),
+            material,
+        }
+    }
+
+    // x² + y² = a² + k z², with z measured from the waist.
+    fn shape(&self) -> (f64, f64) {
+        let half = self.height / 2.0;
+        let a2 = self.waist_radius * self.waist_radius;
+        (a2, (self.end_radius * self.end_radius - a2) / (half * half))
+    }
+
+    // The area from the waist up to `z`, over 2π: the integral of
+    // sqrt(a² + k (1 + k) z²).
+    fn area_to(&self, z: f64) -> f64 {
+        let (a2, k) = self.shape();
+        let a = a2.sqrt();
+        let c = k * (1.0 + k);
+        if c.abs() < 1e-12 {
+            return a * z;
+        }
+        let root = (a2 + c * z * z).max(0.0).sqrt();
+        let term = if a == 0.0 {
+            0.0
+        } else if c > 0.0 {
+            a2 * (c.sqrt() * z / a).asinh() / c.sqrt()
+        } else {
+            a2 * ((-c).sqrt() * z / a).clamp(-1.0, 1.0).asin() / (-c).sqrt()
+        };
+        0.5 * (z * root + term)
+    }
+
+    // The local normal, tangents and surface coordinates at `p`.
+    fn surface(&self, p: &Point3) -> ((Vec3, Vec3, Vec3), (f64, f64)) {
+        let (_, k) = self.shape();
+        let z = p.z() - self.height / 2.0;
+        let rho2 = (p.x() * p.x() + p.y() * p.y()).max(f64::EPSILON);
+        let normal = Vec3::new(p.x(), p.y(), -k * z);
+        let dpdu = 2.0 * PI * Vec3::new(-p.y(), p.x(), 0.0);
+        let dpdv = self.height * Vec3::new(k * z * p.x() / rho2, k * z * p.y() / rho2, 1.0);
+        (
+            (normal, dpdu, dpdv),
+            (azimuth(p) / (2.0 * PI), p.z() / self.height),
+        )
+    }
+}
+
+impl Hittable for Hyperboloid {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        let extent = self.waist_radius.max(self.end_radius);
+        Some(self.frame.bounding_box(
+            Point3::new(-extent, -extent, 0.0),
+            Point3::new(extent, extent, self.height),
+        ))
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let (o, d) = self.frame.to_local(r);
+
+        let half = self.height / 2.0;
+        let (a2, k) = self.shape();
+        let oz = o.z() - half;
+        let a = d.x() * d.x() + d.y() * d.y() - k * d.z() * d.z();
+        let b = 2.0 * (o.x() * d.x() + o.y() * d.y() - k * oz * d.z());
+        let c = o.x() * o.x() + o.y() * o.y() - k * oz * oz - a2;
+        let (t0, t1) = solve_quadratic(a, b, c)?;
+        let t = nearest_root(&[t0, t1], interval, |t| {
+            (0.0..=self.height).contains(&(o.z() + t * d.z()))
+        })?;
+
+        let (local, uv) = self.surface(&(o + t * d));
+        Some(self.frame.hit_record(r, t, local, uv, &self.material))
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let half = self.height / 2.0;
+        let (a2, k) = self.shape();
+        let target = (2.0 * u.0 - 1.0) * self.area_to(half);
+        let z = invert_increasing(|z| self.area_to(z), target, (-half, half));
+        let rho = (a2 + k * z * z).max(0.0).sqrt();
+        let phi = 2.0 * PI * u.1;
+        let p = Point3::new(rho * phi.cos(), rho * phi.sin(), z + half);
+        let (local, uv) = self.surface(&p);
+        Some(self.frame.surface_record(&p, local, uv, &self.material))
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * PI * self.area_to(self.height / 2.0)
+    }
+}
@@ -0,0 +1,317 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh_tree::bvh_node::BVHNode,
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    ray::Ray,
+    textures::image_texture::{Filter, ImageTexture, WrapMode},
+    vec3::{cross, dot, unit_vector, Color, Point3, Vec3},
+};
+
+use super::subdivision::{ControlMesh, SubdivisionScheme};
+
+// Vertex data shared by the triangles of a mesh. Normals, surface
+// coordinates and colors are per vertex and may be left empty.
+#[derive(Clone, Debug, Default)]
+pub struct TriangleMesh {
+    pub positions: Vec<Point3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<(f64, f64)>,
+    pub colors: Vec<Color>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl TriangleMesh {
+    pub fn new(positions: Vec<Point3>, triangles: Vec<[usize; 3]>) -> Self {
+        Self {
+            positions,
+            triangles,
+            ..Default::default()
+        }
+    }
+
+    // Fills in vertex normals averaged from the faces around each vertex,
+    // weighted by their area.
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![Vec3::default(); self.positions.len()];
+        for [a, b, c] in &self.triangles {
+            let n = cross(
+                &(self.positions[*b] - self.positions[*a]),
+                &(self.positions[*c] - self.positions[*a]),
+            );
+            normals[*a] += n;
+            normals[*b] += n;
+            normals[*c] += n;
+        }
+        self.normals = normals
+            .into_iter()
+            .map(|n| if n.near_zero() { n } else { unit_vector(n) })
+            .collect();
+    }
+
+    // Turns the vertex colors into a texture for any material. Every
+    // triangle gets its own 2x2 texel cell of an atlas, whose bilinear
+    // filtering reproduces the interpolated colors exactly, and vertices are
+    // split so that the surface coordinates can point into it.
+    pub fn bake_colors(&mut self) -> Option<ImageTexture> {
+        if self.colors.len() != self.positions.len() || self.triangles.is_empty() {
+            return None;
+        }
+
+        let columns = (self.triangles.len() as f64).sqrt().ceil() as usize;
+        let rows = self.triangles.len().div_ceil(columns);
+        let (width, height) = (2 * columns, 2 * rows);
+        let mut texels = vec![[0.0; 4]; width * height];
+        let uv = |x: usize, y: usize| {
+            (
+                (x as f64 + 0.5) / width as f64,
+                1.0 - (y as f64 + 0.5) / height as f64,
+            )
+        };
+
+        let mut mesh = TriangleMesh::default();
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            let (x, y) = (2 * (index % columns), 2 * (index / columns));
+            let [c0, c1, c2] = triangle.map(|vertex| self.colors[vertex]);
+            // The fourth texel continues the linear blend past the triangle.
+            let c3 = c1 + c2 - c0;
+            for (color, (tx, ty)) in
+                [c0, c1, c2, c3]
+                    .iter()
+                    .zip([(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)])
+            {
+                texels[ty * width + tx] =
+                    [color.x() as f32, color.y() as f32, color.z() as f32, 1.0];
+            }
+
+            let start = mesh.positions.len();
+            for (&vertex, (tx, ty)) in triangle.iter().zip([(x, y), (x + 1, y), (x, y + 1)]) {
+                mesh.positions.push(self.positions[vertex]);
+                if !self.normals.is_empty() {
+                    mesh.normals.push(self.normals[vertex]);
+                }
+                mesh.uvs.push(uv(tx, ty));
+                mesh.colors.push(self.colors[vertex]);
+            }
+            mesh.triangles.push([start, start + 1, start + 2]);
+        }
+        *self = mesh;
+
+        ImageTexture::new_from_data(texels, width, height, WrapMode::Clamp, Filter::Bilinear).ok()
+    }
+}
+
+// A triangle mesh kept in its own BVH.
+pub struct Mesh {
+    triangles: BVHNode,
+}
+
+impl Mesh {
+    pub fn new(mesh: TriangleMesh, material: Arc<dyn Material>) -> Self {
+        let mesh = Arc::new(mesh);
+        let triangles: Vec<Box<dyn Hittable>> = (0..mesh.triangles.len())
+            .map(|index| {
+                Box::new(Triangle {
+                    mesh: Arc::clone(&mesh),
+                    index,
+                    material: Arc::clone(&material),
+                }) as Box<dyn Hittable>
+            })
+            .collect();
+
+        Self {
+            triangles: BVHNode::new(triangles, (0.0, 1.0)),
+        }
+    }
+
+    // Tessellates `control` with `levels` steps of `scheme`, with smooth
+    // normals.
+    pub fn new_subdivided(
+        control: &ControlMesh,
+        scheme: SubdivisionScheme,
+        levels: u32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let mut mesh = control.subdivide(scheme, levels).to_triangle_mesh();
+        mesh.compute_normals();
+        Self::new(mesh, material)
+    }
+}
+
+impl Hittable for Mesh {
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        self.triangles.hit(r, interval)
+    }
+
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        self.triangles.bounding_box(time)
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        self.triangles.lights(lights);
+    }
+
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        self.triangles.sample_surface(u, time)
+    }
+
+    fn area(&self) -> f64 {
+        self.triangles.area()
+    }
+}
+
+pub struct Triangle {
+    mesh: Arc<TriangleMesh>,
+    index: usize,
+    material: Arc<dyn Material>,
+}
+
+impl Triangle {
+    fn corners(&self) -> [Point3; 3] {
+        self.mesh.triangles[self.index].map(|vertex| self.mesh.positions[vertex])
+    }
+
+    // The surface at barycentric coordinates `b1` and `b2`, facing the way
+    // the corners wind.
+    fn record(&self, b1: f64, b2: f64) -> HitRecord {
+        let [a, b, c] = self.mesh.triangles[self.index];
+        let [p0, p1, p2] = self.corners();
+        let b0 = 1.0 - b1 - b2;
+        let e1 = p1 - p0;
+        let e2 = p2 - p0;
+        let geometric = unit_vector(cross(&e1, &e2));
+
+        // Interpolated normals stand in for the smooth surface the triangles
+        // approximate when shading, turned to the side the triangle faces.
+        let shading_normal = if self.mesh.normals.is_empty() {
+            geometric
+        } else {
+            let n =
+                b0 * self.mesh.normals[a] + b1 * self.mesh.normals[b] + b2 * self.mesh.normals[c];
+            if n.near_zero() {
+                geometric
+            } else if dot(&n, &geometric) < 0.0 {
+                -unit_vector(n)
+            } else {
+                unit_vector(n)
+            }
+        };
+
+        let [uv0, uv1, uv2] = if self.mesh.uvs.is_empty() {
+            [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]
+        } else {
+            [self.mesh.uvs[a], self.mesh.uvs[b], self.mesh.uvs[c]]
+        };
+        let uv = (
+            b0 * uv0.0 + b1 * uv1.0 + b2 * uv2.0,
+            b0 * uv0.1 + b1 * uv1.1 + b2 * uv2.1,
+        );
+
+        // Tangents from how the surface coordinates change along the edges.
+        let (du1, dv1) = (uv1.0 - uv0.0, uv1.1 - uv0.1);
+        let (du2, dv2) = (uv2.0 - uv0.0, uv2.1 - uv0.1);
+        let determinant = du1 * dv2 - dv1 * du2;
+        let (dpdu, dpdv) = if determinant.abs() < 1e-12 {
+            (e1, e2)
+        } else {
+            (
+                (dv2 * e1 - dv1 * e2) / determinant,
+                (du1 * e2 - du2 * e1) / determinant,
+            )
+        };
+
+        HitRecord {
+            t: 0.0,
+            p: b0 * p0 + b1 * p1 + b2 * p2,
+            normal: geometric,
+            shading_normal,
+            dpdu,
+            dpdv,
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: uv,
+            material: Arc::clone(&self.material),
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        let [p0, p1, p2] = self.corners();
+        let mut min = p0;
+        let mut max = p0;
+        for p in [p1, p2] {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(p[axis]);
+                max[axis] = max[axis].max(p[axis]);
+            }
+        }
+        let padding = Vec3::new(0.0001, 0.0001, 0.0001);
+        Some(AABB::new(min - padding, max + padding))
+    }
+
+    // Möller-Trumbore.
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let [p0, p1, p2] = self.corners();
+        let e1 = p1 - p0;
+        let e2 = p2 - p0;
+        let pvec = cross(&r.direction(), &e2);
+        let determinant = dot(&e1, &pvec);
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+
+        let tvec = r.origin() - p0;
+        let b1 = dot(&tvec, &pvec) * inverse;
+        if !(0.0..=1.0).contains(&b1) {
+            return None;
+        }
+        let qvec = cross(&tvec, &e1);
+        let b2 = dot(&r.direction(), &qvec) * inverse;
+        if b2 < 0.0 || b1 + b2 > 1.0 {
+            return None;
+        }
+        let t = dot(&e2, &qvec) * inverse;
+        if t < interval.0 || t > interval.1 {
+            return None;
+        }
+
+        let mut result = self.record(b1, b2);
+        result.t = t;
+        result.p = r.at(t);
+
+        let outward = result.normal;
+        result.set_face_normal(r, outward);
+
+        Some(result)
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        // Folds the unit square onto the triangle.
+        let (b1, b2) = if u.0 + u.1 > 1.0 {
+            (1.0 - u.0, 1.0 - u.1)
+        } else {
+            u
+        };
+        Some(self.record(b1, b2))
+    }
+
+    fn area(&self) -> f64 {
+        let [p0, p1, p2] = self.corners();
+        0.5 * cross(&(p1 - p0), &(p2 - p0)).len()
+    }
+}
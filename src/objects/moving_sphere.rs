@@ -1,4 +1,4 @@
-use std::{f64::consts::PI, rc::Rc};
+use std::{f64::consts::PI, sync::Arc};
 
 use crate::{
     hits::{
@@ -7,15 +7,17 @@ use crate::{
     },
     materials::Material,
     ray::Ray,
-    vec3::{dot, Point3, Vec3},
+    vec3::{dot, sample_uniform_sphere, Point3, Vec3},
 };
 
+use super::sphere::get_sphere_tangents;
+
 #[derive(Clone)]
 pub struct MovingSphere {
     centers: (Point3, Point3),
     time_frame: (f64, f64),
     radius: f64,
-    material: Rc<dyn Material>,
+    material: Arc<dyn Material>,
 }
 
 impl MovingSphere {
@@ -23,7 +25,7 @@ impl MovingSphere {
         centers: (Point3, Point3),
         time_frame: (f64, f64),
         radius: f64,
-        material: Rc<dyn Material>,
+        material: Arc<dyn Material>,
     ) -> Self {
         Self {
             centers,
@@ -68,14 +70,20 @@ impl Hittable for MovingSphere {
         let p = ray.at(t);
         let normal = (p - self.center(ray.time())) / self.radius;
         let uv = get_sphere_uv(&normal);
+        let (dpdu, dpdv) = get_sphere_tangents(&normal, self.radius);
 
         let mut result = HitRecord {
             t,
             p,
             normal,
+            shading_normal: normal,
+            dpdu,
+            dpdv,
             front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
             surface_coordinates: uv,
-            material: Rc::clone(&self.material),
+            material: Arc::clone(&self.material),
         };
         result.set_face_normal(ray, normal);
 
@@ -97,6 +105,34 @@ impl Hittable for MovingSphere {
 
         Some(output_box)
     }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let normal = sample_uniform_sphere(u);
+        let (dpdu, dpdv) = get_sphere_tangents(&normal, self.radius);
+        Some(HitRecord {
+            t: 0.0,
+            p: self.center(time) + self.radius * normal,
+            normal,
+            shading_normal: normal,
+            dpdu,
+            dpdv,
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: get_sphere_uv(&normal),
+            material: Arc::clone(&self.material),
+        })
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * PI * self.radius * self.radius
+    }
 }
 
 fn get_sphere_uv(p: &Point3) -> (f64, f64) {
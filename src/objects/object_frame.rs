@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::{aabb::AABB, hittable::HitRecord},
+    materials::Material,
+    onb::Onb,
+    ray::Ray,
+    vec3::{unit_vector, Point3, Vec3},
+};
+
+// Placement of a shape that is defined around the z axis of its own
+// coordinates, with `origin` at the local origin and the z axis along `axis`.
+pub(crate) struct ObjectFrame {
+    origin: Point3,
+    onb: Onb,
+}
+
+impl ObjectFrame {
+    pub(crate) fn new(origin: Point3, axis: &Vec3) -> Self {
+        Self {
+            origin,
+            onb: Onb::build_from_w(axis),
+        }
+    }
+
+    // The origin and direction of `r` in local coordinates. Distances along
+    // the ray are unchanged.
+    pub(crate) fn to_local(&self, r: &Ray) -> (Point3, Vec3) {
+        (
+            self.onb.to_local(&(r.origin() - self.origin)),
+            self.onb.to_local(&r.direction()),
+        )
+    }
+
+    // The world bounds of the local box from `min` to `max`.
+    pub(crate) fn bounding_box(&self, min: Point3, max: Point3) -> AABB {
+        let mut world_min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut world_max = -world_min;
+        for corner in 0..8 {
+            let local = Point3::new(
+                if corner & 1 == 0 { min.x() } else { max.x() },
+                if corner & 2 == 0 { min.y() } else { max.y() },
+                if corner & 4 == 0 { min.z() } else { max.z() },
+            );
+            let world = self.origin + self.onb.to_world(&local);
+            for a in 0..3 {
+                world_min[a] = world_min[a].min(world[a]);
+                world_max[a] = world_max[a].max(world[a]);
+            }
+        }
+
+        let padding = Vec3::new(0.0001, 0.0001, 0.0001);
+        AABB::new(world_min - padding, world_max + padding)
+    }
+
+    // A hit at `t` along `r` from local normal and tangents.
+    pub(crate) fn hit_record(
+        &self,
+        r: &Ray,
+        t: f64,
+        local: (Vec3, Vec3, Vec3),
+        uv: (f64, f64),
+        material: &Arc<dyn Material>,
+    ) -> HitRecord {
+        let mut result = self.surface_record(&Point3::default(), local, uv, material);
+        result.t = t;
+        result.p = r.at(t);
+        let outward = result.normal;
+        result.set_face_normal(r, outward);
+        result
+    }
+
+    // The surface at the local point `p` with its outward normal, as sampled
+    // on lights.
+    pub(crate) fn surface_record(
+        &self,
+        p: &Point3,
+        local: (Vec3, Vec3, Vec3),
+        uv: (f64, f64),
+        material: &Arc<dyn Material>,
+    ) -> HitRecord {
+        let (normal, dpdu, dpdv) = local;
+        let normal = unit_vector(self.onb.to_world(&normal));
+        HitRecord {
+            t: 0.0,
+            p: self.origin + self.onb.to_world(p),
+            normal,
+            shading_normal: normal,
+            dpdu: self.onb.to_world(&dpdu),
+            dpdv: self.onb.to_world(&dpdv),
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: uv,
+            material: Arc::clone(material),
+        }
+    }
+}
+
+// The angle of `p` around the z axis in [0, 2π).
+pub(crate) fn azimuth(p: &Point3) -> f64 {
+    let phi = p.y().atan2(p.x());
+    if phi < 0.0 {
+        phi + 2.0 * std::f64::consts::PI
+    } else {
+        phi
+    }
+}
+
+// The first of `roots` inside `interval` that `accept` takes.
+pub(crate) fn nearest_root(
+    roots: &[f64],
+    interval: (f64, f64),
+    accept: impl Fn(f64) -> bool,
+) -> Option<f64> {
+    roots
+        .iter()
+        .copied()
+        .filter(|t| *t >= interval.0 && *t <= interval.1 && accept(*t))
+        .fold(None, |nearest: Option<f64>, t| {
+            Some(nearest.map_or(t, |nearest| nearest.min(t)))
+        })
+}
+
+// The `x` in `range` where the increasing function `f` reaches `target`, by
+// bisection. Used to invert the area of surfaces of revolution up to a height
+// or angle.
+pub(crate) fn invert_increasing(f: impl Fn(f64) -> f64, target: f64, range: (f64, f64)) -> f64 {
+    let (mut low, mut high) = range;
+    for _ in 0..64 {
+        let middle = 0.5 * (low + high);
+        if f(middle) < target {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    0.5 * (low + high)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hits::hittable::Hittable,
+        materials::{diffuse_light::DiffuseLight, Material},
+        objects::{
+            capsule::Capsule, cone::Cone, cylinder::Cylinder, hyperboloid::Hyperboloid,
+            paraboloid::Paraboloid, torus::Torus,
+        },
+        ray::Ray,
+        vec3::{cross, dot, Color, Point3, Vec3},
+    };
+
+    // Samples that are uniform by area map every small patch of the unit
+    // square onto a patch `area` times as large, and lie on the surface the
+    // rays hit.
+    #[test]
+    fn quadric_samples_are_uniform_by_area() {
+        let light: Arc<dyn Material> = Arc::new(DiffuseLight::new(Color::new(1.0, 1.0, 1.0)));
+        let base = Point3::new(0.3, -0.2, 0.5);
+        let axis = Vec3::new(0.2, 1.5, -0.4);
+        let shapes: Vec<Box<dyn Hittable>> = vec![
+            Box::new(Cylinder::new(base, axis, 0.7, light.clone())),
+            Box::new(Cone::new(base, axis, 0.7, light.clone())),
+            Box::new(Paraboloid::new(base, axis, 0.7, light.clone())),
+            Box::new(Hyperboloid::new(base, axis, 0.4, 0.9, light.clone())),
+            Box::new(Hyperboloid::new(base, axis, 0.9, 0.4, light.clone())),
+            Box::new(Torus::new(base, axis, 1.0, 0.3, light.clone())),
+            Box::new(Capsule::new(base, axis, 0.5, light.clone())),
+        ];
+
+        for (index, shape) in shapes.iter().enumerate() {
+            let mut lights = vec![];
+            shape.lights(&mut lights);
+            assert_eq!(lights.len(), 1);
+
+            let area = shape.area();
+            let at = |u: (f64, f64)| shape.sample_surface(u, 0.0).unwrap();
+            let delta = 1e-6;
+            for i in 0..20 {
+                for j in 0..20 {
+                    let u = ((f64::from(i) + 0.37) / 20.0, (f64::from(j) + 0.61) / 20.0);
+                    let sample = at(u);
+                    let du = at((u.0 + delta, u.1)).p - sample.p;
+                    let dv = at((u.0, u.1 + delta)).p - sample.p;
+                    let jacobian = cross(&du, &dv).len() / (delta * delta);
+                    assert!(
+                        (jacobian / area - 1.0).abs() < 1e-3,
+                        "shape {index} at {u:?}: {jacobian} for an area of {area}"
+                    );
+
+                    let r = Ray::new(sample.p + 0.01 * sample.normal, -sample.normal, 0.0);
+                    let hit = shape.hit(&r, (0.0, f64::INFINITY)).unwrap();
+                    assert!((hit.t - 0.01).abs() < 1e-6, "shape {index} at {u:?}");
+                    assert!(hit.front_face && dot(&hit.normal, &sample.normal) > 0.999);
+                }
+            }
+        }
+    }
+}
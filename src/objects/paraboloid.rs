@@ -0,0 +1,110 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    object_frame::{azimuth, nearest_root, ObjectFrame},
+    polynomial::solve_quadratic,
+};
+
+// An open paraboloid with its vertex at `vertex`, opening along `axis` and
+// `radius` wide where the axis ends. The surface coordinates are the angle
+// around the axis and the height.
+pub struct Paraboloid {
+    frame: ObjectFrame,
+    radius: f64,
+    height: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Paraboloid {
+    pub fn new(vertex: Point3, axis: Vec3, radius: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            frame: ObjectFrame::new(vertex, &axis),
+            radius,
+            height: axis.len(),
+            material,
+        }
+    }
+
+    // x² + y² = k z
+    fn k(&self) -> f64 {
+        self.radius * self.radius / self.height
+    }
+
+    // The area inside the radius `rho`, over 2π.
+    fn area_within(&self, rho: f64) -> f64 {
+        let k = self.k();
+        k * k / 12.0 * ((1.0 + 4.0 * rho * rho / (k * k)).powf(1.5) - 1.0)
+    }
+
+    // The local normal, tangents and surface coordinates at `p`.
+    fn surface(&self, p: &Point3) -> ((Vec3, Vec3, Vec3), (f64, f64)) {
+        let v = p.z() / self.height;
+        let normal = Vec3::new(2.0 * p.x(), 2.0 * p.y(), -self.k());
+        let dpdu = 2.0 * PI * Vec3::new(-p.y(), p.x(), 0.0);
+        let dpdv = if v > 0.0 {
+            Vec3::new(p.x() / (2.0 * v), p.y() / (2.0 * v), self.height)
+        } else {
+            Vec3::new(0.0, 0.0, self.height)
+        };
+        ((normal, dpdu, dpdv), (azimuth(p) / (2.0 * PI), v))
+    }
+}
+
+impl Hittable for Paraboloid {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        Some(self.frame.bounding_box(
+            Point3::new(-self.radius, -self.radius, 0.0),
+            Point3::new(self.radius, self.radius, self.height),
+        ))
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let (o, d) = self.frame.to_local(r);
+
+        let k = self.k();
+        let a = d.x() * d.x() + d.y() * d.y();
+        let b = 2.0 * (o.x() * d.x() + o.y() * d.y()) - k * d.z();
+        let c = o.x() * o.x() + o.y() * o.y() - k * o.z();
+        let (t0, t1) = solve_quadratic(a, b, c)?;
+        let t = nearest_root(&[t0, t1], interval, |t| {
+            (0.0..=self.height).contains(&(o.z() + t * d.z()))
+        })?;
+
+        let (local, uv) = self.surface(&(o + t * d));
+        Some(self.frame.hit_record(r, t, local, uv, &self.material))
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        // Inverts `area_within`.
+        let k = self.k();
+        let area = u.0 * self.area_within(self.radius);
+        let w = (1.0 + 12.0 * area / (k * k)).powf(2.0 / 3.0);
+        let rho = 0.5 * k * (w - 1.0).max(0.0).sqrt();
+        let phi = 2.0 * PI * u.1;
+        let p = Point3::new(rho * phi.cos(), rho * phi.sin(), rho * rho / k);
+        let (local, uv) = self.surface(&p);
+        Some(self.frame.surface_record(&p, local, uv, &self.material))
+    }
+
+    fn area(&self) -> f64 {
+        2.0 * PI * self.area_within(self.radius)
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    onb::Onb,
+    ray::Ray,
+    vec3::{dot, Point3, Vec3},
+};
+
+// An infinite plane through `point` facing along `normal`. It has no bounding
+// box, and its surface coordinates repeat every unit of length along two
+// tangents. Its area is infinite, so it can't be sampled as a light.
+pub struct Plane {
+    point: Point3,
+    frame: Onb,
+    material: Arc<dyn Material>,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, material: Arc<dyn Material>) -> Self {
+        Self {
+            point,
+            frame: Onb::build_from_w(&normal),
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        None
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let normal = self.frame.w();
+        let denominator = dot(&normal, &r.direction());
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = dot(&normal, &(self.point - r.origin())) / denominator;
+        if t < interval.0 || t > interval.1 {
+            return None;
+        }
+
+        let p = r.at(t);
+        let local = self.frame.to_local(&(p - self.point));
+        let uv = (local.x() - local.x().floor(), local.y() - local.y().floor());
+
+        let mut result = HitRecord {
+            t,
+            p,
+            normal,
+            shading_normal: normal,
+            dpdu: self.frame.u(),
+            dpdv: self.frame.v(),
+            front_face: true,
+            samplable: false,
+            exterior_ior: 1.0,
+            surface_coordinates: uv,
+            material: Arc::clone(&self.material),
+        };
+        result.set_face_normal(r, normal);
+
+        Some(result)
+    }
+}
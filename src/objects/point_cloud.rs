@@ -0,0 +1,519 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+    sync::Arc,
+};
+
+use crate::{
+    hits::{
+        aabb::{surrounding_box, AABB},
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    onb::Onb,
+    ray::Ray,
+    vec3::{dot, unit_vector, Color, Point3, Vec3},
+};
+
+use super::sphere::{get_sphere_tangents, get_sphere_uv};
+
+// Points per leaf of the cloud's own BVH.
+const LEAF_SIZE: usize = 4;
+
+// Start of the binary point format. It is followed by the number of points
+// as a little endian u32 and then seven little endian f32 per point: the
+// position, the radius and the color.
+const BINARY_MAGIC: &[u8; 4] = b"PTS1";
+// Bytes per point after the header.
+const RECORD_SIZE: usize = 28;
+
+#[derive(Debug)]
+pub enum PointCloudError {
+    Io(io::Error),
+    Format(String),
+}
+
+impl fmt::Display for PointCloudError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointCloudError::Io(error) => write!(f, "could not read points: {error}"),
+            PointCloudError::Format(message) => write!(f, "invalid points: {message}"),
+        }
+    }
+}
+
+impl Error for PointCloudError {}
+
+impl From<io::Error> for PointCloudError {
+    fn from(error: io::Error) -> Self {
+        PointCloudError::Io(error)
+    }
+}
+
+// One particle, kept in single precision to fit millions of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CloudPoint {
+    pub position: [f32; 3],
+    pub radius: f32,
+    pub color: [f32; 3],
+}
+
+impl CloudPoint {
+    pub fn new(position: Point3, radius: f64, color: Color) -> Self {
+        Self {
+            position: [
+                position.x() as f32,
+                position.y() as f32,
+                position.z() as f32,
+            ],
+            radius: radius as f32,
+            color: [color.x() as f32, color.y() as f32, color.z() as f32],
+        }
+    }
+
+    fn center(&self) -> Point3 {
+        Point3::new(
+            f64::from(self.position[0]),
+            f64::from(self.position[1]),
+            f64::from(self.position[2]),
+        )
+    }
+
+    fn color(&self) -> Color {
+        Color::new(
+            f64::from(self.color[0]),
+            f64::from(self.color[1]),
+            f64::from(self.color[2]),
+        )
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let r = f64::from(self.radius);
+        AABB::new(
+            self.center() - Vec3::new(r, r, r),
+            self.center() + Vec3::new(r, r, r),
+        )
+    }
+}
+
+// What each point is drawn as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointShape {
+    Sphere,
+    // A disc that turns to face every ray, like a splat.
+    Disc,
+}
+
+// A node of the flattened BVH. Leaves hold `count` points from `start`,
+// inner nodes have their first child right after them and the second at
+// `second`.
+struct Node {
+    bounds: AABB,
+    start: u32,
+    count: u32,
+    second: u32,
+}
+
+// Many spheres or discs with their own radius and color in one object. One
+// material is made for every distinct color and shared by the points with it.
+// The points can't be sampled as lights, emission on them is only found by
+// hitting them.
+pub struct PointCloud {
+    points: Vec<CloudPoint>,
+    nodes: Vec<Node>,
+    shape: PointShape,
+    materials: Vec<Arc<dyn Material>>,
+    // The index into `materials` of every point.
+    material_indices: Vec<u32>,
+}
+
+impl PointCloud {
+    pub fn new(
+        mut points: Vec<CloudPoint>,
+        shape: PointShape,
+        material: fn(Color) -> Arc<dyn Material>,
+    ) -> Self {
+        let mut nodes = Vec::with_capacity(2 * points.len() / LEAF_SIZE + 1);
+        if !points.is_empty() {
+            let count = points.len();
+            build(&mut points, 0, count, &mut nodes);
+        }
+
+        let mut materials = vec![];
+        let mut by_color = HashMap::new();
+        let material_indices = points
+            .iter()
+            .map(|point| {
+                *by_color
+                    .entry(point.color.map(f32::to_bits))
+                    .or_insert_with(|| {
+                        materials.push(material(point.color()));
+                        materials.len() as u32 - 1
+                    })
+            })
+            .collect();
+        Self {
+            points,
+            nodes,
+            shape,
+            materials,
+            material_indices,
+        }
+    }
+
+    // Reads points as lines of `x,y,z`, optionally followed by a radius and
+    // then a color. Points without a radius get `radius`, points without a
+    // color are white. Empty lines and lines starting with `#` are skipped.
+    pub fn new_from_csv(
+        path: &str,
+        radius: f64,
+        shape: PointShape,
+        material: fn(Color) -> Arc<dyn Material>,
+    ) -> Result<Self, PointCloudError> {
+        let points = read_csv(BufReader::new(File::open(path)?), radius)?;
+        Ok(Self::new(points, shape, material))
+    }
+
+    // Reads points in the binary format described at `BINARY_MAGIC`.
+    pub fn new_from_binary(
+        path: &str,
+        shape: PointShape,
+        material: fn(Color) -> Arc<dyn Material>,
+    ) -> Result<Self, PointCloudError> {
+        let points = read_binary(BufReader::new(File::open(path)?))?;
+        Ok(Self::new(points, shape, material))
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    // Only the distance to point `index`, without building a record.
+    fn hit_distance(&self, index: usize, r: &Ray, interval: (f64, f64)) -> Option<f64> {
+        let point = &self.points[index];
+        let radius = f64::from(point.radius);
+        let oc = r.origin() - point.center();
+        let a = r.direction().len_squared();
+        let t = match self.shape {
+            PointShape::Sphere => {
+                let half_b = dot(&oc, &r.direction());
+                let c = oc.len_squared() - radius * radius;
+                let discriminant = half_b * half_b - a * c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let sqrtd = discriminant.sqrt();
+                let t = (-half_b - sqrtd) / a;
+                if t < interval.0 {
+                    (-half_b + sqrtd) / a
+                } else {
+                    t
+                }
+            }
+            PointShape::Disc => {
+                let t = -dot(&oc, &r.direction()) / a;
+                if (oc + t * r.direction()).len_squared() > radius * radius {
+                    return None;
+                }
+                t
+            }
+        };
+        (t >= interval.0 && t <= interval.1).then_some(t)
+    }
+
+    // The surface of point `index` where `r` crosses it at `t`.
+    fn record(&self, index: usize, r: &Ray, t: f64) -> HitRecord {
+        let point = &self.points[index];
+        let center = point.center();
+        let radius = f64::from(point.radius);
+        let p = r.at(t);
+
+        let (outward_normal, uv, (dpdu, dpdv)) = match self.shape {
+            PointShape::Sphere => {
+                let normal = (p - center) / radius;
+                (
+                    normal,
+                    get_sphere_uv(&normal),
+                    get_sphere_tangents(&normal, radius),
+                )
+            }
+            PointShape::Disc => {
+                let normal = -unit_vector(r.direction());
+                let frame = Onb::build_from_w(&normal);
+                let offset = p - center;
+                let uv = (
+                    0.5 + 0.5 * dot(&offset, &frame.u()) / radius,
+                    0.5 + 0.5 * dot(&offset, &frame.v()) / radius,
+                );
+                (
+                    normal,
+                    uv,
+                    (2.0 * radius * frame.u(), 2.0 * radius * frame.v()),
+                )
+            }
+        };
+
+        let mut result = HitRecord {
+            t,
+            p,
+            normal: outward_normal,
+            shading_normal: outward_normal,
+            dpdu,
+            dpdv,
+            front_face: true,
+            samplable: false,
+            exterior_ior: 1.0,
+            surface_coordinates: uv,
+            material: self.materials[self.material_indices[index] as usize].clone(),
+        };
+        result.set_face_normal(r, outward_normal);
+        result
+    }
+}
+
+impl Hittable for PointCloud {
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        // Finds the closest point first and builds the record only for it.
+        let mut closest: Option<(usize, f64)> = None;
+        let mut t_max = interval.1;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.bounds.hit(r, (interval.0, t_max)) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.second as usize);
+                stack.push(index + 1);
+                continue;
+            }
+            for point in node.start as usize..(node.start + node.count) as usize {
+                if let Some(t) = self.hit_distance(point, r, (interval.0, t_max)) {
+                    t_max = t;
+                    closest = Some((point, t));
+                }
+            }
+        }
+
+        let (point, t) = closest?;
+        Some(self.record(point, r, t))
+    }
+
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        self.nodes.first().map(|node| node.bounds)
+    }
+}
+
+// Builds the subtree over `points[start..end]`, reordering them so that
+// every leaf covers a contiguous range, and returns its index.
+fn build(points: &mut [CloudPoint], start: usize, end: usize, nodes: &mut Vec<Node>) -> usize {
+    let bounds = points[start..end]
+        .iter()
+        .map(CloudPoint::bounding_box)
+        .reduce(surrounding_box)
+        .unwrap();
+    let index = nodes.len();
+    nodes.push(Node {
+        bounds,
+        start: start as u32,
+        count: (end - start) as u32,
+        second: 0,
+    });
+    if end - start <= LEAF_SIZE {
+        return index;
+    }
+
+    // Splits at the median along the longest extent of the centers.
+    let extent = bounds.max() - bounds.min();
+    let axis = if extent.x() > extent.y() && extent.x() > extent.z() {
+        0
+    } else if extent.y() > extent.z() {
+        1
+    } else {
+        2
+    };
+    let middle = (end - start) / 2;
+    points[start..end]
+        .select_nth_unstable_by(middle, |a, b| a.position[axis].total_cmp(&b.position[axis]));
+
+    build(points, start, start + middle, nodes);
+    let second = build(points, start + middle, end, nodes);
+    nodes[index].count = 0;
+    nodes[index].second = second as u32;
+    index
+}
+
+fn read_csv(reader: impl BufRead, radius: f64) -> Result<Vec<CloudPoint>, PointCloudError> {
+    let mut points = vec![];
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let values = line
+            .split(',')
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| PointCloudError::Format(format!("line {}: {error}", number + 1)))?;
+        let (position, rest) = match values.len() {
+            3..=4 | 7 => values.split_at(3),
+            count => {
+                return Err(PointCloudError::Format(format!(
+                    "line {}: expected 3, 4 or 7 values, got {count}",
+                    number + 1
+                )))
+            }
+        };
+        let radius = rest.first().copied().unwrap_or(radius);
+        let color = if rest.len() == 4 {
+            Color::new(rest[1], rest[2], rest[3])
+        } else {
+            Color::new(1.0, 1.0, 1.0)
+        };
+        points.push(CloudPoint::new(
+            Point3::new(position[0], position[1], position[2]),
+            radius,
+            color,
+        ));
+    }
+    Ok(points)
+}
+
+fn read_binary(mut reader: impl Read) -> Result<Vec<CloudPoint>, PointCloudError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != BINARY_MAGIC {
+        return Err(PointCloudError::Format("missing PTS1 header".to_string()));
+    }
+    let mut count = [0; 4];
+    reader.read_exact(&mut count)?;
+    let count = u32::from_le_bytes(count) as usize;
+
+    // The points are read in full before anything is allocated for them, so
+    // a corrupt count cannot ask for more memory than the file holds.
+    let length = count * RECORD_SIZE;
+    let mut bytes = vec![];
+    reader.take(length as u64 + 1).read_to_end(&mut bytes)?;
+    if bytes.len() != length {
+        return Err(PointCloudError::Format(format!(
+            "expected {count} points, got {} bytes of point data",
+            bytes.len()
+        )));
+    }
+
+    Ok(bytes
+        .chunks_exact(RECORD_SIZE)
+        .map(|record| {
+            let value = |i: usize| f32::from_le_bytes(record[4 * i..4 * i + 4].try_into().unwrap());
+            CloudPoint {
+                position: [value(0), value(1), value(2)],
+                radius: value(3),
+                color: [value(4), value(5), value(6)],
+            }
+        })
+        .collect())
+}
+
+// Writes `points` in the binary format read by `PointCloud::new_from_binary`.
+pub fn write_binary(writer: &mut impl Write, points: &[CloudPoint]) -> io::Result<()> {
+    writer.write_all(BINARY_MAGIC)?;
+    writer.write_all(&(points.len() as u32).to_le_bytes())?;
+    for point in points {
+        for value in point
+            .position
+            .iter()
+            .chain([point.radius].iter())
+            .chain(point.color.iter())
+        {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hits::hittable::Hittable,
+        materials::{lambertian::Lambertian, Material},
+        ray::Ray,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    use super::{read_binary, read_csv, write_binary, CloudPoint, PointCloud, PointShape};
+
+    fn diffuse(color: Color) -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(color))
+    }
+
+    #[test]
+    fn csv_and_binary_agree() {
+        let csv = "# x,y,z,radius,r,g,b\n0,0,0\n\n1,2,3,0.5\n4,5,6,0.25,1,0.5,0\n";
+        let points = read_csv(csv.as_bytes(), 0.1).unwrap();
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].radius, 0.1);
+        assert_eq!(points[1].color, [1.0, 1.0, 1.0]);
+        assert_eq!(points[2].color, [1.0, 0.5, 0.0]);
+        assert!(read_csv("1,2\n".as_bytes(), 0.1).is_err());
+
+        let mut bytes = vec![];
+        write_binary(&mut bytes, &points).unwrap();
+        assert_eq!(read_binary(bytes.as_slice()).unwrap(), points);
+        assert!(read_binary(&bytes[..bytes.len() - 1]).is_err());
+        bytes[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(read_binary(bytes.as_slice()).is_err());
+    }
+
+    // A grid of points hit by rays through every one of them finds the
+    // closest, whichever shape they are drawn as.
+    #[test]
+    fn rays_find_the_nearest_point() {
+        let mut points = vec![];
+        for i in 0..20 {
+            for j in 0..20 {
+                for k in 0..5 {
+                    points.push(CloudPoint::new(
+                        Point3::new(f64::from(i), f64::from(j), -f64::from(k)),
+                        0.3,
+                        Color::new(f64::from(k), 0.0, 0.0),
+                    ));
+                }
+            }
+        }
+
+        for shape in [PointShape::Sphere, PointShape::Disc] {
+            let cloud = PointCloud::new(points.clone(), shape, diffuse);
+            assert_eq!(cloud.len(), 2000);
+            assert_eq!(cloud.materials.len(), 5);
+            for i in 0..20 {
+                for j in 0..20 {
+                    let r = Ray::new(
+                        Point3::new(f64::from(i) + 0.1, f64::from(j), 10.0),
+                        Vec3::new(0.0, 0.0, -1.0),
+                        0.0,
+                    );
+                    let hit = cloud.hit(&r, (0.001, f64::INFINITY)).unwrap();
+                    assert!(hit.p.z() > -0.01 && hit.p.z() < 0.31);
+                    assert!(hit.front_face);
+                }
+            }
+            let miss = Ray::new(Point3::new(0.5, 0.5, 10.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            assert!(cloud.hit(&miss, (0.001, f64::INFINITY)).is_none());
+        }
+    }
+}
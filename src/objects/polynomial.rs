@@ -0,0 +1,140 @@
+use std::f64::consts::PI;
+
+// Coefficients closer to zero than this count as zero.
+const EPSILON: f64 = 1e-9;
+
+// The real roots of a x² + b x + c in increasing order, computed without
+// cancellation. A vanishing `a` leaves the linear equation's root twice.
+pub(crate) fn solve_quadratic(a: f64, b: f64, c: f64) -> Option<(f64, f64)> {
+    if a == 0.0 {
+        if b == 0.0 {
+            return None;
+        }
+        let root = -c / b;
+        return Some((root, root));
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let (t0, t1) = if q == 0.0 { (0.0, 0.0) } else { (q / a, c / q) };
+    Some((t0.min(t1), t0.max(t1)))
+}
+
+// The real roots of x³ + a x² + b x + c, unordered.
+pub(crate) fn solve_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    // Substituting x = y - a/3 leaves y³ + 3p y + 2q.
+    let p = (b - a * a / 3.0) / 3.0;
+    let q = (2.0 * a * a * a / 27.0 - a * b / 3.0 + c) / 2.0;
+    let discriminant = q * q + p * p * p;
+    let shift = a / 3.0;
+
+    let roots = if discriminant.abs() < EPSILON {
+        if q.abs() < EPSILON {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // Three real roots.
+        let phi = (-q / (-p * p * p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.0).cos(),
+            -t * (phi - PI / 3.0).cos(),
+        ]
+    } else {
+        let sqrt_discriminant = discriminant.sqrt();
+        vec![(sqrt_discriminant - q).cbrt() - (sqrt_discriminant + q).cbrt()]
+    };
+
+    roots.into_iter().map(|root| root - shift).collect()
+}
+
+// The real roots of c[4] x⁴ + c[3] x³ + c[2] x² + c[1] x + c[0] in increasing
+// order, by Ferrari's method and polished with Newton steps.
+pub(crate) fn solve_quartic(c: [f64; 5]) -> Vec<f64> {
+    if c[4] == 0.0 {
+        return vec![];
+    }
+    let (a, b, cc, d) = (c[3] / c[4], c[2] / c[4], c[1] / c[4], c[0] / c[4]);
+
+    // Substituting x = y - a/4 leaves y⁴ + p y² + q y + r.
+    let a2 = a * a;
+    let p = b - 3.0 * a2 / 8.0;
+    let q = cc - a * b / 2.0 + a2 * a / 8.0;
+    let r = d - a * cc / 4.0 + a2 * b / 16.0 - 3.0 * a2 * a2 / 256.0;
+
+    let mut roots = vec![];
+    if r.abs() < EPSILON {
+        // y (y³ + p y + q)
+        roots.push(0.0);
+        roots.extend(solve_cubic(0.0, p, q));
+    } else {
+        // Any real root of the resolvent cubic splits the quartic into two
+        // quadratics.
+        let z = solve_cubic(-p / 2.0, -r, r * p / 2.0 - q * q / 8.0)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        if u < -EPSILON || v < -EPSILON {
+            return vec![];
+        }
+        let u = u.max(0.0).sqrt();
+        let v = v.max(0.0).sqrt();
+        let v = if q < 0.0 { -v } else { v };
+
+        if let Some((y0, y1)) = solve_quadratic(1.0, v, z - u) {
+            roots.extend([y0, y1]);
+        }
+        if let Some((y0, y1)) = solve_quadratic(1.0, -v, z + u) {
+            roots.extend([y0, y1]);
+        }
+    }
+
+    let mut roots: Vec<f64> = roots.into_iter().map(|y| polish(&c, y - a / 4.0)).collect();
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+fn polish(c: &[f64; 5], mut x: f64) -> f64 {
+    for _ in 0..2 {
+        let value = (((c[4] * x + c[3]) * x + c[2]) * x + c[1]) * x + c[0];
+        let derivative = ((4.0 * c[4] * x + 3.0 * c[3]) * x + 2.0 * c[2]) * x + c[1];
+        if derivative == 0.0 {
+            break;
+        }
+        x -= value / derivative;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quartic_roots() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        let roots = solve_quartic([24.0, -50.0, 35.0, -10.0, 1.0]);
+        assert_eq!(roots.len(), 4);
+        for (root, expected) in roots.iter().zip([1.0, 2.0, 3.0, 4.0]) {
+            assert!((root - expected).abs() < 1e-9, "{root} != {expected}");
+        }
+
+        // (x² + 1)(x - 0.5)(x + 2) has two real roots.
+        let roots = solve_quartic([-1.0, 1.5, 0.0, 1.5, 1.0]);
+        assert_eq!(roots.len(), 2);
+        assert!((roots[0] + 2.0).abs() < 1e-9);
+        assert!((roots[1] - 0.5).abs() < 1e-9);
+
+        // x⁴ + 1 has none.
+        assert!(solve_quartic([1.0, 0.0, 0.0, 0.0, 1.0]).is_empty());
+    }
+}
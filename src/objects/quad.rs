@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    ray::Ray,
+    vec3::{cross, dot, unit_vector, Point3, Vec3},
+};
+
+// A parallelogram spanned by the edges `u` and `v` from the corner `origin`,
+// in any orientation. It faces along u × v, and the surface coordinates run
+// along the edges.
+pub struct Quad {
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    // Turns offsets in the plane into coordinates along the edges.
+    w: Vec3,
+    material: Arc<dyn Material>,
+}
+
+impl Quad {
+    pub fn new(origin: Point3, u: Vec3, v: Vec3, material: Arc<dyn Material>) -> Self {
+        let n = cross(&u, &v);
+        Self {
+            origin,
+            u,
+            v,
+            normal: unit_vector(n),
+            w: n / dot(&n, &n),
+            material,
+        }
+    }
+
+    // The parallelogram with corners `a`, `b` and `c`, where `b` and `c` are
+    // both adjacent to `a`.
+    pub fn new_from_corners(a: Point3, b: Point3, c: Point3, material: Arc<dyn Material>) -> Self {
+        Self::new(a, b - a, c - a, material)
+    }
+}
+
+impl Hittable for Quad {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        let corners = [
+            self.origin,
+            self.origin + self.u,
+            self.origin + self.v,
+            self.origin + self.u + self.v,
+        ];
+        let mut min = corners[0];
+        let mut max = corners[0];
+        for corner in &corners[1..] {
+            for a in 0..3 {
+                min[a] = min[a].min(corner[a]);
+                max[a] = max[a].max(corner[a]);
+            }
+        }
+
+        // Padded so that quads lying in an axis plane have a volume.
+        let padding = Vec3::new(0.0001, 0.0001, 0.0001);
+        Some(AABB::new(min - padding, max + padding))
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let denominator = dot(&self.normal, &r.direction());
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = dot(&self.normal, &(self.origin - r.origin())) / denominator;
+        if t < interval.0 || t > interval.1 {
+            return None;
+        }
+
+        let p = r.at(t);
+        let planar = p - self.origin;
+        let alpha = dot(&self.w, &cross(&planar, &self.v));
+        let beta = dot(&self.w, &cross(&self.u, &planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let mut result = HitRecord {
+            t,
+            p,
+            normal: self.normal,
+            shading_normal: self.normal,
+            dpdu: self.u,
+            dpdv: self.v,
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: (alpha, beta),
+            material: Arc::clone(&self.material),
+        };
+        result.set_face_normal(r, self.normal);
+
+        Some(result)
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        Some(HitRecord {
+            t: 0.0,
+            p: self.origin + u.0 * self.u + u.1 * self.v,
+            normal: self.normal,
+            shading_normal: self.normal,
+            dpdu: self.u,
+            dpdv: self.v,
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: u,
+            material: Arc::clone(&self.material),
+        })
+    }
+
+    fn area(&self) -> f64 {
+        cross(&self.u, &self.v).len()
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    ray::Ray,
+    sdf::Sdf,
+    vec3::{dot, unit_vector, Point3, Vec3},
+};
+
+use super::sphere::{get_sphere_tangents, get_sphere_uv};
+
+const MAX_STEPS: u32 = 512;
+// How close to the surface a step has to land to count as a hit.
+const HIT_EPSILON: f64 = 0.00001;
+const GRADIENT_DELTA: f64 = 0.0001;
+
+// The surface where a signed distance field is zero, found by sphere
+// tracing. The surface coordinates are spherical around the center of the
+// bounds. The surface can't be sampled as a light, emission on it is only
+// found by hitting it.
+pub struct SdfObject {
+    sdf: Arc<dyn Sdf>,
+    bounds: AABB,
+    material: Arc<dyn Material>,
+}
+
+impl SdfObject {
+    pub fn new(sdf: Arc<dyn Sdf>, material: Arc<dyn Material>) -> Self {
+        let bounds = sdf.bounding_box();
+        let padding = Vec3::new(0.001, 0.001, 0.001);
+        Self {
+            sdf,
+            bounds: AABB::new(bounds.min() - padding, bounds.max() + padding),
+            material,
+        }
+    }
+
+    // Where `r` is inside the bounds, within `interval`.
+    fn clip(&self, r: &Ray, interval: (f64, f64)) -> Option<(f64, f64)> {
+        let (mut t_min, mut t_max) = interval;
+        for axis in 0..3 {
+            let inv_d = 1.0 / r.direction()[axis];
+            let mut t0 = (self.bounds.min()[axis] - r.origin()[axis]) * inv_d;
+            let mut t1 = (self.bounds.max()[axis] - r.origin()[axis]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    // The gradient of the field by central differences on a tetrahedron.
+    fn normal(&self, p: &Point3) -> Vec3 {
+        let offsets = [
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ];
+        let gradient = offsets.iter().fold(Vec3::default(), |sum, offset| {
+            sum + self.sdf.distance(&(*p + GRADIENT_DELTA * *offset)) * *offset
+        });
+        unit_vector(gradient)
+    }
+}
+
+impl Hittable for SdfObject {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        Some(self.bounds)
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let (t_start, t_end) = self.clip(r, interval)?;
+        let ray_length = r.direction().len();
+
+        // Rays starting inside march on the distance to the surface all the
+        // same, and find where they leave.
+        let mut t = t_start;
+        for _ in 0..MAX_STEPS {
+            if t > t_end {
+                return None;
+            }
+            let p = r.at(t);
+            let distance = self.sdf.distance(&p).abs();
+            if distance < HIT_EPSILON {
+                let normal = self.normal(&p);
+                let center = 0.5 * (self.bounds.min() + self.bounds.max());
+                let direction = unit_vector(p - center);
+                let (dpdu, dpdv) = get_sphere_tangents(&direction, (p - center).len());
+
+                let mut result = HitRecord {
+                    t,
+                    p,
+                    normal,
+                    shading_normal: normal,
+                    dpdu: dpdu - dot(&dpdu, &normal) * normal,
+                    dpdv: dpdv - dot(&dpdv, &normal) * normal,
+                    front_face: true,
+                    samplable: false,
+                    exterior_ior: 1.0,
+                    surface_coordinates: get_sphere_uv(&direction),
+                    material: Arc::clone(&self.material),
+                };
+                result.set_face_normal(r, normal);
+                return Some(result);
+            }
+            t += distance / ray_length;
+        }
+
+        None
+    }
+}
@@ -1,4 +1,4 @@
-use std::{f64::consts::PI, rc::Rc};
+use std::{f64::consts::PI, sync::Arc};
 
 use crate::{
     hits::{
@@ -7,24 +7,48 @@ use crate::{
     },
     materials::Material,
     ray,
-    vec3::{dot, Point3, Vec3},
+    vec3::{dot, sample_uniform_sphere, Point3, Vec3},
 };
 
 #[derive(Clone)]
 pub struct Sphere {
     center: Point3,
     radius: f64,
-    material: Rc<dyn Material>,
+    material: Arc<dyn Material>,
 }
 
 impl Sphere {
-    pub fn new(center: Point3, radius: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(center: Point3, radius: f64, material: Arc<dyn Material>) -> Self {
         Self {
             center,
             radius,
             material,
         }
     }
+
+    fn hit_record(&self, r: &ray::Ray, t: f64) -> HitRecord {
+        let p = r.at(t);
+        let normal = (p - self.center) / self.radius;
+        let uv = get_sphere_uv(&normal);
+        let (dpdu, dpdv) = get_sphere_tangents(&normal, self.radius);
+
+        let mut result = HitRecord {
+            t,
+            p,
+            normal,
+            shading_normal: normal,
+            dpdu,
+            dpdv,
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: uv,
+            material: Arc::clone(&self.material),
+        };
+        result.set_face_normal(r, normal);
+
+        result
+    }
 }
 
 impl Hittable for Sphere {
@@ -51,22 +75,26 @@ impl Hittable for Sphere {
             }
         }
 
-        let t = root;
-        let p = r.at(t);
-        let normal = (p - self.center) / self.radius;
-        let uv = get_sphere_uv(&normal);
+        Some(self.hit_record(r, root))
+    }
 
-        let mut result = HitRecord {
-            t,
-            p,
-            normal,
-            front_face: true,
-            surface_coordinates: uv,
-            material: Rc::clone(&self.material),
-        };
-        result.set_face_normal(r, normal);
+    fn hit_all(&self, r: &ray::Ray, interval: (f64, f64)) -> Vec<HitRecord> {
+        let oc = r.origin() - self.center;
+        let a = r.direction().len_squared();
+        let half_b = dot(&oc, &r.direction());
+        let c = oc.len_squared() - self.radius * self.radius;
 
-        Some(result)
+        let discrim = half_b * half_b - a * c;
+        if discrim < 0.0 {
+            return vec![];
+        }
+
+        let sqrtd = discrim.sqrt();
+        [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a]
+            .into_iter()
+            .filter(|t| *t >= interval.0 && *t <= interval.1)
+            .map(|t| self.hit_record(r, t))
+            .collect()
     }
 
     #[allow(unused_variables)]
@@ -77,9 +105,38 @@ impl Hittable for Sphere {
         );
         Some(bounding_box)
     }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        let normal = sample_uniform_sphere(u);
+        let (dpdu, dpdv) = get_sphere_tangents(&normal, self.radius);
+        Some(HitRecord {
+            t: 0.0,
+            p: self.center + self.radius * normal,
+            normal,
+            shading_normal: normal,
+            dpdu,
+            dpdv,
+            front_face: true,
+            samplable: true,
+            exterior_ior: 1.0,
+            surface_coordinates: get_sphere_uv(&normal),
+            material: Arc::clone(&self.material),
+        })
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * PI * self.radius * self.radius
+    }
 }
 
-fn get_sphere_uv(p: &Point3) -> (f64, f64) {
+pub(crate) fn get_sphere_uv(p: &Point3) -> (f64, f64) {
     let theta = (-p.y()).acos();
     let phi = (-p.z()).atan2(p.x()) + PI;
 
@@ -88,3 +145,21 @@ fn get_sphere_uv(p: &Point3) -> (f64, f64) {
 
     (u, v)
 }
+
+pub(crate) fn get_sphere_tangents(n: &Vec3, radius: f64) -> (Vec3, Vec3) {
+    let dpdu = 2.0 * PI * radius * Vec3::new(n.z(), 0.0, -n.x());
+
+    let sin_theta = (n.x() * n.x() + n.z() * n.z()).sqrt();
+    let dpdv = if sin_theta > 0.0 {
+        PI * radius
+            * Vec3::new(
+                -n.x() * n.y() / sin_theta,
+                sin_theta,
+                -n.y() * n.z() / sin_theta,
+            )
+    } else {
+        PI * radius * Vec3::new(1.0, 0.0, 0.0)
+    };
+
+    (dpdu, dpdv)
+}
@@ -0,0 +1,457 @@
+use std::{collections::HashMap, error::Error, f64::consts::PI, fmt};
+
+use crate::vec3::Point3;
+
+use super::mesh::TriangleMesh;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubdivisionScheme {
+    // Any polygons, refined into quads.
+    CatmullClark,
+    // Triangles, refined into triangles. Other polygons are split into
+    // triangles first.
+    Loop,
+}
+
+// A face that cannot be part of a cage, by its index.
+#[derive(Debug)]
+pub enum ControlMeshError {
+    TooFewVertices(usize),
+    VertexOutOfRange(usize),
+    RepeatedVertex(usize),
+}
+
+impl fmt::Display for ControlMeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlMeshError::TooFewVertices(face) => {
+                write!(f, "face {face} has fewer than three vertices")
+            }
+            ControlMeshError::VertexOutOfRange(face) => {
+                write!(f, "face {face} refers to a vertex that does not exist")
+            }
+            ControlMeshError::RepeatedVertex(face) => {
+                write!(f, "face {face} uses a vertex more than once")
+            }
+        }
+    }
+}
+
+impl Error for ControlMeshError {}
+
+// A polygon mesh used as a subdivision cage. Edges can be given a crease
+// sharpness: the number of levels they stay sharp for, with a fraction
+// blending into smooth over the last one. Boundary edges are always sharp.
+#[derive(Clone, Debug, Default)]
+pub struct ControlMesh {
+    positions: Vec<Point3>,
+    faces: Vec<Vec<usize>>,
+    creases: HashMap<(usize, usize), f64>,
+}
+
+impl ControlMesh {
+    // Every face needs at least three distinct vertices, all of them in
+    // `positions`.
+    pub fn new(positions: Vec<Point3>, faces: Vec<Vec<usize>>) -> Result<Self, ControlMeshError> {
+        for (index, face) in faces.iter().enumerate() {
+            if face.len() < 3 {
+                return Err(ControlMeshError::TooFewVertices(index));
+            }
+            if face.iter().any(|vertex| *vertex >= positions.len()) {
+                return Err(ControlMeshError::VertexOutOfRange(index));
+            }
+            if has_repeated_vertex(face) {
+                return Err(ControlMeshError::RepeatedVertex(index));
+            }
+        }
+        Ok(Self {
+            positions,
+            faces,
+            creases: HashMap::new(),
+        })
+    }
+
+    // `f64::INFINITY` keeps the edge sharp at every level.
+    pub fn set_crease(&mut self, a: usize, b: usize, sharpness: f64) {
+        self.creases.insert(edge_key(a, b), sharpness);
+    }
+
+    pub fn positions(&self) -> &[Point3] {
+        &self.positions
+    }
+
+    pub fn faces(&self) -> &[Vec<usize>] {
+        &self.faces
+    }
+
+    pub fn subdivide(&self, scheme: SubdivisionScheme, levels: u32) -> Self {
+        let mut mesh = match scheme {
+            SubdivisionScheme::CatmullClark => self.clone(),
+            SubdivisionScheme::Loop => self.triangulated(),
+        };
+        for _ in 0..levels {
+            mesh = match scheme {
+                SubdivisionScheme::CatmullClark => mesh.catmull_clark_step(),
+                SubdivisionScheme::Loop => mesh.loop_step(),
+            };
+        }
+        mesh
+    }
+
+    // Splits every face into a fan of triangles.
+    pub fn to_triangle_mesh(&self) -> TriangleMesh {
+        let triangles = self
+            .faces
+            .iter()
+            .flat_map(|face| (1..face.len() - 1).map(move |i| [face[0], face[i], face[i + 1]]))
+            .collect();
+        TriangleMesh::new(self.positions.clone(), triangles)
+    }
+
+    fn triangulated(&self) -> Self {
+        let faces = self
+            .faces
+            .iter()
+            .flat_map(|face| (1..face.len() - 1).map(move |i| vec![face[0], face[i], face[i + 1]]))
+            .collect();
+        Self {
+            positions: self.positions.clone(),
+            faces,
+            creases: self.creases.clone(),
+        }
+    }
+
+    fn catmull_clark_step(&self) -> Self {
+        let topology = Topology::new(self);
+        let vertex_count = self.positions.len();
+        let edge_count = topology.edges.len();
+
+        let face_points: Vec<Point3> = self
+            .faces
+            .iter()
+            .map(|face| average(face.iter().map(|v| self.positions[*v])))
+            .collect();
+
+        let edge_points = topology.edges.iter().enumerate().map(|(e, (a, b))| {
+            let faces = &topology.edge_faces[e];
+            let smooth = if faces.len() == 2 {
+                (self.positions[*a]
+                    + self.positions[*b]
+                    + face_points[faces[0]]
+                    + face_points[faces[1]])
+                    / 4.0
+            } else {
+                Point3::default()
+            };
+            self.edge_point(&topology, e, smooth)
+        });
+
+        let vertex_points = (0..vertex_count).map(|v| {
+            let s = self.positions[v];
+            let n = topology.vertex_edges[v].len() as f64;
+            if topology.vertex_faces[v].is_empty() {
+                return s;
+            }
+            let q = average(topology.vertex_faces[v].iter().map(|f| face_points[*f]));
+            let r = average(topology.vertex_edges[v].iter().map(|e| {
+                let (a, b) = topology.edges[*e];
+                (self.positions[a] + self.positions[b]) / 2.0
+            }));
+            let smooth = (q + 2.0 * r + (n - 3.0) * s) / n;
+            self.vertex_point(&topology, v, smooth)
+        });
+
+        let positions = vertex_points
+            .chain(edge_points)
+            .chain(face_points.iter().copied())
+            .collect();
+
+        let mut faces = vec![];
+        for (f, face) in self.faces.iter().enumerate() {
+            let k = face.len();
+            for i in 0..k {
+                let next = topology.edge(face[i], face[(i + 1) % k]);
+                let previous = topology.edge(face[(i + k - 1) % k], face[i]);
+                faces.push(vec![
+                    face[i],
+                    vertex_count + next,
+                    vertex_count + edge_count + f,
+                    vertex_count + previous,
+                ]);
+            }
+        }
+
+        Self {
+            positions,
+            faces,
+            creases: self.child_creases(&topology),
+        }
+    }
+
+    fn loop_step(&self) -> Self {
+        let topology = Topology::new(self);
+        let vertex_count = self.positions.len();
+
+        let edge_points = topology.edges.iter().enumerate().map(|(e, (a, b))| {
+            let faces = &topology.edge_faces[e];
+            let smooth = if faces.len() == 2 {
+                let opposite = |f: usize| {
+                    let v = self.faces[f]
+                        .iter()
+                        .find(|v| *v != a && *v != b)
+                        .copied()
+                        .unwrap_or(*a);
+                    self.positions[v]
+                };
+                3.0 / 8.0 * (self.positions[*a] + self.positions[*b])
+                    + 1.0 / 8.0 * (opposite(faces[0]) + opposite(faces[1]))
+            } else {
+                Point3::default()
+            };
+            self.edge_point(&topology, e, smooth)
+        });
+
+        let vertex_points = (0..vertex_count).map(|v| {
+            let s = self.positions[v];
+            let edges = &topology.vertex_edges[v];
+            if edges.is_empty() {
+                return s;
+            }
+            let n = edges.len() as f64;
+            let beta = (5.0 / 8.0 - (3.0 / 8.0 + (2.0 * PI / n).cos() / 4.0).powi(2)) / n;
+            let neighbors = edges.iter().fold(Point3::default(), |sum, e| {
+                sum + self.positions[topology.other_end(*e, v)]
+            });
+            let smooth = (1.0 - n * beta) * s + beta * neighbors;
+            self.vertex_point(&topology, v, smooth)
+        });
+
+        let positions = vertex_points.chain(edge_points).collect();
+
+        let mut faces = vec![];
+        for face in &self.faces {
+            let (a, b, c) = (face[0], face[1], face[2]);
+            let ab = vertex_count + topology.edge(a, b);
+            let bc = vertex_count + topology.edge(b, c);
+            let ca = vertex_count + topology.edge(c, a);
+            faces.push(vec![a, ab, ca]);
+            faces.push(vec![b, bc, ab]);
+            faces.push(vec![c, ca, bc]);
+            faces.push(vec![ab, bc, ca]);
+        }
+
+        Self {
+            positions,
+            faces,
+            creases: self.child_creases(&topology),
+        }
+    }
+
+    fn sharpness(&self, topology: &Topology, e: usize) -> f64 {
+        if topology.edge_faces[e].len() != 2 {
+            return f64::INFINITY;
+        }
+        let (a, b) = topology.edges[e];
+        self.creases.get(&edge_key(a, b)).copied().unwrap_or(0.0)
+    }
+
+    // The midpoint for sharp edges, `smooth` otherwise.
+    fn edge_point(&self, topology: &Topology, e: usize, smooth: Point3) -> Point3 {
+        let (a, b) = topology.edges[e];
+        let midpoint = (self.positions[a] + self.positions[b]) / 2.0;
+        blend(smooth, midpoint, self.sharpness(topology, e))
+    }
+
+    // `smooth` unless two or more sharp edges meet at the vertex: along a
+    // crease it follows the crease curve, at corners it stays put.
+    fn vertex_point(&self, topology: &Topology, v: usize, smooth: Point3) -> Point3 {
+        let sharp: Vec<(usize, f64)> = topology.vertex_edges[v]
+            .iter()
+            .map(|e| (*e, self.sharpness(topology, *e)))
+            .filter(|(_, sharpness)| *sharpness > 0.0)
+            .collect();
+        if sharp.len() < 2 {
+            return smooth;
+        }
+
+        let s = self.positions[v];
+        let sharp_point = if sharp.len() == 2 {
+            let a = self.positions[topology.other_end(sharp[0].0, v)];
+            let b = self.positions[topology.other_end(sharp[1].0, v)];
+            0.75 * s + 0.125 * (a + b)
+        } else {
+            s
+        };
+        let sharpness =
+            sharp.iter().map(|(_, sharpness)| sharpness).sum::<f64>() / sharp.len() as f64;
+        blend(smooth, sharp_point, sharpness)
+    }
+
+    // Creases carry over to both halves of their edge, one level less sharp.
+    fn child_creases(&self, topology: &Topology) -> HashMap<(usize, usize), f64> {
+        let vertex_count = self.positions.len();
+        let mut creases = HashMap::new();
+        for (e, (a, b)) in topology.edges.iter().enumerate() {
+            if let Some(sharpness) = self.creases.get(&edge_key(*a, *b)) {
+                if *sharpness > 1.0 {
+                    creases.insert(edge_key(*a, vertex_count + e), sharpness - 1.0);
+                    creases.insert(edge_key(vertex_count + e, *b), sharpness - 1.0);
+                }
+            }
+        }
+        creases
+    }
+}
+
+// Edges and what meets at them and at the vertices.
+struct Topology {
+    edges: Vec<(usize, usize)>,
+    edge_index: HashMap<(usize, usize), usize>,
+    edge_faces: Vec<Vec<usize>>,
+    vertex_edges: Vec<Vec<usize>>,
+    vertex_faces: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    fn new(mesh: &ControlMesh) -> Self {
+        let mut topology = Self {
+            edges: vec![],
+            edge_index: HashMap::new(),
+            edge_faces: vec![],
+            vertex_edges: vec![vec![]; mesh.positions.len()],
+            vertex_faces: vec![vec![]; mesh.positions.len()],
+        };
+
+        for (f, face) in mesh.faces.iter().enumerate() {
+            for (i, v) in face.iter().enumerate() {
+                topology.vertex_faces[*v].push(f);
+                let next = face[(i + 1) % face.len()];
+                let key = edge_key(*v, next);
+                let e = match topology.edge_index.get(&key) {
+                    Some(e) => *e,
+                    None => {
+                        let e = topology.edges.len();
+                        topology.edges.push(key);
+                        topology.edge_index.insert(key, e);
+                        topology.edge_faces.push(vec![]);
+                        topology.vertex_edges[key.0].push(e);
+                        topology.vertex_edges[key.1].push(e);
+                        e
+                    }
+                };
+                topology.edge_faces[e].push(f);
+            }
+        }
+
+        topology
+    }
+
+    fn edge(&self, a: usize, b: usize) -> usize {
+        self.edge_index[&edge_key(a, b)]
+    }
+
+    fn other_end(&self, e: usize, v: usize) -> usize {
+        let (a, b) = self.edges[e];
+        if a == v {
+            b
+        } else {
+            a
+        }
+    }
+}
+
+// A repeated vertex would make a face meet itself along a zero-length edge.
+pub(crate) fn has_repeated_vertex(face: &[usize]) -> bool {
+    face.iter()
+        .enumerate()
+        .any(|(i, vertex)| face[i + 1..].contains(vertex))
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+fn average(points: impl Iterator<Item = Point3>) -> Point3 {
+    let (sum, count) = points.fold((Point3::default(), 0.0), |(sum, count), p| {
+        (sum + p, count + 1.0)
+    });
+    sum / count
+}
+
+// `smooth` moved towards `sharp` by the sharpness, reaching it at one.
+fn blend(smooth: Point3, sharp: Point3, sharpness: f64) -> Point3 {
+    if sharpness >= 1.0 {
+        sharp
+    } else if sharpness <= 0.0 {
+        smooth
+    } else {
+        (1.0 - sharpness) * smooth + sharpness * sharp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Point3;
+
+    use super::{ControlMesh, SubdivisionScheme};
+
+    fn cube() -> ControlMesh {
+        let positions = (0..8)
+            .map(|i| {
+                Point3::new(
+                    if i & 1 == 0 { -1.0 } else { 1.0 },
+                    if i & 2 == 0 { -1.0 } else { 1.0 },
+                    if i & 4 == 0 { -1.0 } else { 1.0 },
+                )
+            })
+            .collect();
+        let faces = vec![
+            vec![0, 2, 3, 1],
+            vec![4, 5, 7, 6],
+            vec![0, 1, 5, 4],
+            vec![2, 6, 7, 3],
+            vec![0, 4, 6, 2],
+            vec![1, 3, 7, 5],
+        ];
+        ControlMesh::new(positions, faces).unwrap()
+    }
+
+    fn max_norm(p: &Point3) -> f64 {
+        p.x().abs().max(p.y().abs()).max(p.z().abs())
+    }
+
+    #[test]
+    fn smooth_cube_shrinks_inside_its_cage() {
+        let mesh = cube().subdivide(SubdivisionScheme::CatmullClark, 3);
+        assert_eq!(mesh.faces().len(), 6 * 64);
+        assert!(mesh.positions().iter().all(|p| max_norm(p) < 1.0));
+
+        let mesh = cube().subdivide(SubdivisionScheme::Loop, 2);
+        assert_eq!(mesh.faces().len(), 12 * 16);
+        assert!(mesh.positions().iter().all(|p| max_norm(p) < 1.0));
+    }
+
+    #[test]
+    fn invalid_faces_are_rejected() {
+        let positions = cube().positions().to_vec();
+        assert!(ControlMesh::new(positions.clone(), vec![vec![]]).is_err());
+        assert!(ControlMesh::new(positions.clone(), vec![vec![0, 1]]).is_err());
+        assert!(ControlMesh::new(positions.clone(), vec![vec![0, 1, 8]]).is_err());
+        assert!(ControlMesh::new(positions, vec![vec![0, 1, 2, 1]]).is_err());
+    }
+
+    #[test]
+    fn sharp_creases_keep_the_cube() {
+        let mut cage = cube();
+        for face in cube().faces() {
+            for i in 0..4 {
+                cage.set_crease(face[i], face[(i + 1) % 4], f64::INFINITY);
+            }
+        }
+        let mesh = cage.subdivide(SubdivisionScheme::CatmullClark, 2);
+        assert!(mesh
+            .positions()
+            .iter()
+            .all(|p| (max_norm(p) - 1.0).abs() < 1e-12));
+    }
+}
@@ -0,0 +1,143 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hits::{
+        aabb::AABB,
+        hittable::{HitRecord, Hittable},
+    },
+    materials::Material,
+    ray::Ray,
+    vec3::{dot, Point3, Vec3},
+};
+
+use super::{
+    object_frame::{azimuth, invert_increasing, nearest_root, ObjectFrame},
+    polynomial::solve_quartic,
+};
+
+// A ring around `center` in the plane facing along `axis`: the surface at
+// `minor_radius` from the circle of `major_radius`. The surface coordinates
+// are the angles around the axis and around the tube.
+pub struct Torus {
+    frame: ObjectFrame,
+    major_radius: f64,
+    minor_radius: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Torus {
+    pub fn new(
+        center: Point3,
+        axis: Vec3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            frame: ObjectFrame::new(center, &axis),
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+
+    // The local normal, tangents and surface coordinates at `p`.
+    fn surface(&self, p: &Point3) -> ((Vec3, Vec3, Vec3), (f64, f64)) {
+        let rho = (p.x() * p.x() + p.y() * p.y()).sqrt();
+        let phi = azimuth(p);
+        let (cos_phi, sin_phi) = (phi.cos(), phi.sin());
+        let ring = Vec3::new(
+            self.major_radius * cos_phi,
+            self.major_radius * sin_phi,
+            0.0,
+        );
+        let mut theta = p.z().atan2(rho - self.major_radius);
+        if theta < 0.0 {
+            theta += 2.0 * PI;
+        }
+
+        let normal = *p - ring;
+        let dpdu = 2.0 * PI * Vec3::new(-p.y(), p.x(), 0.0);
+        let dpdv = 2.0
+            * PI
+            * self.minor_radius
+            * Vec3::new(-theta.sin() * cos_phi, -theta.sin() * sin_phi, theta.cos());
+        ((normal, dpdu, dpdv), (phi / (2.0 * PI), theta / (2.0 * PI)))
+    }
+}
+
+impl Hittable for Torus {
+    #[allow(unused_variables)]
+    fn bounding_box(&self, time: (f64, f64)) -> Option<AABB> {
+        let extent = self.major_radius + self.minor_radius;
+        Some(self.frame.bounding_box(
+            Point3::new(-extent, -extent, -self.minor_radius),
+            Point3::new(extent, extent, self.minor_radius),
+        ))
+    }
+
+    fn hit(&self, r: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+        let (o, d) = self.frame.to_local(r);
+        let length = d.len();
+        if length == 0.0 {
+            return None;
+        }
+
+        // The quartic is solved for a unit direction from the point closest
+        // to the center, in units of the torus' size, where it is well
+        // conditioned.
+        let d_unit = d / length;
+        let t_closest = -dot(&o, &d_unit);
+        let scale = 1.0 / (self.major_radius + self.minor_radius);
+        let start = (o + t_closest * d_unit) * scale;
+        let major = self.major_radius * scale;
+        let minor = self.minor_radius * scale;
+
+        // (|p|² + R² - r²)² = 4 R² (x² + y²)
+        let od = dot(&start, &d_unit);
+        let k = dot(&start, &start) + major * major - minor * minor;
+        let four_r2 = 4.0 * major * major;
+        let roots = solve_quartic([
+            k * k - four_r2 * (start.x() * start.x() + start.y() * start.y()),
+            4.0 * od * k - 2.0 * four_r2 * (start.x() * d_unit.x() + start.y() * d_unit.y()),
+            4.0 * od * od + 2.0 * k - four_r2 * (d_unit.x() * d_unit.x() + d_unit.y() * d_unit.y()),
+            4.0 * od,
+            1.0,
+        ]);
+        let roots: Vec<f64> = roots
+            .into_iter()
+            .map(|root| (t_closest + root / scale) / length)
+            .collect();
+        let t = nearest_root(&roots, interval, |_| true)?;
+
+        let (local, uv) = self.surface(&(o + t * d));
+        Some(self.frame.hit_record(r, t, local, uv, &self.material))
+    }
+
+    fn lights<'a>(&'a self, lights: &mut Vec<&'a dyn Hittable>) {
+        if self.material.is_emissive() {
+            lights.push(self);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn sample_surface(&self, u: (f64, f64), time: f64) -> Option<HitRecord> {
+        // The outside of the ring has more area than the inside: up to the
+        // angle θ around the tube it is R θ + r sin θ.
+        let (major, minor) = (self.major_radius, self.minor_radius);
+        let theta = invert_increasing(
+            |theta| major * theta + minor * theta.sin(),
+            2.0 * PI * major * u.0,
+            (0.0, 2.0 * PI),
+        );
+        let phi = 2.0 * PI * u.1;
+        let rho = major + minor * theta.cos();
+        let p = Point3::new(rho * phi.cos(), rho * phi.sin(), minor * theta.sin());
+        let (local, uv) = self.surface(&p);
+        Some(self.frame.surface_record(&p, local, uv, &self.material))
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * PI * PI * self.major_radius * self.minor_radius
+    }
+}
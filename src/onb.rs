@@ -0,0 +1,59 @@
+use crate::vec3::{cross, dot, unit_vector, Vec3};
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Onb {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Onb {
+    pub fn new(u: Vec3, v: Vec3, w: Vec3) -> Self {
+        Self { u, v, w }
+    }
+
+    pub fn build_from_w(n: &Vec3) -> Self {
+        let w = unit_vector(*n);
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = unit_vector(cross(&w, &a));
+        let u = cross(&w, &v);
+
+        Self { u, v, w }
+    }
+
+    pub fn build_from_w_and_tangent(n: &Vec3, tangent: &Vec3) -> Self {
+        let w = unit_vector(*n);
+        let projected = *tangent - dot(tangent, &w) * w;
+        if projected.near_zero() {
+            return Self::build_from_w(n);
+        }
+        let u = unit_vector(projected);
+        let v = cross(&w, &u);
+
+        Self { u, v, w }
+    }
+
+    pub fn u(&self) -> Vec3 {
+        self.u
+    }
+
+    pub fn v(&self) -> Vec3 {
+        self.v
+    }
+
+    pub fn w(&self) -> Vec3 {
+        self.w
+    }
+
+    pub fn to_world(&self, a: &Vec3) -> Vec3 {
+        a.x() * self.u + a.y() * self.v + a.z() * self.w
+    }
+
+    pub fn to_local(&self, a: &Vec3) -> Vec3 {
+        Vec3::new(dot(a, &self.u), dot(a, &self.v), dot(a, &self.w))
+    }
+}
@@ -0,0 +1,13 @@
+pub mod operations;
+pub mod primitives;
+
+use crate::{hits::aabb::AABB, vec3::Point3};
+
+// A signed distance bound: negative inside, positive outside, and never more
+// than the distance to the surface, so a ray can safely advance by it.
+pub trait Sdf: Send + Sync {
+    fn distance(&self, p: &Point3) -> f64;
+
+    // Encloses everywhere the distance is negative.
+    fn bounding_box(&self) -> AABB;
+}
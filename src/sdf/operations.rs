@@ -0,0 +1,209 @@
+use std::sync::Arc;
+
+use crate::{
+    hits::aabb::{surrounding_box, AABB},
+    noise::Noise,
+    vec3::{Point3, Vec3},
+};
+
+use super::Sdf;
+
+// Bound on how fast lattice noise changes per unit of its input.
+const NOISE_LIPSCHITZ: f64 = 2.5;
+
+// The union of two shapes, blended over a distance of about `smoothness`
+// where they meet. No smoothness gives the plain union.
+pub struct SmoothUnion {
+    a: Arc<dyn Sdf>,
+    b: Arc<dyn Sdf>,
+    smoothness: f64,
+}
+
+impl SmoothUnion {
+    pub fn new(a: Arc<dyn Sdf>, b: Arc<dyn Sdf>, smoothness: f64) -> Self {
+        Self { a, b, smoothness }
+    }
+}
+
+impl Sdf for SmoothUnion {
+    fn distance(&self, p: &Point3) -> f64 {
+        let a = self.a.distance(p);
+        let b = self.b.distance(p);
+        if self.smoothness <= 0.0 {
+            return a.min(b);
+        }
+        let h = (self.smoothness - (a - b).abs()).max(0.0) / self.smoothness;
+        a.min(b) - h * h * self.smoothness / 4.0
+    }
+
+    fn bounding_box(&self) -> AABB {
+        // Where the surfaces run close together the blend swells them by up
+        // to a quarter of the smoothness, also past their hull.
+        let hull = surrounding_box(self.a.bounding_box(), self.b.bounding_box());
+        let swell = self.smoothness.max(0.0) / 4.0;
+        let padding = Vec3::new(swell, swell, swell);
+        AABB::new(hull.min() - padding, hull.max() + padding)
+    }
+}
+
+// Twists a shape around the y axis by `rate` radians per unit of height.
+pub struct Twist {
+    sdf: Arc<dyn Sdf>,
+    rate: f64,
+    // The twist stretches distances by at most this much inside the bounds.
+    stretch: f64,
+}
+
+impl Twist {
+    pub fn new(sdf: Arc<dyn Sdf>, rate: f64) -> Self {
+        let radius = max_radius(&sdf.bounding_box());
+        Self {
+            sdf,
+            rate,
+            stretch: (1.0 + (rate * radius).powi(2)).sqrt(),
+        }
+    }
+}
+
+impl Sdf for Twist {
+    fn distance(&self, p: &Point3) -> f64 {
+        let angle = -self.rate * p.y();
+        let (sin, cos) = angle.sin_cos();
+        let untwisted = Point3::new(cos * p.x() - sin * p.z(), p.y(), sin * p.x() + cos * p.z());
+        self.sdf.distance(&untwisted) / self.stretch
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let inner = self.sdf.bounding_box();
+        let radius = max_radius(&inner);
+        AABB::new(
+            Point3::new(-radius, inner.min().y(), -radius),
+            Point3::new(radius, inner.max().y(), radius),
+        )
+    }
+}
+
+// The largest distance from the y axis within `bounds`.
+fn max_radius(bounds: &AABB) -> f64 {
+    let x = bounds.min().x().abs().max(bounds.max().x().abs());
+    let z = bounds.min().z().abs().max(bounds.max().z().abs());
+    (x * x + z * z).sqrt()
+}
+
+// Copies of a shape every `spacing` along each axis, `copies` to either side
+// of the original. Axes with no spacing aren't repeated. Each copy has to fit
+// in its cell for the distances to hold.
+pub struct Repetition {
+    sdf: Arc<dyn Sdf>,
+    spacing: Vec3,
+    copies: (u32, u32, u32),
+}
+
+impl Repetition {
+    pub fn new(sdf: Arc<dyn Sdf>, spacing: Vec3, copies: (u32, u32, u32)) -> Self {
+        Self {
+            sdf,
+            spacing,
+            copies,
+        }
+    }
+
+    fn copies(&self, axis: usize) -> f64 {
+        let copies = match axis {
+            0 => self.copies.0,
+            1 => self.copies.1,
+            _ => self.copies.2,
+        };
+        if self.spacing[axis] == 0.0 {
+            0.0
+        } else {
+            f64::from(copies)
+        }
+    }
+}
+
+impl Sdf for Repetition {
+    fn distance(&self, p: &Point3) -> f64 {
+        let mut local = *p;
+        for axis in 0..3 {
+            let copies = self.copies(axis);
+            if copies > 0.0 {
+                let cell = (p[axis] / self.spacing[axis])
+                    .round()
+                    .clamp(-copies, copies);
+                local[axis] -= cell * self.spacing[axis];
+            }
+        }
+        self.sdf.distance(&local)
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let inner = self.sdf.bounding_box();
+        let mut extent = Vec3::default();
+        for axis in 0..3 {
+            extent[axis] = self.copies(axis) * self.spacing[axis].abs();
+        }
+        AABB::new(inner.min() - extent, inner.max() + extent)
+    }
+}
+
+// Pushes the surface out and in by up to `amplitude` with noise sampled at
+// `scale` times the position.
+pub struct Displacement {
+    sdf: Arc<dyn Sdf>,
+    noise: Arc<dyn Noise>,
+    scale: f64,
+    amplitude: f64,
+}
+
+impl Displacement {
+    pub fn new(sdf: Arc<dyn Sdf>, noise: Arc<dyn Noise>, scale: f64, amplitude: f64) -> Self {
+        Self {
+            sdf,
+            noise,
+            scale,
+            amplitude,
+        }
+    }
+}
+
+impl Sdf for Displacement {
+    fn distance(&self, p: &Point3) -> f64 {
+        let displaced =
+            self.sdf.distance(p) - self.amplitude * self.noise.noise(&(self.scale * *p));
+        // Scaled down by how much the noise can steepen the field.
+        displaced / (1.0 + self.amplitude.abs() * self.scale * NOISE_LIPSCHITZ)
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let inner = self.sdf.bounding_box();
+        let extent = self.amplitude.abs();
+        let extent = Vec3::new(extent, extent, extent);
+        AABB::new(inner.min() - extent, inner.max() + extent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        sdf::{primitives::SphereSdf, Sdf},
+        vec3::Point3,
+    };
+
+    use super::SmoothUnion;
+
+    // Two overlapping spheres swell at their shared top, which must stay
+    // inside the bounds.
+    #[test]
+    fn smooth_union_bounds_cover_the_blend() {
+        let a = Arc::new(SphereSdf::new(Point3::new(-0.01, 0.0, 0.0), 1.0));
+        let b = Arc::new(SphereSdf::new(Point3::new(0.01, 0.0, 0.0), 1.0));
+        let union = SmoothUnion::new(a, b, 0.4);
+
+        let top = Point3::new(0.0, 1.05, 0.0);
+        assert!(union.distance(&top) < 0.0);
+        assert!(union.bounding_box().max().y() > top.y());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    hits::aabb::AABB,
+    vec3::{dot, Point3, Vec3},
+};
+
+use super::Sdf;
+
+pub struct SphereSdf {
+    center: Point3,
+    radius: f64,
+}
+
+impl SphereSdf {
+    pub fn new(center: Point3, radius: f64) -> Self {
+        Self { center, radius }
+    }
+}
+
+impl Sdf for SphereSdf {
+    fn distance(&self, p: &Point3) -> f64 {
+        (*p - self.center).len() - self.radius
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let extent = Vec3::new(self.radius, self.radius, self.radius);
+        AABB::new(self.center - extent, self.center + extent)
+    }
+}
+
+// An axis-aligned box reaching `half_size` from its center, with the edges
+// rounded by `rounding`.
+pub struct BoxSdf {
+    center: Point3,
+    half_size: Vec3,
+    rounding: f64,
+}
+
+impl BoxSdf {
+    pub fn new(center: Point3, half_size: Vec3, rounding: f64) -> Self {
+        Self {
+            center,
+            half_size,
+            rounding,
+        }
+    }
+}
+
+impl Sdf for BoxSdf {
+    fn distance(&self, p: &Point3) -> f64 {
+        let local = *p - self.center;
+        let q = Vec3::new(
+            local.x().abs() - self.half_size.x() + self.rounding,
+            local.y().abs() - self.half_size.y() + self.rounding,
+            local.z().abs() - self.half_size.z() + self.rounding,
+        );
+        let outside = Vec3::new(q.x().max(0.0), q.y().max(0.0), q.z().max(0.0)).len();
+        let inside = q.x().max(q.y()).max(q.z()).min(0.0);
+        outside + inside - self.rounding
+    }
+
+    fn bounding_box(&self) -> AABB {
+        AABB::new(self.center - self.half_size, self.center + self.half_size)
+    }
+}
+
+// A ring around `center` in the plane facing along the y axis.
+pub struct TorusSdf {
+    center: Point3,
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl TorusSdf {
+    pub fn new(center: Point3, major_radius: f64, minor_radius: f64) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Sdf for TorusSdf {
+    fn distance(&self, p: &Point3) -> f64 {
+        let local = *p - self.center;
+        let ring = (local.x() * local.x() + local.z() * local.z()).sqrt() - self.major_radius;
+        (ring * ring + local.y() * local.y()).sqrt() - self.minor_radius
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let extent = self.major_radius + self.minor_radius;
+        let extent = Vec3::new(extent, self.minor_radius, extent);
+        AABB::new(self.center - extent, self.center + extent)
+    }
+}
+
+// Everything within `radius` of the segment from `a` to `b`.
+pub struct CapsuleSdf {
+    a: Point3,
+    b: Point3,
+    radius: f64,
+}
+
+impl CapsuleSdf {
+    pub fn new(a: Point3, b: Point3, radius: f64) -> Self {
+        Self { a, b, radius }
+    }
+}
+
+impl Sdf for CapsuleSdf {
+    fn distance(&self, p: &Point3) -> f64 {
+        let pa = *p - self.a;
+        let ba = self.b - self.a;
+        let h = (dot(&pa, &ba) / dot(&ba, &ba)).clamp(0.0, 1.0);
+        (pa - h * ba).len() - self.radius
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let extent = Vec3::new(self.radius, self.radius, self.radius);
+        let mut min = self.a;
+        let mut max = self.a;
+        for axis in 0..3 {
+            min[axis] = min[axis].min(self.b[axis]);
+            max[axis] = max[axis].max(self.b[axis]);
+        }
+        AABB::new(min - extent, max + extent)
+    }
+}
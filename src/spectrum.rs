@@ -0,0 +1,268 @@
+pub mod cie;
+pub mod rgb_to_spectrum;
+
+use std::ops::{Add, AddAssign, Div, Index, Mul, MulAssign};
+
+use crate::{
+    hits::hittable::HitRecord,
+    materials::{bsdf::BsdfFlags, Material},
+    vec3::{Color, Vec3},
+};
+
+use self::cie::{color_matching, white_balance, xyz_to_linear_srgb, y_integral};
+
+pub const LAMBDA_MIN: f64 = 360.0;
+pub const LAMBDA_MAX: f64 = 830.0;
+pub const N_WAVELENGTHS: usize = 4;
+
+// The wavelengths, in nanometers, a spectral path carries. The first is the
+// hero wavelength; the others are spread at equal offsets across the range.
+#[derive(Debug, Clone, Copy)]
+pub struct SampledWavelengths {
+    lambda: [f64; N_WAVELENGTHS],
+    pdf: [f64; N_WAVELENGTHS],
+}
+
+impl SampledWavelengths {
+    pub fn sample_uniform(u: f64) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let mut lambda = [0.0; N_WAVELENGTHS];
+        lambda[0] = LAMBDA_MIN + u * range;
+        for i in 1..N_WAVELENGTHS {
+            lambda[i] = lambda[i - 1] + range / N_WAVELENGTHS as f64;
+            if lambda[i] > LAMBDA_MAX {
+                lambda[i] -= range;
+            }
+        }
+        Self {
+            lambda,
+            pdf: [1.0 / range; N_WAVELENGTHS],
+        }
+    }
+
+    // Importance samples the wavelengths the eye is most sensitive to, with
+    // the distribution from Radziszewski et al. (2009).
+    pub fn sample_visible(u: f64) -> Self {
+        let mut lambda = [0.0; N_WAVELENGTHS];
+        let mut pdf = [0.0; N_WAVELENGTHS];
+        for i in 0..N_WAVELENGTHS {
+            let u = (u + i as f64 / N_WAVELENGTHS as f64).fract();
+            lambda[i] = 538.0 - 138.888_889 * (0.856_910_62 - 1.827_501_97 * u).atanh();
+            pdf[i] = visible_wavelengths_pdf(lambda[i]);
+        }
+        Self { lambda, pdf }
+    }
+
+    pub fn lambda(&self, i: usize) -> f64 {
+        self.lambda[i]
+    }
+
+    pub fn pdf(&self, i: usize) -> f64 {
+        self.pdf[i]
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.lambda[0]
+    }
+
+    // Drops all but the hero wavelength, for wavelength dependent scattering
+    // such as dispersion where the others cannot follow the same direction.
+    pub fn terminate_secondary(&mut self) {
+        if self.secondary_terminated() {
+            return;
+        }
+        for pdf in self.pdf.iter_mut().skip(1) {
+            *pdf = 0.0;
+        }
+        self.pdf[0] /= N_WAVELENGTHS as f64;
+    }
+
+    pub fn secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|&pdf| pdf == 0.0)
+    }
+}
+
+fn visible_wavelengths_pdf(lambda: f64) -> f64 {
+    if !(LAMBDA_MIN..=LAMBDA_MAX).contains(&lambda) {
+        return 0.0;
+    }
+    0.003_939_804_2 / (0.0072 * (lambda - 538.0)).cosh().powi(2)
+}
+
+// Values of a spectral distribution at the wavelengths of a
+// `SampledWavelengths`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SampledSpectrum([f64; N_WAVELENGTHS]);
+
+impl SampledSpectrum {
+    pub fn new(values: [f64; N_WAVELENGTHS]) -> Self {
+        Self(values)
+    }
+
+    pub fn constant(value: f64) -> Self {
+        Self([value; N_WAVELENGTHS])
+    }
+
+    pub fn is_black(&self) -> bool {
+        self.0.iter().all(|&value| value == 0.0)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.0
+            .iter()
+            .fold(f64::NEG_INFINITY, |acc, &value| acc.max(value))
+    }
+
+    pub fn average(&self) -> f64 {
+        self.0.iter().sum::<f64>() / N_WAVELENGTHS as f64
+    }
+
+    // Monte Carlo estimate of the color of this radiance, in linear sRGB
+    // white balanced to the illuminant of `cie`.
+    pub fn to_rgb(&self, lambda: &SampledWavelengths) -> Color {
+        let mut xyz = Vec3::default();
+        for i in 0..N_WAVELENGTHS {
+            if lambda.pdf(i) > 0.0 {
+                xyz += color_matching(lambda.lambda(i)) * (self.0[i] / lambda.pdf(i));
+            }
+        }
+        let rgb = xyz_to_linear_srgb(&(xyz / (N_WAVELENGTHS as f64 * y_integral())));
+
+        let white = white_balance();
+        Color::new(
+            rgb.x() / white.x(),
+            rgb.y() / white.y(),
+            rgb.z() / white.z(),
+        )
+    }
+}
+
+impl Index<usize> for SampledSpectrum {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl Add for SampledSpectrum {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(std::array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl AddAssign for SampledSpectrum {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Mul for SampledSpectrum {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(std::array::from_fn(|i| self.0[i] * rhs.0[i]))
+    }
+}
+
+impl Mul<f64> for SampledSpectrum {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self(self.0.map(|value| value * rhs))
+    }
+}
+
+impl MulAssign for SampledSpectrum {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div<f64> for SampledSpectrum {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Self(self.0.map(|value| value / rhs))
+    }
+}
+
+// What a path carries through the integrator: an RGB `Color`, or radiance at
+// a few sampled wavelengths in spectral mode. RGB inputs are converted on the
+// way in.
+pub trait PathRadiance:
+    Copy + Default + Add<Output = Self> + Mul<Output = Self> + Mul<f64, Output = Self>
+{
+    type Wavelengths;
+
+    fn from_reflectance(rgb: Color, lambda: &Self::Wavelengths) -> Self;
+
+    fn from_illuminant(rgb: Color, lambda: &Self::Wavelengths) -> Self;
+
+    fn max_value(&self) -> f64;
+
+    fn sample(
+        material: &dyn Material,
+        hitrecord: &HitRecord,
+        wo: &Vec3,
+        u: (f64, f64),
+        lambda: &mut Self::Wavelengths,
+    ) -> Option<(Vec3, Self, f64, BsdfFlags)>;
+}
+
+impl PathRadiance for Color {
+    type Wavelengths = ();
+
+    #[allow(unused_variables)]
+    fn from_reflectance(rgb: Color, lambda: &()) -> Self {
+        rgb
+    }
+
+    #[allow(unused_variables)]
+    fn from_illuminant(rgb: Color, lambda: &()) -> Self {
+        rgb
+    }
+
+    fn max_value(&self) -> f64 {
+        self.x().max(self.y()).max(self.z())
+    }
+
+    #[allow(unused_variables)]
+    fn sample(
+        material: &dyn Material,
+        hitrecord: &HitRecord,
+        wo: &Vec3,
+        u: (f64, f64),
+        lambda: &mut (),
+    ) -> Option<(Vec3, Self, f64, BsdfFlags)> {
+        material.sample(hitrecord, wo, u)
+    }
+}
+
+impl PathRadiance for SampledSpectrum {
+    type Wavelengths = SampledWavelengths;
+
+    fn from_reflectance(rgb: Color, lambda: &SampledWavelengths) -> Self {
+        rgb_to_spectrum::reflectance(rgb, lambda)
+    }
+
+    fn from_illuminant(rgb: Color, lambda: &SampledWavelengths) -> Self {
+        rgb_to_spectrum::illuminant_spectrum(rgb, lambda)
+    }
+
+    fn max_value(&self) -> f64 {
+        self.max()
+    }
+
+    fn sample(
+        material: &dyn Material,
+        hitrecord: &HitRecord,
+        wo: &Vec3,
+        u: (f64, f64),
+        lambda: &mut SampledWavelengths,
+    ) -> Option<(Vec3, Self, f64, BsdfFlags)> {
+        material.sample_spectral(hitrecord, wo, u, lambda)
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::OnceLock;
+
+use crate::vec3::{Color, Vec3};
+
+use super::{LAMBDA_MAX, LAMBDA_MIN};
+
+// Correlated color temperature of the illuminant lights and reflectances are
+// defined against. A blackbody stands in for D65.
+const WHITE_TEMPERATURE: f64 = 6504.0;
+
+fn piecewise_gaussian(lambda: f64, mean: f64, sigma_below: f64, sigma_above: f64) -> f64 {
+    let sigma = if lambda < mean {
+        sigma_below
+    } else {
+        sigma_above
+    };
+    let t = (lambda - mean) / sigma;
+    (-0.5 * t * t).exp()
+}
+
+// CIE 1931 2° color matching functions, after the multi-lobe fit by Wyman,
+// Sloan and Shirley (2013). `lambda` is in nanometers.
+pub fn color_matching(lambda: f64) -> Vec3 {
+    let x = 1.056 * piecewise_gaussian(lambda, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(lambda, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(lambda, 501.1, 20.4, 26.2);
+    let y = 0.821 * piecewise_gaussian(lambda, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(lambda, 530.9, 16.3, 31.1);
+    let z = 1.217 * piecewise_gaussian(lambda, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(lambda, 459.0, 26.0, 13.8);
+    Vec3::new(x, y, z)
+}
+
+// Planck's law for a wavelength in nanometers, up to a constant factor.
+pub fn blackbody(lambda: f64, temperature: f64) -> f64 {
+    const C: f64 = 299_792_458.0;
+    const H: f64 = 6.626_070_15e-34;
+    const KB: f64 = 1.380_649e-23;
+
+    let l = lambda * 1e-9;
+    2.0 * H * C * C / (l.powi(5) * ((H * C / (l * KB * temperature)).exp() - 1.0))
+}
+
+// Integrates `f` over the sampled wavelength range in 1 nm steps.
+pub fn integrate(f: impl Fn(f64) -> f64) -> f64 {
+    let steps = (LAMBDA_MAX - LAMBDA_MIN) as usize;
+    (0..=steps).map(|i| f(LAMBDA_MIN + i as f64)).sum()
+}
+
+pub fn y_integral() -> f64 {
+    static Y_INTEGRAL: OnceLock<f64> = OnceLock::new();
+    *Y_INTEGRAL.get_or_init(|| integrate(|lambda| color_matching(lambda).y()))
+}
+
+// The white illuminant, scaled so that it has unit luminance.
+pub fn illuminant(lambda: f64) -> f64 {
+    static SCALE: OnceLock<f64> = OnceLock::new();
+    let scale = SCALE.get_or_init(|| {
+        y_integral()
+            / integrate(|lambda| blackbody(lambda, WHITE_TEMPERATURE) * color_matching(lambda).y())
+    });
+    scale * blackbody(lambda, WHITE_TEMPERATURE)
+}
+
+pub fn xyz_to_linear_srgb(xyz: &Vec3) -> Color {
+    Color::new(
+        3.240_454_2 * xyz.x() - 1.537_138_5 * xyz.y() - 0.498_531_4 * xyz.z(),
+        -0.969_266 * xyz.x() + 1.876_010_8 * xyz.y() + 0.041_556 * xyz.z(),
+        0.055_643_4 * xyz.x() - 0.204_025_9 * xyz.y() + 1.057_225_2 * xyz.z(),
+    )
+}
+
+// Linear sRGB of the illuminant, divided out at the film so that it maps to
+// exactly (1, 1, 1).
+pub fn white_balance() -> Color {
+    static WHITE: OnceLock<[f64; 3]> = OnceLock::new();
+    let white = WHITE.get_or_init(|| {
+        let xyz = Vec3::new(
+            integrate(|lambda| illuminant(lambda) * color_matching(lambda).x()),
+            integrate(|lambda| illuminant(lambda) * color_matching(lambda).y()),
+            integrate(|lambda| illuminant(lambda) * color_matching(lambda).z()),
+        ) / y_integral();
+        let rgb = xyz_to_linear_srgb(&xyz);
+        [rgb.x(), rgb.y(), rgb.z()]
+    });
+    Color::new(white[0], white[1], white[2])
+}
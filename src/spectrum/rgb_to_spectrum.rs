@@ -0,0 +1,352 @@
+use std::sync::OnceLock;
+
+use crate::vec3::Color;
+
+use super::{
+    cie::{color_matching, illuminant, white_balance, xyz_to_linear_srgb, y_integral},
+    SampledSpectrum, SampledWavelengths, LAMBDA_MAX, LAMBDA_MIN, N_WAVELENGTHS,
+};
+
+// RGB to spectrum upsampling after Jakob and Hanika (2019): every color is
+// represented by a smooth spectrum sigmoid(c0 t^2 + c1 t + c2), with t the
+// wavelength normalized to [0, 1]. The coefficients are fitted on a grid
+// once, on first use, and interpolated in between.
+
+const RESOLUTION: usize = 16;
+// Wavelength spacing of the fit's quadrature, in nanometers.
+const FIT_STEP: f64 = 5.0;
+const FIT_ITERATIONS: usize = 100;
+
+pub fn reflectance(rgb: Color, lambda: &SampledWavelengths) -> SampledSpectrum {
+    let rgb = Color::new(
+        rgb.x().clamp(0.0, 1.0),
+        rgb.y().clamp(0.0, 1.0),
+        rgb.z().clamp(0.0, 1.0),
+    );
+    if rgb.x() == rgb.y() && rgb.y() == rgb.z() {
+        return SampledSpectrum::constant(rgb.x());
+    }
+
+    let coefficients = table().lookup(&rgb);
+    let mut values = [0.0; N_WAVELENGTHS];
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = sigmoid_polynomial(&coefficients, normalize(lambda.lambda(i)));
+    }
+    SampledSpectrum::new(values)
+}
+
+// For values above one, e.g. emission. The color is scaled into the lower
+// half of the gamut where the fitted spectra are smooth.
+pub fn unbounded(rgb: Color, lambda: &SampledWavelengths) -> SampledSpectrum {
+    let max = rgb.x().max(rgb.y()).max(rgb.z());
+    if max <= 0.0 {
+        return SampledSpectrum::default();
+    }
+    let scale = 2.0 * max;
+    reflectance(rgb / scale, lambda) * scale
+}
+
+pub fn illuminant_spectrum(rgb: Color, lambda: &SampledWavelengths) -> SampledSpectrum {
+    let mut white = [0.0; N_WAVELENGTHS];
+    for (i, value) in white.iter_mut().enumerate() {
+        *value = illuminant(lambda.lambda(i));
+    }
+    unbounded(rgb, lambda) * SampledSpectrum::new(white)
+}
+
+fn normalize(lambda: f64) -> f64 {
+    (lambda - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN)
+}
+
+fn sigmoid(x: f64) -> f64 {
+    if x.is_infinite() {
+        return if x > 0.0 { 1.0 } else { 0.0 };
+    }
+    0.5 + x / (2.0 * (1.0 + x * x).sqrt())
+}
+
+fn sigmoid_polynomial(c: &[f64; 3], t: f64) -> f64 {
+    sigmoid((c[0] * t + c[1]) * t + c[2])
+}
+
+fn smoothstep(x: f64) -> f64 {
+    x * x * (3.0 - 2.0 * x)
+}
+
+struct RgbToSpectrumTable {
+    // Grid positions of the largest channel, denser near zero and one.
+    z_nodes: Vec<f64>,
+    // Indexed by largest channel, then z, y and x, where x and y are the
+    // following channels divided by the largest.
+    coefficients: Vec<[f64; 3]>,
+}
+
+fn table() -> &'static RgbToSpectrumTable {
+    static TABLE: OnceLock<RgbToSpectrumTable> = OnceLock::new();
+    TABLE.get_or_init(RgbToSpectrumTable::fit)
+}
+
+impl RgbToSpectrumTable {
+    fn fit() -> Self {
+        let fitter = Fitter::new();
+        let z_nodes: Vec<f64> = (0..RESOLUTION)
+            .map(|k| smoothstep(smoothstep(k as f64 / (RESOLUTION - 1) as f64)))
+            .collect();
+        let mut coefficients = vec![[0.0; 3]; 3 * RESOLUTION * RESOLUTION * RESOLUTION];
+
+        for channel in 0..3 {
+            for y in 0..RESOLUTION {
+                for x in 0..RESOLUTION {
+                    let fx = x as f64 / (RESOLUTION - 1) as f64;
+                    let fy = y as f64 / (RESOLUTION - 1) as f64;
+
+                    let target = |z: usize| {
+                        let z_value = z_nodes[z].max(1e-4);
+                        let mut rgb = [0.0; 3];
+                        rgb[channel] = z_value;
+                        rgb[(channel + 1) % 3] = fx * z_value;
+                        rgb[(channel + 2) % 3] = fy * z_value;
+                        rgb
+                    };
+
+                    // Fits are warm started from their neighbor, walking
+                    // outward from a mid gray where the fit is easy.
+                    let start = RESOLUTION / 5;
+                    let mut c = [0.0; 3];
+                    for z in start..RESOLUTION {
+                        c = fitter.fit(&target(z), c);
+                        coefficients[Self::index(channel, z, y, x)] = c;
+                    }
+                    let mut c = coefficients[Self::index(channel, start, y, x)];
+                    for z in (0..start).rev() {
+                        c = fitter.fit(&target(z), c);
+                        coefficients[Self::index(channel, z, y, x)] = c;
+                    }
+                }
+            }
+        }
+
+        Self {
+            z_nodes,
+            coefficients,
+        }
+    }
+
+    fn index(channel: usize, z: usize, y: usize, x: usize) -> usize {
+        ((channel * RESOLUTION + z) * RESOLUTION + y) * RESOLUTION + x
+    }
+
+    fn lookup(&self, rgb: &Color) -> [f64; 3] {
+        let values = [rgb.x(), rgb.y(), rgb.z()];
+        let channel = if values[0] >= values[1] && values[0] >= values[2] {
+            0
+        } else if values[1] >= values[2] {
+            1
+        } else {
+            2
+        };
+        let z = values[channel];
+        let scale = (RESOLUTION - 1) as f64 / z;
+        let x = values[(channel + 1) % 3] * scale;
+        let y = values[(channel + 2) % 3] * scale;
+
+        let xi = (x as usize).min(RESOLUTION - 2);
+        let yi = (y as usize).min(RESOLUTION - 2);
+        let zi = self
+            .z_nodes
+            .partition_point(|&node| node <= z)
+            .clamp(1, RESOLUTION - 1)
+            - 1;
+        let dx = x - xi as f64;
+        let dy = y - yi as f64;
+        let dz = (z - self.z_nodes[zi]) / (self.z_nodes[zi + 1] - self.z_nodes[zi]);
+
+        let mut result = [0.0; 3];
+        for (k, wz) in [(0, 1.0 - dz), (1, dz)] {
+            for (j, wy) in [(0, 1.0 - dy), (1, dy)] {
+                for (i, wx) in [(0, 1.0 - dx), (1, dx)] {
+                    let c = &self.coefficients[Self::index(channel, zi + k, yi + j, xi + i)];
+                    for n in 0..3 {
+                        result[n] += wx * wy * wz * c[n];
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+// Gauss-Newton solver for the coefficients reproducing a linear sRGB color
+// under the white illuminant.
+struct Fitter {
+    // Normalized wavelength of each quadrature point and its contribution to
+    // each RGB channel.
+    points: Vec<(f64, [f64; 3])>,
+}
+
+impl Fitter {
+    fn new() -> Self {
+        let white = white_balance();
+        let steps = ((LAMBDA_MAX - LAMBDA_MIN) / FIT_STEP) as usize;
+        let points = (0..=steps)
+            .map(|i| {
+                let lambda = LAMBDA_MIN + i as f64 * FIT_STEP;
+                let xyz = color_matching(lambda) * (illuminant(lambda) * FIT_STEP / y_integral());
+                let rgb = xyz_to_linear_srgb(&xyz);
+                (
+                    normalize(lambda),
+                    [
+                        rgb.x() / white.x(),
+                        rgb.y() / white.y(),
+                        rgb.z() / white.z(),
+                    ],
+                )
+            })
+            .collect();
+        Self { points }
+    }
+
+    // Residual of the color reproduced by `c` and its Jacobian.
+    fn evaluate(&self, target: &[f64; 3], c: &[f64; 3]) -> ([f64; 3], [[f64; 3]; 3]) {
+        let mut residual = [-target[0], -target[1], -target[2]];
+        let mut jacobian = [[0.0; 3]; 3];
+
+        for (t, weights) in &self.points {
+            let x = (c[0] * t + c[1]) * t + c[2];
+            let s = sigmoid(x);
+            let ds = 0.5 / (1.0 + x * x).powf(1.5);
+            let dx = [t * t, *t, 1.0];
+            for row in 0..3 {
+                residual[row] += weights[row] * s;
+                for column in 0..3 {
+                    jacobian[row][column] += weights[row] * ds * dx[column];
+                }
+            }
+        }
+
+        (residual, jacobian)
+    }
+
+    // Levenberg-Marquardt iterations starting from `c`. Plain Gauss-Newton
+    // diverges for dark and saturated colors.
+    fn fit(&self, target: &[f64; 3], mut c: [f64; 3]) -> [f64; 3] {
+        let error = |r: &[f64; 3]| r.iter().map(|x| x * x).sum::<f64>();
+        let (mut residual, mut jacobian) = self.evaluate(target, &c);
+        let mut damping = 1e-3;
+
+        for _ in 0..FIT_ITERATIONS {
+            if error(&residual) < 1e-14 || damping > 1e12 {
+                break;
+            }
+
+            let mut a = [[0.0; 3]; 3];
+            let mut b = [0.0; 3];
+            for i in 0..3 {
+                for row in 0..3 {
+                    b[i] += jacobian[row][i] * residual[row];
+                    for k in 0..3 {
+                        a[i][k] += jacobian[row][i] * jacobian[row][k];
+                    }
+                }
+            }
+            for (i, row) in a.iter_mut().enumerate() {
+                row[i] += damping * row[i].max(1e-12);
+            }
+
+            let step = match solve(&a, &b) {
+                Some(step) if step.iter().all(|x| x.is_finite()) => step,
+                _ => {
+                    damping *= 10.0;
+                    continue;
+                }
+            };
+            let candidate = [c[0] - step[0], c[1] - step[1], c[2] - step[2]];
+            let (candidate_residual, candidate_jacobian) = self.evaluate(target, &candidate);
+
+            if error(&candidate_residual) < error(&residual) {
+                c = candidate;
+                residual = candidate_residual;
+                jacobian = candidate_jacobian;
+                damping *= 0.3;
+            } else {
+                damping *= 10.0;
+            }
+        }
+        c
+    }
+}
+
+// Solves the 3x3 system `a x = b` with Cramer's rule.
+fn solve(a: &[[f64; 3]; 3], b: &[f64; 3]) -> Option<[f64; 3]> {
+    let det = |m: &[[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let d = det(a);
+    if d == 0.0 {
+        return None;
+    }
+
+    let mut x = [0.0; 3];
+    for (column, value) in x.iter_mut().enumerate() {
+        let mut m = *a;
+        for row in 0..3 {
+            m[row][column] = b[row];
+        }
+        *value = det(&m) / d;
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{spectrum::SampledWavelengths, vec3::Color};
+
+    use super::{illuminant_spectrum, reflectance, sigmoid_polynomial, table, Fitter};
+
+    #[test]
+    fn upsampled_colors_round_trip() {
+        let fitter = Fitter::new();
+        let steps = 10;
+        for i in 0..=steps {
+            for j in 0..=steps {
+                for k in 0..=steps {
+                    // Grays bypass the table.
+                    if i == j && j == k {
+                        continue;
+                    }
+                    let rgb = Color::new(i as f64, j as f64, k as f64) / steps as f64;
+                    let c = table().lookup(&rgb);
+
+                    let mut result = [0.0; 3];
+                    for (t, weights) in &fitter.points {
+                        for n in 0..3 {
+                            result[n] += weights[n] * sigmoid_polynomial(&c, *t);
+                        }
+                    }
+                    // Interpolating between table entries costs some accuracy
+                    // on saturated colors.
+                    for n in 0..3 {
+                        assert!((result[n] - rgb[n]).abs() < 0.05, "{rgb:?} -> {result:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn white_stays_white() {
+        let mut sum = Color::default();
+        for i in 0..1000 {
+            let lambda = SampledWavelengths::sample_visible((i as f64 + 0.5) / 1000.0);
+            let white = reflectance(Color::new(1.0, 1.0, 1.0), &lambda);
+            assert_eq!(white.average(), 1.0);
+            sum += illuminant_spectrum(Color::new(1.0, 1.0, 1.0), &lambda).to_rgb(&lambda);
+        }
+        let average = sum / 1000.0;
+        for n in 0..3 {
+            assert!((average[n] - 1.0).abs() < 0.02, "{average:?}");
+        }
+    }
+}
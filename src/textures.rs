@@ -1,10 +1,33 @@
 pub mod checker_texture;
+pub mod gradient;
 pub mod image_texture;
+pub mod nodes;
 pub mod noise_texture;
+pub mod procedural;
+pub mod radiance_hdr;
+pub mod scalar;
 pub mod solid_color;
+pub mod triplanar;
+pub mod uv_transform;
 
-use crate::vec3::{Color, Point3};
+use crate::vec3::{Color, Point3, Vec3};
 
-pub trait Texture {
+pub trait Texture: Send + Sync {
     fn value(&self, uv: (f64, f64), p: &Point3) -> Color;
+
+    // Textures that project along the surface normal override this; everything
+    // else ignores the normal.
+    #[allow(unused_variables)]
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> Color {
+        self.value(uv, p)
+    }
+}
+
+pub trait ScalarTexture: Send + Sync {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64;
+
+    #[allow(unused_variables)]
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> f64 {
+        self.value(uv, p)
+    }
 }
@@ -1,23 +1,23 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::vec3::{Color, Point3};
+use crate::vec3::{Color, Point3, Vec3};
 
 use super::{solid_color::SolidColor, Texture};
 
 pub struct CheckerTexture {
-    odd: Rc<dyn Texture>,
-    even: Rc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+    even: Arc<dyn Texture>,
 }
 
 impl CheckerTexture {
-    pub fn new(odd: Rc<dyn Texture>, even: Rc<dyn Texture>) -> Self {
+    pub fn new(odd: Arc<dyn Texture>, even: Arc<dyn Texture>) -> Self {
         Self { odd, even }
     }
 
     pub fn new_from_color(c1: Color, c2: Color) -> Self {
         Self::new(
-            Rc::new(SolidColor::new_from_color(c1)),
-            Rc::new(SolidColor::new_from_color(c2)),
+            Arc::new(SolidColor::new_from_color(c1)),
+            Arc::new(SolidColor::new_from_color(c2)),
         )
     }
 }
@@ -31,4 +31,13 @@ impl Texture for CheckerTexture {
             self.even.value(uv, p)
         }
     }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> Color {
+        let sines = (10.0 * p.x()).sin() * (10.0 * p.y()).sin() * (10.0 * p.z()).sin();
+        if sines < 0.0 {
+            self.odd.value_with_normal(uv, p, normal)
+        } else {
+            self.even.value_with_normal(uv, p, normal)
+        }
+    }
 }
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use crate::vec3::{dot, Color, Point3, Vec3};
+
+use super::{ScalarTexture, Texture};
+
+// Goes linearly from 0 at `start` to 1 at `end`, measured along the line
+// between them and clamped outside.
+pub struct LinearGradient {
+    start: Point3,
+    direction: Vec3,
+}
+
+impl LinearGradient {
+    // A gradient of zero length stays at 0, the value at `start`.
+    pub fn new(start: Point3, end: Point3) -> Self {
+        let direction = end - start;
+        let length_squared = direction.len_squared();
+        Self {
+            start,
+            direction: if length_squared > 0.0 {
+                direction / length_squared
+            } else {
+                Vec3::default()
+            },
+        }
+    }
+}
+
+impl ScalarTexture for LinearGradient {
+    #[allow(unused_variables)]
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        dot(&(*p - self.start), &self.direction).clamp(0.0, 1.0)
+    }
+}
+
+// Maps a scalar input to a color by linearly interpolating between stops,
+// given as (position, color) pairs.
+pub struct ColorRamp {
+    input: Arc<dyn ScalarTexture>,
+    stops: Vec<(f64, Color)>,
+}
+
+impl ColorRamp {
+    pub fn new(input: Arc<dyn ScalarTexture>, stops: Vec<(f64, Color)>) -> Self {
+        let mut stops = stops;
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { input, stops }
+    }
+
+    pub fn lookup(&self, x: f64) -> Color {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Color::default(),
+        };
+        if x <= first.0 {
+            return first.1;
+        }
+        if x >= last.0 {
+            return last.1;
+        }
+
+        for pair in self.stops.windows(2) {
+            let ((x0, c0), (x1, c1)) = (pair[0], pair[1]);
+            if x <= x1 {
+                if x1 == x0 {
+                    return c1;
+                }
+                let t = (x - x0) / (x1 - x0);
+                return (1.0 - t) * c0 + t * c1;
+            }
+        }
+
+        last.1
+    }
+}
+
+impl Texture for ColorRamp {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.lookup(self.input.value(uv, p))
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> Color {
+        self.lookup(self.input.value_with_normal(uv, p, normal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        textures::ScalarTexture,
+        vec3::{Point3, Vec3},
+    };
+
+    use super::LinearGradient;
+
+    #[test]
+    fn linear_gradient() {
+        let start = Point3::new(1.0, 0.0, 0.0);
+        let gradient = LinearGradient::new(start, Point3::new(3.0, 0.0, 0.0));
+        assert_eq!(gradient.value((0.0, 0.0), &Point3::new(2.0, 5.0, 0.0)), 0.5);
+        assert_eq!(gradient.value((0.0, 0.0), &Point3::new(4.0, 0.0, 0.0)), 1.0);
+
+        let empty = LinearGradient::new(start, start);
+        let p = start + Vec3::new(0.5, 0.5, 0.5);
+        assert_eq!(empty.value((0.0, 0.0), &p), 0.0);
+    }
+}
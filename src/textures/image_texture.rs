@@ -1,78 +1,392 @@
-use std::path::Path;
+use std::{error::Error, fmt, io, path::Path, sync::Arc};
 
-use load_image::export::rgb::RGB;
-use load_image::ImageData::RGB8;
+use load_image::ImageData;
 
-use crate::{
-    clamp,
-    vec3::{self, Color},
-};
+use crate::vec3::{self, Color, Point3};
 
-use super::Texture;
+use super::{radiance_hdr, ScalarTexture, Texture};
 
-pub struct ImageTexture {
-    data: Vec<RGB<u8>>,
+#[derive(Debug)]
+pub enum ImageTextureError {
+    Io(io::Error),
+    Decode(load_image::Error),
+    Format(String),
+}
+
+impl fmt::Display for ImageTextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageTextureError::Io(error) => write!(f, "could not read image: {error}"),
+            ImageTextureError::Decode(error) => write!(f, "could not decode image: {error}"),
+            ImageTextureError::Format(message) => write!(f, "invalid image: {message}"),
+        }
+    }
+}
+
+impl Error for ImageTextureError {}
+
+impl From<io::Error> for ImageTextureError {
+    fn from(error: io::Error) -> Self {
+        ImageTextureError::Io(error)
+    }
+}
+
+impl From<load_image::Error> for ImageTextureError {
+    fn from(error: load_image::Error) -> Self {
+        ImageTextureError::Decode(error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    Mirror,
+    Clamp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+    // Blurs every lookup to a fixed footprint of the given size in uv space,
+    // blending between box-downsampled copies of the image. The footprint is
+    // not derived from how large the lookup is on screen, so this is not
+    // mipmapping; it suits textures that should always look soft.
+    Prefiltered(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    Linear,
+    Srgb,
+}
+
+struct Level {
+    data: Vec<[f32; 4]>,
     width: usize,
     height: usize,
 }
 
+impl Level {
+    fn texel(&self, x: i64, y: i64, wrap_modes: (WrapMode, WrapMode)) -> [f32; 4] {
+        let i = wrap(x, self.width, wrap_modes.0);
+        let j = wrap(y, self.height, wrap_modes.1);
+        self.data[j * self.width + i]
+    }
+
+    fn downsample(&self) -> Level {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut data = Vec::with_capacity(width * height);
+
+        for j in 0..height {
+            for i in 0..width {
+                let mut texel = [0.0; 4];
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let x = (2 * i + dx).min(self.width - 1);
+                    let y = (2 * j + dy).min(self.height - 1);
+                    let source = self.data[y * self.width + x];
+                    for c in 0..4 {
+                        texel[c] += 0.25 * source[c];
+                    }
+                }
+                data.push(texel);
+            }
+        }
+
+        Level {
+            data,
+            width,
+            height,
+        }
+    }
+}
+
+pub struct ImageTexture {
+    levels: Vec<Level>,
+    // Along s and along t.
+    wrap_modes: (WrapMode, WrapMode),
+    filter: Filter,
+}
+
 impl ImageTexture {
-    pub fn new(image_path: &str) -> Self {
+    pub fn new(image_path: &str) -> Result<Self, ImageTextureError> {
+        Self::new_with_options(image_path, WrapMode::Clamp, Filter::Bilinear, None)
+    }
+
+    // Without an explicit color space, 8 and 16 bit images are decoded as sRGB
+    // and HDR images as linear.
+    pub fn new_with_options(
+        image_path: &str,
+        wrap_mode: WrapMode,
+        filter: Filter,
+        color_space: Option<ColorSpace>,
+    ) -> Result<Self, ImageTextureError> {
         let path = Path::new(image_path);
-        let image = load_image::load_path(path).unwrap();
 
-        let data = if let RGB8(vector) = image.bitmap {
-            vector
+        let (data, width, height) = if radiance_hdr::is_hdr(path)? {
+            let (data, width, height) = radiance_hdr::load(path)?;
+            let data = match color_space {
+                Some(ColorSpace::Srgb) => data.into_iter().map(srgb_texel_to_linear).collect(),
+                _ => data,
+            };
+            (data, width, height)
         } else {
-            panic!()
+            let image = load_image::load_path(path)?;
+            let data = decode(image.bitmap);
+            let data = match color_space.unwrap_or(ColorSpace::Srgb) {
+                ColorSpace::Srgb => data.into_iter().map(srgb_texel_to_linear).collect(),
+                ColorSpace::Linear => data,
+            };
+            (data, image.width, image.height)
         };
 
-        Self {
+        Self::new_from_data(data, width, height, wrap_mode, filter)
+    }
+
+    pub fn new_from_data(
+        data: Vec<[f32; 4]>,
+        width: usize,
+        height: usize,
+        wrap_mode: WrapMode,
+        filter: Filter,
+    ) -> Result<Self, ImageTextureError> {
+        Self::new_from_data_with_wrap_modes(data, width, height, (wrap_mode, wrap_mode), filter)
+    }
+
+    // Wraps the horizontal and the vertical image coordinate separately.
+    pub fn new_from_data_with_wrap_modes(
+        data: Vec<[f32; 4]>,
+        width: usize,
+        height: usize,
+        wrap_modes: (WrapMode, WrapMode),
+        filter: Filter,
+    ) -> Result<Self, ImageTextureError> {
+        if width == 0 || height == 0 || data.len() != width * height {
+            return Err(ImageTextureError::Format(format!(
+                "expected {}x{} texels, got {}",
+                width,
+                height,
+                data.len()
+            )));
+        }
+
+        let mut levels = vec![Level {
             data,
-            width: image.width,
-            height: image.height,
+            width,
+            height,
+        }];
+        if let Filter::Prefiltered(_) = filter {
+            while let Some(last) = levels.last() {
+                if last.width == 1 && last.height == 1 {
+                    break;
+                }
+                let next = last.downsample();
+                levels.push(next);
+            }
+        }
+
+        Ok(Self {
+            levels,
+            wrap_modes,
+            filter,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.levels[0].width
+    }
+
+    pub fn height(&self) -> usize {
+        self.levels[0].height
+    }
+
+    pub fn alpha(&self, uv: (f64, f64)) -> f64 {
+        f64::from(self.lookup(uv)[3])
+    }
+
+    fn lookup(&self, uv: (f64, f64)) -> [f32; 4] {
+        // Image rows are stored top to bottom while v points up.
+        let (s, t) = (uv.0, 1.0 - uv.1);
+
+        match self.filter {
+            Filter::Nearest => self.nearest(0, s, t),
+            Filter::Bilinear => self.bilinear(0, s, t),
+            Filter::Prefiltered(footprint) => {
+                let texels = footprint * self.width().max(self.height()) as f64;
+                let lod = texels.max(1.0).log2().min((self.levels.len() - 1) as f64);
+                let level = lod.floor() as usize;
+                if level + 1 >= self.levels.len() {
+                    return self.bilinear(level, s, t);
+                }
+
+                let weight = (lod - level as f64) as f32;
+                let lower = self.bilinear(level, s, t);
+                let upper = self.bilinear(level + 1, s, t);
+                let mut result = [0.0; 4];
+                for c in 0..4 {
+                    result[c] = (1.0 - weight) * lower[c] + weight * upper[c];
+                }
+                result
+            }
+        }
+    }
+
+    fn nearest(&self, level: usize, s: f64, t: f64) -> [f32; 4] {
+        let level = &self.levels[level];
+        let x = (s * level.width as f64).floor() as i64;
+        let y = (t * level.height as f64).floor() as i64;
+        level.texel(x, y, self.wrap_modes)
+    }
+
+    fn bilinear(&self, level: usize, s: f64, t: f64) -> [f32; 4] {
+        let level = &self.levels[level];
+        let x = s * level.width as f64 - 0.5;
+        let y = t * level.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (dx, dy) = ((x - x0) as f32, (y - y0) as f32);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let t00 = level.texel(x0, y0, self.wrap_modes);
+        let t10 = level.texel(x0 + 1, y0, self.wrap_modes);
+        let t01 = level.texel(x0, y0 + 1, self.wrap_modes);
+        let t11 = level.texel(x0 + 1, y0 + 1, self.wrap_modes);
+
+        let mut result = [0.0; 4];
+        for c in 0..4 {
+            result[c] = (1.0 - dx) * (1.0 - dy) * t00[c]
+                + dx * (1.0 - dy) * t10[c]
+                + (1.0 - dx) * dy * t01[c]
+                + dx * dy * t11[c];
         }
+        result
     }
 }
 
 impl Texture for ImageTexture {
     #[allow(unused_variables)]
     fn value(&self, uv: (f64, f64), p: &vec3::Point3) -> Color {
-        if self.data.is_empty() {
-            return Color::new(0.0, 1.0, 1.0);
-        }
+        let texel = self.lookup(uv);
+        Color::new(
+            f64::from(texel[0]),
+            f64::from(texel[1]),
+            f64::from(texel[2]),
+        )
+    }
+}
 
-        let u = clamp(uv.0, (0.0, 1.0));
-        let v = 1.0 - clamp(uv.1, (0.0, 1.0));
+// The alpha channel of an image, e.g. as a cutout mask for `MixMaterial`.
+pub struct ImageAlpha {
+    image: Arc<ImageTexture>,
+}
 
-        let mut i = (u * self.width as f64) as usize;
-        let mut j = (v * self.height as f64) as usize;
+impl ImageAlpha {
+    pub fn new(image: Arc<ImageTexture>) -> Self {
+        Self { image }
+    }
+}
 
-        if i >= self.width {
-            i = self.width - 1;
-        };
-        if j >= self.height {
-            j = self.height - 1;
-        };
+impl ScalarTexture for ImageAlpha {
+    #[allow(unused_variables)]
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.image.alpha(uv)
+    }
+}
 
-        let color = self.data[(j * self.width + i)];
+fn wrap(coordinate: i64, size: usize, wrap_mode: WrapMode) -> usize {
+    let size = size as i64;
+    let wrapped = match wrap_mode {
+        WrapMode::Clamp => coordinate.clamp(0, size - 1),
+        WrapMode::Repeat => coordinate.rem_euclid(size),
+        WrapMode::Mirror => {
+            let period = coordinate.rem_euclid(2 * size);
+            if period < size {
+                period
+            } else {
+                2 * size - 1 - period
+            }
+        }
+    };
+    wrapped as usize
+}
 
-        let scale = 1.0 / 255.0;
+fn decode(bitmap: ImageData) -> Vec<[f32; 4]> {
+    let byte = |v: u8| f32::from(v) / 255.0;
+    let word = |v: u16| f32::from(v) / 65535.0;
 
-        Color::new(
-            f64::from(color.r) * scale,
-            f64::from(color.g) * scale,
-            f64::from(color.b) * scale,
-        )
+    match bitmap {
+        ImageData::RGB8(data) => data
+            .iter()
+            .map(|c| [byte(c.r), byte(c.g), byte(c.b), 1.0])
+            .collect(),
+        ImageData::RGBA8(data) => data
+            .iter()
+            .map(|c| [byte(c.r), byte(c.g), byte(c.b), byte(c.a)])
+            .collect(),
+        ImageData::RGB16(data) => data
+            .iter()
+            .map(|c| [word(c.r), word(c.g), word(c.b), 1.0])
+            .collect(),
+        ImageData::RGBA16(data) => data
+            .iter()
+            .map(|c| [word(c.r), word(c.g), word(c.b), word(c.a)])
+            .collect(),
+        ImageData::GRAY8(data) => data
+            .iter()
+            .map(|c| {
+                let v = byte(c.value());
+                [v, v, v, 1.0]
+            })
+            .collect(),
+        ImageData::GRAY16(data) => data
+            .iter()
+            .map(|c| {
+                let v = word(c.value());
+                [v, v, v, 1.0]
+            })
+            .collect(),
+        ImageData::GRAYA8(data) => data
+            .iter()
+            .map(|c| {
+                let v = byte(c.v);
+                [v, v, v, byte(c.a)]
+            })
+            .collect(),
+        ImageData::GRAYA16(data) => data
+            .iter()
+            .map(|c| {
+                let v = word(c.v);
+                [v, v, v, word(c.a)]
+            })
+            .collect(),
     }
 }
 
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn srgb_texel_to_linear(texel: [f32; 4]) -> [f32; 4] {
+    [
+        srgb_to_linear(texel[0]),
+        srgb_to_linear(texel[1]),
+        srgb_to_linear(texel[2]),
+        texel[3],
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
     use load_image::ImageData::RGB8;
 
+    use super::{super::radiance_hdr, wrap, Filter, ImageTexture, WrapMode};
+
     #[test]
     fn load_image() {
         let path = Path::new("earthmap.jpg");
@@ -85,4 +399,44 @@ mod tests {
         };
         println!("{:?}", data.pop().unwrap());
     }
+
+    #[test]
+    fn missing_image_is_an_error() {
+        assert!(ImageTexture::new("does_not_exist.png").is_err());
+    }
+
+    #[test]
+    fn oversized_hdr_is_an_error() {
+        for resolution in ["-Y 4294967296 +X 4294967296", "-Y 100000 +X 100000"] {
+            let header = format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n{}\n", resolution);
+            assert!(radiance_hdr::read(&mut header.as_bytes()).is_err());
+        }
+    }
+
+    #[test]
+    fn wrap_modes() {
+        assert_eq!(wrap(-1, 4, WrapMode::Clamp), 0);
+        assert_eq!(wrap(5, 4, WrapMode::Clamp), 3);
+        assert_eq!(wrap(-1, 4, WrapMode::Repeat), 3);
+        assert_eq!(wrap(5, 4, WrapMode::Repeat), 1);
+        assert_eq!(wrap(-1, 4, WrapMode::Mirror), 0);
+        assert_eq!(wrap(4, 4, WrapMode::Mirror), 3);
+        assert_eq!(wrap(9, 4, WrapMode::Mirror), 1);
+    }
+
+    #[test]
+    fn prefiltered_levels_average_texels() {
+        let data = vec![
+            [0.0, 0.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0, 1.0],
+            [1.0, 1.0, 1.0, 1.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        let texture =
+            ImageTexture::new_from_data(data, 2, 2, WrapMode::Repeat, Filter::Prefiltered(1.0))
+                .unwrap();
+
+        assert_eq!(texture.levels.len(), 2);
+        assert_eq!(texture.levels[1].data[0], [0.5, 0.5, 0.5, 1.0]);
+    }
 }
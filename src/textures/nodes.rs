@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use crate::vec3::{Color, Point3, Vec3};
+
+use super::{ScalarTexture, Texture};
+
+// Blends `first` into `second` as the mask goes from 0 to 1.
+pub struct MixTexture<T: ?Sized> {
+    first: Arc<T>,
+    second: Arc<T>,
+    mask: Arc<dyn ScalarTexture>,
+}
+
+impl<T: ?Sized> MixTexture<T> {
+    pub fn new(first: Arc<T>, second: Arc<T>, mask: Arc<dyn ScalarTexture>) -> Self {
+        Self {
+            first,
+            second,
+            mask,
+        }
+    }
+}
+
+impl Texture for MixTexture<dyn Texture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> Color {
+        let amount = self.mask.value_with_normal(uv, p, normal).clamp(0.0, 1.0);
+        (1.0 - amount) * self.first.value_with_normal(uv, p, normal)
+            + amount * self.second.value_with_normal(uv, p, normal)
+    }
+}
+
+impl ScalarTexture for MixTexture<dyn ScalarTexture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> f64 {
+        let amount = self.mask.value_with_normal(uv, p, normal).clamp(0.0, 1.0);
+        (1.0 - amount) * self.first.value_with_normal(uv, p, normal)
+            + amount * self.second.value_with_normal(uv, p, normal)
+    }
+}
+
+pub struct MultiplyTexture<T: ?Sized> {
+    first: Arc<T>,
+    second: Arc<T>,
+}
+
+impl<T: ?Sized> MultiplyTexture<T> {
+    pub fn new(first: Arc<T>, second: Arc<T>) -> Self {
+        Self { first, second }
+    }
+}
+
+impl Texture for MultiplyTexture<dyn Texture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> Color {
+        self.first.value_with_normal(uv, p, normal) * self.second.value_with_normal(uv, p, normal)
+    }
+}
+
+impl ScalarTexture for MultiplyTexture<dyn ScalarTexture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> f64 {
+        self.first.value_with_normal(uv, p, normal) * self.second.value_with_normal(uv, p, normal)
+    }
+}
+
+pub struct AddTexture<T: ?Sized> {
+    first: Arc<T>,
+    second: Arc<T>,
+}
+
+impl<T: ?Sized> AddTexture<T> {
+    pub fn new(first: Arc<T>, second: Arc<T>) -> Self {
+        Self { first, second }
+    }
+}
+
+impl Texture for AddTexture<dyn Texture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> Color {
+        self.first.value_with_normal(uv, p, normal) + self.second.value_with_normal(uv, p, normal)
+    }
+}
+
+impl ScalarTexture for AddTexture<dyn ScalarTexture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> f64 {
+        self.first.value_with_normal(uv, p, normal) + self.second.value_with_normal(uv, p, normal)
+    }
+}
+
+// Returns one minus the wrapped texture.
+pub struct InvertTexture<T: ?Sized> {
+    texture: Arc<T>,
+}
+
+impl<T: ?Sized> InvertTexture<T> {
+    pub fn new(texture: Arc<T>) -> Self {
+        Self { texture }
+    }
+}
+
+impl Texture for InvertTexture<dyn Texture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> Color {
+        Color::new(1.0, 1.0, 1.0) - self.texture.value_with_normal(uv, p, normal)
+    }
+}
+
+impl ScalarTexture for InvertTexture<dyn ScalarTexture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> f64 {
+        1.0 - self.texture.value_with_normal(uv, p, normal)
+    }
+}
@@ -1,8 +1,11 @@
 use rand::{thread_rng, Rng};
 
-use crate::vec3::{dot, random_vector_in_range, unit_vector, Color, Point3, Vec3};
+use crate::{
+    noise::Noise,
+    vec3::{dot, random_vector_in_range, unit_vector, Color, Point3, Vec3},
+};
 
-use super::Texture;
+use super::{ScalarTexture, Texture};
 
 #[derive(Default)]
 pub struct NoiseTexture {
@@ -13,9 +16,14 @@ pub struct NoiseTexture {
 impl Texture for NoiseTexture {
     #[allow(unused_variables)]
     fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
-        Color::new(1.0, 1.0, 1.0)
-            * 0.5
-            * (1.0 + (self.scale * p.z() + 10.0 * self.noise.turb(p, 7)).sin())
+        Color::new(1.0, 1.0, 1.0) * self.marble(p)
+    }
+}
+
+impl ScalarTexture for NoiseTexture {
+    #[allow(unused_variables)]
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.marble(p)
     }
 }
 
@@ -26,6 +34,10 @@ impl NoiseTexture {
             scale: sc,
         }
     }
+
+    fn marble(&self, p: &Point3) -> f64 {
+        0.5 * (1.0 + (self.scale * p.z() + 10.0 * self.noise.turb(p, 7)).sin())
+    }
 }
 
 pub struct Perlin {
@@ -93,6 +105,12 @@ impl Perlin {
     }
 }
 
+impl Noise for Perlin {
+    fn noise(&self, p: &Point3) -> f64 {
+        Perlin::noise(self, p)
+    }
+}
+
 impl Default for Perlin {
     fn default() -> Self {
         Self::new()
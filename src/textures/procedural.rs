@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use crate::{
+    noise::{
+        fractal::{Fractal, FractalKind},
+        gradient::GradientNoise,
+        warp::DomainWarp,
+        worley::{WorleyMode, WorleyNoise},
+        Noise,
+    },
+    vec3::{Color, Point3},
+};
+
+use super::{ScalarTexture, Texture};
+
+fn mix(t: f64, a: Color, b: Color) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    (1.0 - t) * a + t * b
+}
+
+// Any noise as a scalar texture, remapped from [-1, 1] to [0, 1].
+pub struct NoiseScalar {
+    noise: Arc<dyn Noise>,
+    scale: f64,
+}
+
+impl NoiseScalar {
+    pub fn new(noise: Arc<dyn Noise>, scale: f64) -> Self {
+        Self { noise, scale }
+    }
+}
+
+impl ScalarTexture for NoiseScalar {
+    #[allow(unused_variables)]
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        (0.5 * (1.0 + self.noise.noise(&(self.scale * *p)))).clamp(0.0, 1.0)
+    }
+}
+
+// Veins along the x axis, bent by turbulence.
+pub struct MarbleTexture {
+    base: Color,
+    vein: Color,
+    scale: f64,
+    turbulence: f64,
+    noise: Fractal,
+}
+
+impl MarbleTexture {
+    pub fn new(base: Color, vein: Color, scale: f64, turbulence: f64, seed: u64) -> Self {
+        Self {
+            base,
+            vein,
+            scale,
+            turbulence,
+            noise: Fractal::new(
+                Arc::new(GradientNoise::new(seed)),
+                FractalKind::Billow,
+                6,
+                2.0,
+                0.5,
+            ),
+        }
+    }
+}
+
+impl Texture for MarbleTexture {
+    #[allow(unused_variables)]
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        let q = self.scale * *p;
+        let turbulence = 0.5 * (1.0 + self.noise.noise(&q));
+        let stripes = (q.x() + self.turbulence * turbulence).sin();
+        mix((1.0 - stripes.abs()).powi(3), self.base, self.vein)
+    }
+}
+
+// Concentric growth rings around the y axis with noisy grain.
+pub struct WoodTexture {
+    light: Color,
+    dark: Color,
+    ring_frequency: f64,
+    grain: f64,
+    noise: DomainWarp,
+}
+
+impl WoodTexture {
+    pub fn new(light: Color, dark: Color, ring_frequency: f64, grain: f64, seed: u64) -> Self {
+        let source = Arc::new(GradientNoise::new(seed));
+        let warp = Arc::new(Fractal::new_fbm(
+            Arc::new(GradientNoise::new(seed.wrapping_add(1))),
+            3,
+        ));
+        Self {
+            light,
+            dark,
+            ring_frequency,
+            grain,
+            noise: DomainWarp::new(source, warp, 0.5),
+        }
+    }
+}
+
+impl Texture for WoodTexture {
+    #[allow(unused_variables)]
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        let radius = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        let rings = radius * self.ring_frequency + self.grain * self.noise.noise(p);
+        let t = rings - rings.floor();
+        mix(t * t * (3.0 - 2.0 * t), self.light, self.dark)
+    }
+}
+
+// Crystals from cellular noise, sprinkled with fine dark specks.
+pub struct GraniteTexture {
+    base: Color,
+    crystal: Color,
+    speck: Color,
+    scale: f64,
+    cells: WorleyNoise,
+    specks: Fractal,
+}
+
+impl GraniteTexture {
+    pub fn new(base: Color, crystal: Color, speck: Color, scale: f64, seed: u64) -> Self {
+        Self {
+            base,
+            crystal,
+            speck,
+            scale,
+            cells: WorleyNoise::new(seed, WorleyMode::F2MinusF1, 1.0),
+            specks: Fractal::new_fbm(Arc::new(GradientNoise::new(seed.wrapping_add(1))), 4),
+        }
+    }
+}
+
+impl Texture for GraniteTexture {
+    #[allow(unused_variables)]
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        let q = self.scale * *p;
+        let edges = self.cells.noise(&q);
+        let color = mix(edges * 2.0, self.crystal, self.base);
+
+        let speck = self.specks.noise(&(4.0 * q));
+        if speck > 0.35 {
+            self.speck
+        } else {
+            color
+        }
+    }
+}
+
+// Thresholded fBm; `coverage` in [0, 1] controls how much sky is covered.
+pub struct CloudTexture {
+    sky: Color,
+    cloud: Color,
+    scale: f64,
+    coverage: f64,
+    noise: Fractal,
+}
+
+impl CloudTexture {
+    pub fn new(sky: Color, cloud: Color, scale: f64, coverage: f64, seed: u64) -> Self {
+        Self {
+            sky,
+            cloud,
+            scale,
+            coverage,
+            noise: Fractal::new_fbm(Arc::new(GradientNoise::new(seed)), 6),
+        }
+    }
+}
+
+impl Texture for CloudTexture {
+    #[allow(unused_variables)]
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        let density = 0.5 * (1.0 + self.noise.noise(&(self.scale * *p)));
+        let threshold = 1.0 - self.coverage;
+        let t = ((density - threshold) / (1.0 - threshold).max(1e-6)).clamp(0.0, 1.0);
+        mix(t.sqrt(), self.sky, self.cloud)
+    }
+}
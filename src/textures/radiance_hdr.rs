@@ -0,0 +1,148 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use super::image_texture::ImageTextureError;
+
+// Largest image accepted, so that a corrupt header cannot ask for more
+// memory than any real image needs.
+const MAX_TEXELS: usize = 1 << 28;
+
+pub fn is_hdr(path: &Path) -> Result<bool, ImageTextureError> {
+    let mut magic = [0; 2];
+    let mut file = File::open(path)?;
+    if file.read(&mut magic)? < 2 {
+        return Ok(false);
+    }
+    Ok(&magic == b"#?")
+}
+
+// Reads a Radiance RGBE image into linear RGBA texels, rows top to bottom.
+pub fn load(path: &Path) -> Result<(Vec<[f32; 4]>, usize, usize), ImageTextureError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    read(&mut reader)
+}
+
+pub fn read(reader: &mut impl BufRead) -> Result<(Vec<[f32; 4]>, usize, usize), ImageTextureError> {
+    let format_error = |message: &str| ImageTextureError::Format(message.to_string());
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if !line.starts_with("#?") {
+        return Err(format_error("missing Radiance header"));
+    }
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(format_error("unterminated header"));
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            break;
+        }
+        if let Some(format) = trimmed.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(format_error("only RGBE pixel data is supported"));
+            }
+        }
+    }
+
+    line.clear();
+    reader.read_line(&mut line)?;
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let (height, width) = match tokens.as_slice() {
+        ["-Y", height, "+X", width] => (height.parse::<usize>(), width.parse::<usize>()),
+        _ => return Err(format_error("only -Y H +X W orientation is supported")),
+    };
+    let (height, width) = match (height, width) {
+        (Ok(height), Ok(width)) if height > 0 && width > 0 => (height, width),
+        _ => return Err(format_error("invalid resolution")),
+    };
+
+    let texels = width
+        .checked_mul(height)
+        .filter(|texels| *texels <= MAX_TEXELS)
+        .ok_or_else(|| format_error("resolution too large"))?;
+
+    let mut data = Vec::with_capacity(texels);
+    let mut scanline = vec![[0u8; 4]; width];
+    for _ in 0..height {
+        read_scanline(reader, &mut scanline)?;
+        data.extend(scanline.iter().map(rgbe_to_texel));
+    }
+
+    Ok((data, width, height))
+}
+
+fn read_scanline(
+    reader: &mut impl Read,
+    scanline: &mut [[u8; 4]],
+) -> Result<(), ImageTextureError> {
+    let width = scanline.len();
+    let mut first = [0u8; 4];
+    reader.read_exact(&mut first)?;
+
+    let is_rle = (8..0x8000).contains(&width)
+        && first[0] == 2
+        && first[1] == 2
+        && first[2] & 0x80 == 0
+        && ((usize::from(first[2]) << 8) | usize::from(first[3])) == width;
+
+    if !is_rle {
+        scanline[0] = first;
+        for pixel in scanline.iter_mut().skip(1) {
+            reader.read_exact(pixel)?;
+        }
+        return Ok(());
+    }
+
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let mut count = [0u8; 1];
+            reader.read_exact(&mut count)?;
+            let count = usize::from(count[0]);
+
+            if count > 128 {
+                let run = count - 128;
+                if x + run > width {
+                    return Err(ImageTextureError::Format("bad scanline run".to_string()));
+                }
+                let mut value = [0u8; 1];
+                reader.read_exact(&mut value)?;
+                for pixel in &mut scanline[x..x + run] {
+                    pixel[channel] = value[0];
+                }
+                x += run;
+            } else {
+                if count == 0 || x + count > width {
+                    return Err(ImageTextureError::Format("bad scanline data".to_string()));
+                }
+                let mut values = vec![0u8; count];
+                reader.read_exact(&mut values)?;
+                for (pixel, value) in scanline[x..x + count].iter_mut().zip(values) {
+                    pixel[channel] = value;
+                }
+                x += count;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn rgbe_to_texel(rgbe: &[u8; 4]) -> [f32; 4] {
+    if rgbe[3] == 0 {
+        return [0.0, 0.0, 0.0, 1.0];
+    }
+    let scale = 2f32.powi(i32::from(rgbe[3]) - (128 + 8));
+    [
+        f32::from(rgbe[0]) * scale,
+        f32::from(rgbe[1]) * scale,
+        f32::from(rgbe[2]) * scale,
+        1.0,
+    ]
+}
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use crate::vec3::{Color, Point3, Vec3};
+
+use super::{ScalarTexture, Texture};
+
+#[derive(Debug, Default)]
+pub struct ConstantScalar {
+    value: f64,
+}
+
+impl ConstantScalar {
+    pub fn new(value: f64) -> Self {
+        Self { value }
+    }
+}
+
+impl ScalarTexture for ConstantScalar {
+    #[allow(unused_variables)]
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.value
+    }
+}
+
+// Reduces a color texture to a scalar by its Rec. 709 luminance.
+pub struct Luminance {
+    texture: Arc<dyn Texture>,
+}
+
+impl Luminance {
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self { texture }
+    }
+}
+
+impl ScalarTexture for Luminance {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> f64 {
+        luminance(&self.texture.value_with_normal(uv, p, normal))
+    }
+}
+
+// Broadcasts a scalar texture to all three color channels.
+pub struct Grayscale {
+    texture: Arc<dyn ScalarTexture>,
+}
+
+impl Grayscale {
+    pub fn new(texture: Arc<dyn ScalarTexture>) -> Self {
+        Self { texture }
+    }
+}
+
+impl Texture for Grayscale {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> Color {
+        let value = self.texture.value_with_normal(uv, p, normal);
+        Color::new(value, value, value)
+    }
+}
+
+// Picks the red, green or blue channel of a color texture, as packed
+// material maps store separate parameters in them.
+pub struct Channel {
+    texture: Arc<dyn Texture>,
+    channel: usize,
+}
+
+impl Channel {
+    pub fn new(texture: Arc<dyn Texture>, channel: usize) -> Self {
+        Self {
+            texture,
+            channel: channel.min(2),
+        }
+    }
+}
+
+impl ScalarTexture for Channel {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> f64 {
+        self.texture.value_with_normal(uv, p, normal)[self.channel]
+    }
+}
+
+pub fn luminance(c: &Color) -> f64 {
+    0.2126 * c.x() + 0.7152 * c.y() + 0.0722 * c.z()
+}
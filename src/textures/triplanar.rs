@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use crate::vec3::{Color, Point3, Vec3};
+
+use super::{ScalarTexture, Texture};
+
+// Projects the wrapped texture along the three world axes, using the position
+// scaled by `scale` as uv coordinates, and blends the projections by the
+// surface normal raised to `sharpness`.
+pub struct TriplanarTexture<T: ?Sized> {
+    texture: Arc<T>,
+    scale: f64,
+    sharpness: f64,
+}
+
+impl<T: ?Sized> TriplanarTexture<T> {
+    pub fn new(texture: Arc<T>, scale: f64, sharpness: f64) -> Self {
+        Self {
+            texture,
+            scale,
+            sharpness,
+        }
+    }
+
+    fn projections(&self, p: &Point3, normal: &Vec3) -> [((f64, f64), f64); 3] {
+        let mut weights = [
+            normal.x().abs().powf(self.sharpness),
+            normal.y().abs().powf(self.sharpness),
+            normal.z().abs().powf(self.sharpness),
+        ];
+        let total: f64 = weights.iter().sum();
+        if total > 0.0 {
+            weights.iter_mut().for_each(|w| *w /= total);
+        } else {
+            weights = [1.0 / 3.0; 3];
+        }
+
+        let q = self.scale * *p;
+        [
+            ((q.y(), q.z()), weights[0]),
+            ((q.x(), q.z()), weights[1]),
+            ((q.x(), q.y()), weights[2]),
+        ]
+    }
+}
+
+impl Texture for TriplanarTexture<dyn Texture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    #[allow(unused_variables)]
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> Color {
+        self.projections(p, normal)
+            .iter()
+            .filter(|(_, weight)| *weight > 0.0)
+            .fold(Color::default(), |acc, (uv, weight)| {
+                acc + *weight * self.texture.value_with_normal(*uv, p, normal)
+            })
+    }
+}
+
+impl ScalarTexture for TriplanarTexture<dyn ScalarTexture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.value_with_normal(uv, p, &Vec3::default())
+    }
+
+    #[allow(unused_variables)]
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> f64 {
+        self.projections(p, normal)
+            .iter()
+            .filter(|(_, weight)| *weight > 0.0)
+            .map(|(uv, weight)| weight * self.texture.value_with_normal(*uv, p, normal))
+            .sum()
+    }
+}
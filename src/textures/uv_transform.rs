@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use crate::{
+    degrees_to_radians,
+    vec3::{Color, Point3, Vec3},
+};
+
+use super::{ScalarTexture, Texture};
+
+// Scales, rotates (in degrees, around the uv origin) and then offsets the
+// surface coordinates before looking up the wrapped texture.
+pub struct UvTransform<T: ?Sized> {
+    texture: Arc<T>,
+    scale: (f64, f64),
+    offset: (f64, f64),
+    sin_theta: f64,
+    cos_theta: f64,
+}
+
+impl<T: ?Sized> UvTransform<T> {
+    pub fn new(texture: Arc<T>, scale: (f64, f64), offset: (f64, f64), rotation: f64) -> Self {
+        let radians = degrees_to_radians(rotation);
+        Self {
+            texture,
+            scale,
+            offset,
+            sin_theta: radians.sin(),
+            cos_theta: radians.cos(),
+        }
+    }
+
+    fn transform(&self, uv: (f64, f64)) -> (f64, f64) {
+        let u = uv.0 * self.scale.0;
+        let v = uv.1 * self.scale.1;
+
+        (
+            self.cos_theta * u - self.sin_theta * v + self.offset.0,
+            self.sin_theta * u + self.cos_theta * v + self.offset.1,
+        )
+    }
+}
+
+impl Texture for UvTransform<dyn Texture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        self.texture.value(self.transform(uv), p)
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> Color {
+        self.texture
+            .value_with_normal(self.transform(uv), p, normal)
+    }
+}
+
+impl ScalarTexture for UvTransform<dyn ScalarTexture> {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> f64 {
+        self.texture.value(self.transform(uv), p)
+    }
+
+    fn value_with_normal(&self, uv: (f64, f64), p: &Point3, normal: &Vec3) -> f64 {
+        self.texture
+            .value_with_normal(self.transform(uv), p, normal)
+    }
+}
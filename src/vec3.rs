@@ -1,4 +1,7 @@
-use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub};
+use std::{
+    f64::consts::{FRAC_PI_2, FRAC_PI_4, PI},
+    ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub},
+};
 
 use crate::{random_f64, random_f64_between};
 
@@ -236,3 +239,31 @@ impl IndexMut<usize> for Vec3 {
         }
     }
 }
+
+pub fn sample_uniform_disk(u: (f64, f64)) -> Vec3 {
+    let offset = (2.0 * u.0 - 1.0, 2.0 * u.1 - 1.0);
+    if offset.0 == 0.0 && offset.1 == 0.0 {
+        return Vec3::default();
+    }
+
+    let (r, theta) = if offset.0.abs() > offset.1.abs() {
+        (offset.0, FRAC_PI_4 * (offset.1 / offset.0))
+    } else {
+        (offset.1, FRAC_PI_2 - FRAC_PI_4 * (offset.0 / offset.1))
+    };
+
+    Vec3::new(r * theta.cos(), r * theta.sin(), 0.0)
+}
+
+pub fn sample_cosine_hemisphere(u: (f64, f64)) -> Vec3 {
+    let d = sample_uniform_disk(u);
+    let z = (1.0 - d.x * d.x - d.y * d.y).max(0.0).sqrt();
+    Vec3::new(d.x, d.y, z)
+}
+
+pub fn sample_uniform_sphere(u: (f64, f64)) -> Vec3 {
+    let z = 1.0 - 2.0 * u.0;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * PI * u.1;
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
@@ -0,0 +1,21 @@
+pub mod grid_density;
+pub mod majorant_grid;
+pub mod noise_density;
+pub mod vol_file;
+
+use crate::{ray::Ray, vec3::Point3};
+
+// Spatially varying extinction coefficient of a participating medium.
+pub trait DensityField: Send + Sync {
+    fn density(&self, p: &Point3) -> f64;
+
+    // An upper bound of `density` everywhere, used as the tracking majorant.
+    fn max_density(&self) -> f64;
+
+    // Splits `interval` of `r` into `(t_start, t_end, majorant)` pieces. Parts
+    // where the density is known to be zero may be left out.
+    #[allow(unused_variables)]
+    fn majorants(&self, r: &Ray, interval: (f64, f64)) -> Vec<(f64, f64, f64)> {
+        vec![(interval.0, interval.1, self.max_density())]
+    }
+}
@@ -0,0 +1,188 @@
+use std::path::Path;
+
+use crate::{
+    hits::aabb::AABB,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    majorant_grid::MajorantGrid,
+    vol_file::{self, VolumeError},
+    DensityField,
+};
+
+// Voxels per majorant cell along each axis.
+const MAJORANT_CELL_SIZE: usize = 8;
+
+// A dense voxel grid spanning `bounds`, with samples at the voxel centers and
+// trilinear interpolation in between. Data is stored x fastest, then y, then z.
+pub struct GridDensity {
+    bounds: AABB,
+    resolution: (usize, usize, usize),
+    data: Vec<f32>,
+    scale: f64,
+    majorants: MajorantGrid,
+    max: f64,
+}
+
+impl GridDensity {
+    // `data` needs one value per voxel, and neither the resolution nor the
+    // bounds may be empty along any axis.
+    pub fn new(
+        bounds: AABB,
+        resolution: (usize, usize, usize),
+        data: Vec<f32>,
+        scale: f64,
+    ) -> Result<Self, VolumeError> {
+        let format_error = |message: &str| VolumeError::Format(message.to_string());
+        let (nx, ny, nz) = resolution;
+        if nx == 0 || ny == 0 || nz == 0 {
+            return Err(format_error("invalid resolution"));
+        }
+        if (0..3).any(|a| bounds.max()[a] <= bounds.min()[a]) {
+            return Err(format_error("empty bounding box"));
+        }
+        let length = nx
+            .checked_mul(ny)
+            .and_then(|length| length.checked_mul(nz))
+            .ok_or_else(|| format_error("resolution too large"))?;
+        if data.len() != length {
+            return Err(format_error("voxel data does not match the resolution"));
+        }
+
+        let mut grid = Self {
+            bounds,
+            resolution,
+            data,
+            scale,
+            majorants: MajorantGrid::new(bounds, (1, 1, 1), vec![0.0]),
+            max: 0.0,
+        };
+        grid.majorants = grid.build_majorants();
+        grid.max = grid.majorants.max();
+        Ok(grid)
+    }
+
+    // Loads a Mitsuba `.vol` file, see `vol_file`.
+    pub fn new_from_file(path: &str, scale: f64) -> Result<Self, VolumeError> {
+        let (bounds, resolution, data) = vol_file::load(Path::new(path))?;
+        Self::new(bounds, resolution, data, scale)
+    }
+
+    pub fn bounds(&self) -> AABB {
+        self.bounds
+    }
+
+    pub fn resolution(&self) -> (usize, usize, usize) {
+        self.resolution
+    }
+
+    pub fn voxel(&self, x: usize, y: usize, z: usize) -> f64 {
+        let (nx, ny, _) = self.resolution;
+        f64::from(self.data[(z * ny + y) * nx + x])
+    }
+
+    // Maps a world position into continuous voxel coordinates.
+    pub fn to_grid(&self, p: &Point3) -> Vec3 {
+        let extent = self.bounds.max() - self.bounds.min();
+        let local = *p - self.bounds.min();
+        Vec3::new(
+            local.x() / extent.x() * self.resolution.0 as f64,
+            local.y() / extent.y() * self.resolution.1 as f64,
+            local.z() / extent.z() * self.resolution.2 as f64,
+        )
+    }
+
+    // Each majorant cell bounds every voxel whose trilinear footprint reaches
+    // into it, i.e. the voxels it covers plus a one voxel margin.
+    fn build_majorants(&self) -> MajorantGrid {
+        let (nx, ny, nz) = self.resolution;
+        let cells = |n: usize| n.div_ceil(MAJORANT_CELL_SIZE);
+        let (mx, my, mz) = (cells(nx), cells(ny), cells(nz));
+        let range = |cell: usize, cells: usize, n: usize| {
+            let start = (cell * n / cells).saturating_sub(1);
+            let end = ((cell + 1) * n).div_ceil(cells).min(n - 1);
+            start..=end
+        };
+
+        let mut data = Vec::with_capacity(mx * my * mz);
+        for cz in 0..mz {
+            for cy in 0..my {
+                for cx in 0..mx {
+                    let mut max = 0.0f64;
+                    for z in range(cz, mz, nz) {
+                        for y in range(cy, my, ny) {
+                            for x in range(cx, mx, nx) {
+                                max = max.max(self.voxel(x, y, z));
+                            }
+                        }
+                    }
+                    data.push(self.scale * max);
+                }
+            }
+        }
+
+        MajorantGrid::new(self.bounds, (mx, my, mz), data)
+    }
+
+    fn lookup(&self, x: i64, y: i64, z: i64) -> f64 {
+        let (nx, ny, nz) = self.resolution;
+        if x < 0 || y < 0 || z < 0 || x >= nx as i64 || y >= ny as i64 || z >= nz as i64 {
+            return 0.0;
+        }
+        self.voxel(x as usize, y as usize, z as usize)
+    }
+}
+
+impl DensityField for GridDensity {
+    fn density(&self, p: &Point3) -> f64 {
+        let (min, max) = (self.bounds.min(), self.bounds.max());
+        if (0..3).any(|a| p[a] < min[a] || p[a] > max[a]) {
+            return 0.0;
+        }
+
+        let g = self.to_grid(p) - Vec3::new(0.5, 0.5, 0.5);
+        let (x0, y0, z0) = (g.x().floor(), g.y().floor(), g.z().floor());
+        let (dx, dy, dz) = (g.x() - x0, g.y() - y0, g.z() - z0);
+        let (x0, y0, z0) = (x0 as i64, y0 as i64, z0 as i64);
+
+        let mut result = 0.0;
+        for (i, wx) in [(0, 1.0 - dx), (1, dx)] {
+            for (j, wy) in [(0, 1.0 - dy), (1, dy)] {
+                for (k, wz) in [(0, 1.0 - dz), (1, dz)] {
+                    result += wx * wy * wz * self.lookup(x0 + i, y0 + j, z0 + k);
+                }
+            }
+        }
+
+        self.scale * result
+    }
+
+    fn max_density(&self) -> f64 {
+        self.max
+    }
+
+    fn majorants(&self, r: &Ray, interval: (f64, f64)) -> Vec<(f64, f64, f64)> {
+        self.majorants.segments(r, interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hits::aabb::AABB, vec3::Point3};
+
+    use super::GridDensity;
+
+    #[test]
+    fn invalid_grids_are_errors() {
+        let bounds = AABB::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        assert!(GridDensity::new(bounds, (2, 2, 2), vec![1.0; 8], 1.0).is_ok());
+        assert!(GridDensity::new(bounds, (2, 2, 2), vec![1.0; 7], 1.0).is_err());
+        assert!(GridDensity::new(bounds, (0, 2, 2), vec![], 1.0).is_err());
+        assert!(GridDensity::new(bounds, (usize::MAX, 2, 2), vec![], 1.0).is_err());
+
+        let flat = AABB::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 1.0));
+        assert!(GridDensity::new(flat, (2, 2, 2), vec![1.0; 8], 1.0).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{hits::aabb::AABB, ray::Ray};
+
+// A coarse grid of density upper bounds over `bounds`. Walking it along a ray
+// gives tight local majorants, so tracking takes fewer null collisions in
+// sparse regions than with a single global bound.
+pub struct MajorantGrid {
+    bounds: AABB,
+    resolution: (usize, usize, usize),
+    data: Vec<f64>,
+}
+
+impl MajorantGrid {
+    pub fn new(bounds: AABB, resolution: (usize, usize, usize), data: Vec<f64>) -> Self {
+        assert_eq!(
+            data.len(),
+            resolution.0 * resolution.1 * resolution.2,
+            "majorant data does not match its resolution"
+        );
+        Self {
+            bounds,
+            resolution,
+            data,
+        }
+    }
+
+    pub fn resolution(&self) -> (usize, usize, usize) {
+        self.resolution
+    }
+
+    pub fn max(&self) -> f64 {
+        self.data.iter().fold(0.0, |acc, &m| acc.max(m))
+    }
+
+    pub fn cell(&self, x: usize, y: usize, z: usize) -> f64 {
+        let (nx, ny, _) = self.resolution;
+        self.data[(z * ny + y) * nx + x]
+    }
+
+    // Splits `interval` of `r` into `(t_start, t_end, majorant)` pieces, one per
+    // run of cells with the same bound, with a 3D DDA. Parts of the ray outside
+    // the grid are left out.
+    pub fn segments(&self, r: &Ray, interval: (f64, f64)) -> Vec<(f64, f64, f64)> {
+        let (min, max) = (self.bounds.min(), self.bounds.max());
+        let (origin, direction) = (r.origin(), r.direction());
+
+        let (mut t_min, mut t_max) = interval;
+        for a in 0..3 {
+            let inv_d = 1.0 / direction[a];
+            let mut t0 = (min[a] - origin[a]) * inv_d;
+            let mut t1 = (max[a] - origin[a]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+        }
+        if t_min >= t_max {
+            return vec![];
+        }
+
+        let resolution = [self.resolution.0, self.resolution.1, self.resolution.2];
+        let start = r.at(t_min);
+        let mut cell = [0usize; 3];
+        let mut step = [0isize; 3];
+        let mut next_t = [f64::INFINITY; 3];
+        let mut delta_t = [f64::INFINITY; 3];
+
+        for a in 0..3 {
+            let cell_size = (max[a] - min[a]) / resolution[a] as f64;
+            let index = ((start[a] - min[a]) / cell_size).floor().max(0.0) as usize;
+            cell[a] = index.min(resolution[a] - 1);
+
+            if direction[a] > 0.0 {
+                step[a] = 1;
+                let boundary = min[a] + (cell[a] + 1) as f64 * cell_size;
+                next_t[a] = t_min + (boundary - start[a]) / direction[a];
+                delta_t[a] = cell_size / direction[a];
+            } else if direction[a] < 0.0 {
+                step[a] = -1;
+                let boundary = min[a] + cell[a] as f64 * cell_size;
+                next_t[a] = t_min + (boundary - start[a]) / direction[a];
+                delta_t[a] = -cell_size / direction[a];
+            }
+        }
+
+        let mut segments: Vec<(f64, f64, f64)> = vec![];
+        let mut t = t_min;
+        loop {
+            let axis = if next_t[0] < next_t[1] && next_t[0] < next_t[2] {
+                0
+            } else if next_t[1] < next_t[2] {
+                1
+            } else {
+                2
+            };
+            let end = next_t[axis].min(t_max);
+            let majorant = self.cell(cell[0], cell[1], cell[2]);
+
+            match segments.last_mut() {
+                Some(last) if last.2 == majorant => last.1 = end,
+                _ => segments.push((t, end, majorant)),
+            }
+
+            if next_t[axis] >= t_max {
+                break;
+            }
+            match cell[axis].checked_add_signed(step[axis]) {
+                Some(index) if index < resolution[axis] => cell[axis] = index,
+                _ => break,
+            }
+            t = next_t[axis];
+            next_t[axis] += delta_t[axis];
+        }
+
+        segments
+    }
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use crate::{noise::Noise, vec3::Point3};
+
+use super::DensityField;
+
+// Density from a noise field remapped to [0, 1]. Values below `offset` are
+// cut away and the rest is stretched back to [0, density].
+pub struct NoiseDensity {
+    noise: Arc<dyn Noise>,
+    scale: f64,
+    offset: f64,
+    density: f64,
+}
+
+impl NoiseDensity {
+    pub fn new(noise: Arc<dyn Noise>, scale: f64, offset: f64, density: f64) -> Self {
+        Self {
+            noise,
+            scale,
+            offset,
+            density,
+        }
+    }
+}
+
+impl DensityField for NoiseDensity {
+    fn density(&self, p: &Point3) -> f64 {
+        let n = 0.5 * (1.0 + self.noise.noise(&(self.scale * *p)));
+        self.density * ((n - self.offset) / (1.0 - self.offset)).clamp(0.0, 1.0)
+    }
+
+    fn max_density(&self) -> f64 {
+        self.density
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{hits::aabb::AABB, vec3::Point3};
+
+// Reader and writer for the Mitsuba `.vol` grid format. A file is the bytes
+// "VOL" and version 3, then little endian i32 encoding (1 = f32, 3 = u8),
+// x, y and z resolution and channel count, six f32 for the bounding box
+// minimum and maximum, and finally the voxels, x fastest, then y, then z.
+// Multi-channel voxels are averaged into a single density.
+
+const FLOAT32: i32 = 1;
+const UINT8: i32 = 3;
+
+#[derive(Debug)]
+pub enum VolumeError {
+    Io(io::Error),
+    Format(String),
+}
+
+impl fmt::Display for VolumeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VolumeError::Io(error) => write!(f, "could not read volume: {error}"),
+            VolumeError::Format(message) => write!(f, "invalid volume: {message}"),
+        }
+    }
+}
+
+impl Error for VolumeError {}
+
+impl From<io::Error> for VolumeError {
+    fn from(error: io::Error) -> Self {
+        VolumeError::Io(error)
+    }
+}
+
+pub type VolumeData = (AABB, (usize, usize, usize), Vec<f32>);
+
+pub fn load(path: &Path) -> Result<VolumeData, VolumeError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    read(&mut reader)
+}
+
+pub fn read(reader: &mut impl Read) -> Result<VolumeData, VolumeError> {
+    let format_error = |message: &str| VolumeError::Format(message.to_string());
+
+    let mut header = [0u8; 4];
+    reader.read_exact(&mut header)?;
+    if &header[..3] != b"VOL" {
+        return Err(format_error("missing VOL header"));
+    }
+    if header[3] != 3 {
+        return Err(format_error("only version 3 is supported"));
+    }
+
+    let encoding = read_i32(reader)?;
+    let mut dimensions = [0usize; 4];
+    for dimension in &mut dimensions {
+        *dimension = match usize::try_from(read_i32(reader)?) {
+            Ok(value) if value > 0 => value,
+            _ => return Err(format_error("invalid resolution")),
+        };
+    }
+    let [nx, ny, nz, channels] = dimensions;
+
+    let mut corners = [0.0; 6];
+    for corner in &mut corners {
+        *corner = f64::from(read_f32(reader)?);
+    }
+    let bounds = AABB::new(
+        Point3::new(corners[0], corners[1], corners[2]),
+        Point3::new(corners[3], corners[4], corners[5]),
+    );
+    if (0..3).any(|a| bounds.max()[a] <= bounds.min()[a]) {
+        return Err(format_error("empty bounding box"));
+    }
+
+    let value_size = match encoding {
+        FLOAT32 => 4,
+        UINT8 => 1,
+        _ => return Err(format_error("only f32 and u8 encodings are supported")),
+    };
+    let length = [ny, nz, channels, value_size]
+        .into_iter()
+        .try_fold(nx, usize::checked_mul)
+        .ok_or_else(|| format_error("resolution too large"))?;
+
+    // Reads what is there rather than allocating what the header claims, so
+    // a corrupt resolution fails on the size check instead of the allocator.
+    let mut bytes = vec![];
+    reader.take(length as u64 + 1).read_to_end(&mut bytes)?;
+    if bytes.len() != length {
+        return Err(format_error("voxel data does not match the resolution"));
+    }
+    let values: Vec<f32> = if encoding == FLOAT32 {
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect()
+    } else {
+        bytes.into_iter().map(|b| f32::from(b) / 255.0).collect()
+    };
+
+    let data = values
+        .chunks_exact(channels)
+        .map(|voxel| voxel.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    Ok((bounds, (nx, ny, nz), data))
+}
+
+pub fn save(path: &Path, volume: &VolumeData) -> Result<(), VolumeError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer, volume)?;
+    writer.flush()?;
+    Ok(())
+}
+
+// Writes a single channel f32 grid.
+pub fn write(writer: &mut impl Write, volume: &VolumeData) -> Result<(), VolumeError> {
+    let (bounds, (nx, ny, nz), data) = volume;
+    if data.len() != nx * ny * nz {
+        return Err(VolumeError::Format(
+            "data does not match its resolution".to_string(),
+        ));
+    }
+
+    writer.write_all(b"VOL\x03")?;
+    for value in [FLOAT32, *nx as i32, *ny as i32, *nz as i32, 1] {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    for corner in [bounds.min(), bounds.max()] {
+        for a in 0..3 {
+            writer.write_all(&(corner[a] as f32).to_le_bytes())?;
+        }
+    }
+    for value in data {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn read_i32(reader: &mut impl Read) -> Result<i32, VolumeError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(i32::from_le_bytes(bytes))
+}
+
+fn read_f32(reader: &mut impl Read) -> Result<f32, VolumeError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hits::aabb::AABB, vec3::Point3};
+
+    use super::{read, write};
+
+    #[test]
+    fn round_trip() {
+        let bounds = AABB::new(Point3::new(-1.0, 0.0, 2.0), Point3::new(1.0, 3.0, 4.0));
+        let data: Vec<f32> = (0..24).map(|i| i as f32 * 0.5).collect();
+
+        let mut bytes = vec![];
+        write(&mut bytes, &(bounds, (2, 3, 4), data.clone())).unwrap();
+        let (read_bounds, resolution, read_data) = read(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(resolution, (2, 3, 4));
+        assert_eq!(read_data, data);
+        assert_eq!(read_bounds.min().y(), 0.0);
+        assert_eq!(read_bounds.max().z(), 4.0);
+    }
+
+    #[test]
+    fn truncated_file_is_an_error() {
+        let bounds = AABB::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        let mut bytes = vec![];
+        write(&mut bytes, &(bounds, (2, 2, 2), vec![1.0; 8])).unwrap();
+        bytes.truncate(bytes.len() - 1);
+
+        assert!(read(&mut bytes.as_slice()).is_err());
+        assert!(read(&mut &b"NOPE"[..]).is_err());
+
+        // A resolution whose size overflows, and one far larger than the data.
+        for resolution in [i32::MAX, 1 << 20] {
+            let mut header = b"VOL\x03".to_vec();
+            for value in [1, resolution, resolution, resolution, 1] {
+                header.extend_from_slice(&i32::to_le_bytes(value));
+            }
+            for value in [0.0f32, 0.0, 0.0, 1.0, 1.0, 1.0] {
+                header.extend_from_slice(&value.to_le_bytes());
+            }
+            header.extend_from_slice(&[0; 16]);
+            assert!(read(&mut header.as_slice()).is_err());
+        }
+    }
+}